@@ -0,0 +1,67 @@
+//! How long the input monitoring thread spends on a single event, fed in through [`MockBackend`].
+//!
+//! Run with `cargo bench --no-default-features`, so that no input is grabbed from the OS.
+use criterion::{criterion_group, criterion_main, Criterion};
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::{
+    backend::MockBackend,
+    events::{create_keybind_channel_with_backend, InputConfig},
+    keybinds::add_keybind,
+};
+use rdev::{
+    Event,
+    EventType::{self, KeyPress, KeyRelease},
+    Key as RDevKey,
+};
+
+use std::{hint::black_box, time::SystemTime};
+
+fn push(event_type: EventType) -> Option<Event> {
+    MockBackend.push_event(Event {
+        time: SystemTime::now(),
+        name: None,
+        event_type,
+    })
+}
+
+fn per_event(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .build()
+        .unwrap();
+    let _rx = {
+        let _entered = runtime.enter();
+        create_keybind_channel_with_backend(InputConfig::default(), Box::new(MockBackend)).unwrap()
+    };
+    // Without notifications, so that the channel never fills up and blocks the benchmark
+    let kb = KeyBinding {
+        key: Some(Key::Other('b')),
+        mods: Modifiers::ODILIA,
+        repeat: 1,
+        consume: true,
+        notify: false,
+        mode: None,
+    };
+    runtime.block_on(add_keybind(kb, || async {}));
+
+    c.bench_function("unbound key", |b| {
+        b.iter(|| {
+            black_box(push(KeyPress(RDevKey::KeyA)));
+            black_box(push(KeyRelease(RDevKey::KeyA)));
+        })
+    });
+    c.bench_function("bound chord", |b| {
+        b.iter(|| {
+            black_box(push(KeyPress(RDevKey::CapsLock)));
+            black_box(push(KeyPress(RDevKey::KeyB)));
+            black_box(push(KeyRelease(RDevKey::KeyB)));
+            black_box(push(KeyRelease(RDevKey::CapsLock)));
+        })
+    });
+    push(KeyPress(RDevKey::KeyA));
+    c.bench_function("auto-repeat", |b| b.iter(|| black_box(push(KeyPress(RDevKey::KeyA)))));
+    push(KeyRelease(RDevKey::KeyA));
+}
+
+criterion_group!(benches, per_event);
+criterion_main!(benches);
@@ -0,0 +1,382 @@
+//! Sources of raw input events.
+//!
+//! A backend feeds every [`Event`] it sees through the keybinding pipeline, and passes on to the
+//! rest of the system whatever the pipeline returns. [`rdev::grab`] is the default backend, or
+//! [`MockBackend`] without the `grab` feature; others can be selected with
+//! [`create_keybind_channel_with_backend`][crate::events::create_keybind_channel_with_backend].
+use rdev::{Event, EventType};
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex, PoisonError,
+};
+
+/// The keybinding pipeline: returns the event if it should be passed through, or `None` if it was
+/// consumed.
+pub type EventHandler = fn(Event) -> Option<Event>;
+
+/// Why an [`InputBackend`] stopped, e.g. missing permissions or no display to grab input from.
+pub type BackendError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Injects an event into the system as if it had been typed; see [`InputBackend::simulator`].
+pub type Simulator = fn(&EventType) -> Result<(), BackendError>;
+
+/// Tells a running [`InputBackend`] that it should stop; see
+/// [`shutdown_input`][crate::events::shutdown_input].
+#[derive(Clone, Debug, Default)]
+pub struct ShutdownSignal(Arc<AtomicBool>);
+
+impl ShutdownSignal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn request(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// A source of raw input events.
+pub trait InputBackend: Send + 'static {
+    /// Feed every input event to `handler` until input ends or `shutdown` is requested.
+    ///
+    /// This runs on a dedicated thread and may block for as long as it likes, but should check
+    /// `shutdown` now and then. Events `handler` returns `None` for should be kept from the rest of
+    /// the system, if the backend is able to. Once shutdown was requested, `handler` passes every
+    /// event through, so a backend which cannot stop early is harmless.
+    ///
+    /// Returns an error if input could not be read (any longer); see
+    /// [`backend_stopped`][crate::events::backend_stopped].
+    fn run(self: Box<Self>, handler: EventHandler, shutdown: ShutdownSignal) -> Result<(), BackendError>;
+
+    /// Whether the backend captures input as soon as [`run`][Self::run] is called. Backends which
+    /// need a while to start capturing should return `false`; they are then considered ready once
+    /// the first event comes through. See [`backend_ready`][crate::events::backend_ready].
+    fn ready_on_start(&self) -> bool {
+        true
+    }
+
+    /// How events are simulated while this backend runs, e.g. for
+    /// [`remap_key`][crate::events::remap_key]. By default, through [`rdev::simulate`].
+    fn simulator(&self) -> Simulator {
+        simulate_with_rdev
+    }
+}
+
+fn simulate_with_rdev(event: &EventType) -> Result<(), BackendError> {
+    // SimulateError implements neither Display nor Error
+    rdev::simulate(event).map_err(|e| format!("could not simulate {:?}: {:?}", event, e).into())
+}
+
+/// The events simulated while a [`MockBackend`] runs, oldest first.
+static MOCK_SIMULATED: Mutex<Vec<EventType>> = Mutex::new(Vec::new());
+
+fn simulate_with_mock(event: &EventType) -> Result<(), BackendError> {
+    MOCK_SIMULATED.lock().unwrap_or_else(PoisonError::into_inner).push(*event);
+    Ok(())
+}
+
+/// A stand-in for an OS input backend, for driving the keybinding pipeline by hand in tests and
+/// headless environments.
+///
+/// Running it does nothing; events are fed in through [`push_event`][Self::push_event] instead.
+/// Without the `grab` feature, this is what the input monitoring system uses by default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MockBackend;
+
+impl MockBackend {
+    /// Feed an [`Event`] through the keybinding pipeline as if it had been grabbed from the OS.
+    ///
+    /// Returns the event if it would have been passed through, or `None` if it would have been
+    /// consumed. The input monitoring system must have been initialised first.
+    ///
+    /// Like the real input monitoring thread, this blocks; call it from a synchronous context
+    /// (e.g. [`tokio::task::spawn_blocking`]), not directly from async code.
+    pub fn push_event(&self, ev: Event) -> Option<Event> {
+        crate::events::process_event(ev)
+    }
+
+    /// Take the events simulated since the last call, oldest first, e.g. those of a
+    /// [`remap_key`][crate::events::remap_key].
+    ///
+    /// Simulated events are only recorded, never injected into the system. An OS would feed them
+    /// back through the input monitoring system; do so with [`push_event`][Self::push_event].
+    pub fn take_simulated(&self) -> Vec<EventType> {
+        std::mem::take(&mut *MOCK_SIMULATED.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+impl InputBackend for MockBackend {
+    fn run(self: Box<Self>, _handler: EventHandler, _shutdown: ShutdownSignal) -> Result<(), BackendError> {
+        Ok(())
+    }
+
+    fn simulator(&self) -> Simulator {
+        simulate_with_mock
+    }
+}
+
+/// Grabs all input through [`rdev::grab`]. This is the default backend.
+#[cfg(feature = "grab")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RdevBackend;
+
+#[cfg(feature = "grab")]
+impl InputBackend for RdevBackend {
+    /* rdev::grab never returns, so a shutdown only makes the handler pass everything through */
+    fn run(self: Box<Self>, handler: EventHandler, _shutdown: ShutdownSignal) -> Result<(), BackendError> {
+        // GrabError implements neither Display nor Error
+        rdev::grab(handler).map_err(|e| format!("could not grab input: {:?}", e).into())
+    }
+
+    /* grabbing the devices takes a moment, and rdev does not tell when it is done */
+    fn ready_on_start(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "libinput")]
+pub use self::libinput::LibinputBackend;
+
+#[cfg(feature = "libinput")]
+mod libinput {
+    use super::{BackendError, EventHandler, InputBackend, ShutdownSignal};
+
+    use input::{
+        event::{keyboard::KeyboardEventTrait, KeyboardEvent},
+        Libinput, LibinputInterface,
+    };
+    use libc::{O_ACCMODE, O_RDONLY, O_RDWR, O_WRONLY};
+    use rdev::{
+        Event,
+        EventType::{KeyPress, KeyRelease},
+        Key as RDevKey,
+    };
+
+    use std::{
+        fs::{File, OpenOptions},
+        os::unix::{
+            fs::OpenOptionsExt,
+            io::{AsRawFd, OwnedFd},
+        },
+        path::{Path, PathBuf},
+        time::SystemTime,
+    };
+
+    /// Reads keyboard input through libinput, for Wayland sessions where [`rdev::grab`] does not
+    /// work.
+    ///
+    /// libinput only observes input: events the keybinding pipeline consumes still reach the
+    /// rest of the system. The process needs read access to the devices, usually by being in the
+    /// `input` group.
+    #[derive(Clone, Debug)]
+    pub struct LibinputBackend {
+        devices: Vec<PathBuf>,
+    }
+
+    impl LibinputBackend {
+        /// Read from every device under `/dev/input`.
+        pub fn new() -> Self {
+            let devices = std::fs::read_dir("/dev/input")
+                .map(|entries| {
+                    entries
+                        .filter_map(Result::ok)
+                        .map(|entry| entry.path())
+                        .filter(|path| {
+                            path.file_name().is_some_and(|name| name.to_string_lossy().starts_with("event"))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            LibinputBackend { devices }
+        }
+
+        /// Read from the given device nodes only, e.g. `/dev/input/event3`.
+        pub fn with_devices(devices: Vec<PathBuf>) -> Self {
+            LibinputBackend { devices }
+        }
+    }
+
+    impl Default for LibinputBackend {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    struct Interface;
+
+    impl LibinputInterface for Interface {
+        fn open_restricted(&mut self, path: &Path, flags: i32) -> Result<OwnedFd, i32> {
+            OpenOptions::new()
+                .custom_flags(flags)
+                .read(flags & O_ACCMODE == O_RDONLY || flags & O_ACCMODE == O_RDWR)
+                .write(flags & O_ACCMODE == O_WRONLY || flags & O_ACCMODE == O_RDWR)
+                .open(path)
+                .map(OwnedFd::from)
+                .map_err(|e| e.raw_os_error().unwrap_or(libc::EIO))
+        }
+
+        fn close_restricted(&mut self, fd: OwnedFd) {
+            drop(File::from(fd));
+        }
+    }
+
+    impl InputBackend for LibinputBackend {
+        fn run(self: Box<Self>, handler: EventHandler, shutdown: ShutdownSignal) -> Result<(), BackendError> {
+            let mut libinput = Libinput::new_from_path(Interface);
+            for device in &self.devices {
+                if libinput.path_add_device(&device.to_string_lossy()).is_none() {
+                    tracing::warn!("Could not open input device {}", device.display());
+                }
+            }
+            let mut pollfd = libc::pollfd {
+                fd: libinput.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            while !shutdown.is_requested() {
+                libinput.dispatch()?;
+                for event in &mut libinput {
+                    if let input::Event::Keyboard(KeyboardEvent::Key(key_event)) = event {
+                        let key = evdev_code_to_rdev_key(key_event.key());
+                        let event_type = match key_event.key_state() {
+                            input::event::keyboard::KeyState::Pressed => KeyPress(key),
+                            input::event::keyboard::KeyState::Released => KeyRelease(key),
+                        };
+                        // The event can't be consumed, so whatever the pipeline decides is moot
+                        let _ = handler(Event {
+                            time: SystemTime::now(),
+                            name: None,
+                            event_type,
+                        });
+                    }
+                }
+                // Wait for the next batch of events, looking up now and then to check for a shutdown
+                if unsafe { libc::poll(&mut pollfd, 1, SHUTDOWN_POLL_MS) } < 0 {
+                    return Err(std::io::Error::last_os_error().into());
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// How long to wait for input before checking whether to shut down, in milliseconds.
+    const SHUTDOWN_POLL_MS: i32 = 200;
+
+    /// Translate a Linux evdev key code (`KEY_*` in `linux/input-event-codes.h`) to the key rdev
+    /// reports for it. Unknown codes are passed on like rdev does on Linux, as X11 key codes.
+    fn evdev_code_to_rdev_key(code: u32) -> RDevKey {
+        match code {
+            1 => RDevKey::Escape,
+            2 => RDevKey::Num1,
+            3 => RDevKey::Num2,
+            4 => RDevKey::Num3,
+            5 => RDevKey::Num4,
+            6 => RDevKey::Num5,
+            7 => RDevKey::Num6,
+            8 => RDevKey::Num7,
+            9 => RDevKey::Num8,
+            10 => RDevKey::Num9,
+            11 => RDevKey::Num0,
+            12 => RDevKey::Minus,
+            13 => RDevKey::Equal,
+            14 => RDevKey::Backspace,
+            15 => RDevKey::Tab,
+            16 => RDevKey::KeyQ,
+            17 => RDevKey::KeyW,
+            18 => RDevKey::KeyE,
+            19 => RDevKey::KeyR,
+            20 => RDevKey::KeyT,
+            21 => RDevKey::KeyY,
+            22 => RDevKey::KeyU,
+            23 => RDevKey::KeyI,
+            24 => RDevKey::KeyO,
+            25 => RDevKey::KeyP,
+            26 => RDevKey::LeftBracket,
+            27 => RDevKey::RightBracket,
+            28 => RDevKey::Return,
+            29 => RDevKey::ControlLeft,
+            30 => RDevKey::KeyA,
+            31 => RDevKey::KeyS,
+            32 => RDevKey::KeyD,
+            33 => RDevKey::KeyF,
+            34 => RDevKey::KeyG,
+            35 => RDevKey::KeyH,
+            36 => RDevKey::KeyJ,
+            37 => RDevKey::KeyK,
+            38 => RDevKey::KeyL,
+            39 => RDevKey::SemiColon,
+            40 => RDevKey::Quote,
+            41 => RDevKey::BackQuote,
+            42 => RDevKey::ShiftLeft,
+            43 => RDevKey::BackSlash,
+            44 => RDevKey::KeyZ,
+            45 => RDevKey::KeyX,
+            46 => RDevKey::KeyC,
+            47 => RDevKey::KeyV,
+            48 => RDevKey::KeyB,
+            49 => RDevKey::KeyN,
+            50 => RDevKey::KeyM,
+            51 => RDevKey::Comma,
+            52 => RDevKey::Dot,
+            53 => RDevKey::Slash,
+            54 => RDevKey::ShiftRight,
+            55 => RDevKey::KpMultiply,
+            56 => RDevKey::Alt,
+            57 => RDevKey::Space,
+            58 => RDevKey::CapsLock,
+            59 => RDevKey::F1,
+            60 => RDevKey::F2,
+            61 => RDevKey::F3,
+            62 => RDevKey::F4,
+            63 => RDevKey::F5,
+            64 => RDevKey::F6,
+            65 => RDevKey::F7,
+            66 => RDevKey::F8,
+            67 => RDevKey::F9,
+            68 => RDevKey::F10,
+            69 => RDevKey::NumLock,
+            70 => RDevKey::ScrollLock,
+            71 => RDevKey::Kp7,
+            72 => RDevKey::Kp8,
+            73 => RDevKey::Kp9,
+            74 => RDevKey::KpMinus,
+            75 => RDevKey::Kp4,
+            76 => RDevKey::Kp5,
+            77 => RDevKey::Kp6,
+            78 => RDevKey::KpPlus,
+            79 => RDevKey::Kp1,
+            80 => RDevKey::Kp2,
+            81 => RDevKey::Kp3,
+            82 => RDevKey::Kp0,
+            83 => RDevKey::KpDelete,
+            86 => RDevKey::IntlBackslash,
+            87 => RDevKey::F11,
+            88 => RDevKey::F12,
+            96 => RDevKey::KpReturn,
+            97 => RDevKey::ControlRight,
+            98 => RDevKey::KpDivide,
+            99 => RDevKey::PrintScreen,
+            100 => RDevKey::AltGr,
+            102 => RDevKey::Home,
+            103 => RDevKey::UpArrow,
+            104 => RDevKey::PageUp,
+            105 => RDevKey::LeftArrow,
+            106 => RDevKey::RightArrow,
+            107 => RDevKey::End,
+            108 => RDevKey::DownArrow,
+            109 => RDevKey::PageDown,
+            110 => RDevKey::Insert,
+            111 => RDevKey::Delete,
+            119 => RDevKey::Pause,
+            125 => RDevKey::MetaLeft,
+            126 => RDevKey::MetaRight,
+            464 => RDevKey::Function,
+            code => RDevKey::Unknown(code + 8),
+        }
+    }
+}
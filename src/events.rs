@@ -1,5 +1,15 @@
+#[cfg(feature = "grab")]
+use crate::backend::RdevBackend;
+use crate::backend::{BackendError, InputBackend, ShutdownSignal, Simulator};
+pub use crate::backend::MockBackend;
 use crate::keybinds::{
-  keyevent_match_sync,
+  capture_keyevent_sync,
+  capture_pending_sync,
+  wake_waiters_sync,
+  Combo,
+  InputManager,
+  matches,
+  UnknownMode,
 };
 
 use odilia_common::{
@@ -9,175 +19,742 @@ use odilia_common::{
     Key,
     Modifiers,
   },
+  modes::ScreenReaderMode,
 };
 use rdev::{
-    simulate,
     Event,
-    EventType::{KeyPress, KeyRelease},
+    EventType::{self, KeyPress, KeyRelease},
     Key as RDevKey,
 };
-use tokio::{sync::mpsc};
+use tokio::{
+    runtime::Handle,
+    sync::{mpsc, oneshot, Notify, Semaphore},
+    task::JoinHandle,
+    time::Instant,
+};
 
 use once_cell::sync::{Lazy, OnceCell};
-use std::{future::Future, sync::Mutex};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    time::{Duration, SystemTime},
+};
 
 pub type AsyncFn =
     Box<dyn Fn() -> Box<dyn Future<Output = ()> + Unpin + Send + 'static> + Send + Sync + 'static>;
 
-// These are to be used only from the input monitoring thread
-thread_local! {
-    /// The channel's [`mpsc::Sender`].
-    static TX: OnceCell<mpsc::Sender<KeyBinding>> = OnceCell::new();
+/// The channel's [`mpsc::Sender`], set once by [`create_keybind_channel`].
+static TX: OnceCell<mpsc::Sender<KeyBinding>> = OnceCell::new();
+/// The tokio runtime [`create_keybind_channel`] was called from, used to spawn handlers.
+static RUNTIME: OnceCell<Handle> = OnceCell::new();
+static SHUTDOWN: Lazy<ShutdownSignal> = Lazy::new(ShutdownSignal::new);
+type BackendResult = oneshot::Receiver<Result<(), BackendError>>;
+/// Receives the result of the backend thread.
+static BACKEND_EXIT: Lazy<Mutex<Option<BackendResult>>> = Lazy::new(|| Mutex::new(None));
+
+/// Lock `mutex`, even if a panic while it was held poisoned it.
+///
+/// The state behind these locks is valid after every single update, and a panic in one event (or
+/// one setter) must not keep the input monitoring thread from handling all the following ones.
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The state the input monitoring thread updates on every event, behind a single lock so that each
+/// event only takes it once.
+#[derive(Clone, Debug)]
+struct KeyState {
+    /// The keys held down, in the order they were pressed.
+    current: Vec<RDevKey>,
+    /// When the last key of a chord which has not (yet) fired a binding was pressed.
+    last_press: Option<SystemTime>,
+    /// When each held key went down, for matching combos.
+    press_times: HashMap<RDevKey, SystemTime>,
+    /// Modifiers released less than the grace period ago, and when; they are still in `current`.
+    released: Vec<(RDevKey, SystemTime)>,
+    /// Non-modifier keys still held down whose chord already ended, e.g. because nothing matched
+    /// it. They stay in `current`, so that their auto-repeat is not taken for new presses, but the
+    /// next chord starts without them.
+    spent: Vec<RDevKey>,
+}
+
+impl KeyState {
+    /// The keys which are physically held down, leaving out modifiers in their grace period.
+    fn held(&self) -> Vec<RDevKey> {
+        without_released(&self.current, &self.released)
+    }
+
+    /// Drop the released modifiers whose grace period is over at `now`, or all of them.
+    fn expire_released(&mut self, now: Option<SystemTime>, grace: Option<Duration>) {
+        let current = &mut self.current;
+        self.released.retain(|(k, at)| {
+            let keep = matches!(
+                (now, grace),
+                (Some(now), Some(grace)) if now.duration_since(*at).map_or(true, |gone| gone <= grace)
+            );
+            if !keep {
+                current.retain(|held| held != k);
+            }
+            keep
+        });
+    }
+}
+
+fn without_released(current: &[RDevKey], released: &[(RDevKey, SystemTime)]) -> Vec<RDevKey> {
+    current.iter().copied().filter(|k| !released.iter().any(|(r, _)| r == k)).collect()
+}
+
+/// The keys of the chord being pressed: `current`, leaving out the `spent` ones.
+fn chord_keys<'a>(current: &'a [RDevKey], spent: &[RDevKey]) -> Cow<'a, [RDevKey]> {
+    if spent.is_empty() {
+        Cow::Borrowed(current)
+    } else {
+        Cow::Owned(current.iter().copied().filter(|k| !spent.contains(k)).collect())
+    }
+}
+
+/// End the chord being pressed: its non-modifier keys stay held, but the next chord starts without
+/// them. Only the modifiers carry over.
+fn end_chord(current: &[RDevKey], spent: &mut Vec<RDevKey>) {
+    for &k in current {
+        if !is_modifier(&k) && !spent.contains(&k) {
+            spent.push(k);
+        }
+    }
+}
+
+static KEY_STATE: Lazy<Mutex<KeyState>> = Lazy::new(|| Mutex::new(KeyState {
+    current: Vec::new(),
+    last_press: None,
+    press_times: HashMap::new(),
+    released: Vec::new(),
+    spent: Vec::new(),
+}));
+static CHORD_TIMEOUT: Lazy<Mutex<Option<Duration>>> = Lazy::new(|| Mutex::new(None));
+static MODIFIER_RELEASE_GRACE: Lazy<Mutex<Option<Duration>>> = Lazy::new(|| Mutex::new(None));
+static MAX_CHORD_KEYS: Lazy<Mutex<Option<usize>>> = Lazy::new(|| Mutex::new(None));
+
+/// Modifier keys pressed on their own, for matching modifier-only bindings.
+struct ModifierTap {
+    /// The modifiers pressed since all keys were last released.
+    mods: Modifiers,
+    /// Whether any other key was pressed since all keys were last released.
+    interrupted: bool,
+    /// The modifiers, release time and tap count of the last completed tap.
+    last: Option<(Modifiers, SystemTime, u8)>,
+    /// When the first modifier of the current tap went down.
+    pressed: Option<SystemTime>,
+    /// How soon a tap must follow the previous one to count towards a double (or triple...) tap.
+    window: Duration,
+    /// How long the modifiers may be held down and still count as a tap.
+    max_hold: Option<Duration>,
+}
+
+static MODIFIER_TAP: Lazy<Mutex<ModifierTap>> = Lazy::new(|| Mutex::new(ModifierTap {
+    mods: Modifiers::empty(),
+    interrupted: false,
+    last: None,
+    pressed: None,
+    window: DEFAULT_MULTI_TAP_WINDOW,
+    max_hold: None,
+}));
+
+const DEFAULT_MULTI_TAP_WINDOW: Duration = Duration::from_millis(400);
+
+/// Tune what counts as tapping modifiers on their own, for modifier-only bindings like a double tap
+/// of Shift.
+///
+/// A tap always has to be a press and release of modifiers with no other key pressed in between,
+/// so typing Shift+A never counts as a Shift tap. On top of that, a tap only follows the previous
+/// one if it comes within `window` (400ms by default), and with `max_hold` set, modifiers held down
+/// for longer than that are not a tap at all. Still, someone who presses Shift and thinks better of
+/// it twice in a row does double tap it: a binding on a modifier used for typing always carries
+/// some risk of firing by accident, and a shorter window and hold make that less likely.
+///
+/// A modifier-only binding never consumes, whatever its `consume` says: the tap is only known once
+/// the modifiers are released, after their presses already reached the rest of the system, and
+/// keeping back just the releases would leave them stuck down.
+pub fn set_modifier_tap_timing(window: Duration, max_hold: Option<Duration>) {
+    let mut tap = lock(&MODIFIER_TAP);
+    tap.window = window;
+    tap.max_hold = max_hold;
+}
+
+/// How soon a modifier tap must follow the previous one to count towards a multi-tap.
+fn multi_tap_window() -> Duration {
+    lock(&MODIFIER_TAP).window
+}
+
+/// A tap/hold binding whose key is down, and which has not been decided on yet, or was decided to
+/// be a hold before the key came up.
+struct PendingTapHold {
+    keybind: KeyBinding,
+    /// The manager `keybind` is registered with, which runs its hold handler.
+    manager: InputManager,
+    /// Whether the press was consumed; the release goes the same way.
+    consume: bool,
+    /// The non-modifier key which has to be released for a tap.
+    key: RDevKey,
+    pressed: SystemTime,
+    threshold: Duration,
+    /// Tells the hold timer whether its press is still the pending one.
+    id: u64,
+    /// Whether the hold timer already ran the hold handler; only the release is left to handle.
+    held: bool,
+}
+
+static PENDING_TAP_HOLD: Lazy<Mutex<Option<PendingTapHold>>> = Lazy::new(|| Mutex::new(None));
+static NEXT_TAP_HOLD_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Start timing a press of the tap/hold binding `keybind`.
+///
+/// A hold fires once `threshold` has passed; the tap is decided on by [`release_tap_hold`].
+fn start_tap_hold(
+    manager: &InputManager,
+    keybind: KeyBinding,
+    consume: bool,
+    key: RDevKey,
+    pressed: SystemTime,
+    threshold: Duration,
+) {
+    let id = NEXT_TAP_HOLD_ID.fetch_add(1, Ordering::Relaxed);
+    // Only one key can be pending at a time, a second tap/hold key cuts the first one short
+    let earlier = lock(&PENDING_TAP_HOLD).replace(PendingTapHold {
+        keybind,
+        manager: manager.clone(),
+        consume,
+        key,
+        pressed,
+        threshold,
+        id,
+        held: false,
+    });
+    if let Some(earlier) = earlier.filter(|earlier| !earlier.held) {
+        notify_keybind(&earlier.manager, &earlier.keybind);
+    }
+    RUNTIME.get().unwrap().spawn(async move {
+        tokio::time::sleep(threshold).await;
+        // Keep the press pending, so that its release still goes the same way
+        let hold = match &mut *lock(&PENDING_TAP_HOLD) {
+            Some(p) if p.id == id && !p.held => {
+                p.held = true;
+                Some((p.keybind.clone(), p.manager.clone()))
+            },
+            _ => None,
+        };
+        if let Some((keybind, manager)) = hold {
+            manager.run_hold_func(&keybind).await;
+        }
+    });
+}
+
+/// Decide on the pending tap/hold binding if `ev` releases its key.
+///
+/// Returns whether the event is consumed, if it released a pending key.
+fn release_tap_hold(ev: &Event) -> Option<bool> {
+    let KeyRelease(released) = ev.event_type else {
+        return None;
+    };
+    let mut pending = lock(&PENDING_TAP_HOLD);
+    if !pending.as_ref().is_some_and(|p| p.key == released) {
+        return None;
+    }
+    let p = pending.take().unwrap();
+    if p.held {
+        return Some(p.consume);
+    }
+    // The timer may not have had a chance to run yet
+    if ev.time.duration_since(p.pressed).is_ok_and(|held| held >= p.threshold) {
+        let (keybind, manager) = (p.keybind, p.manager);
+        RUNTIME.get().unwrap().spawn(async move { manager.run_hold_func(&keybind).await });
+        return Some(p.consume);
+    }
+    notify_keybind(&p.manager, &p.keybind);
+    Some(p.consume)
+}
+
+/// Track modifiers pressed on their own.
+///
+/// When the last held key is released and only modifiers were pressed since all keys were last
+/// released, this returns a [`KeyEvent`] without a key, carrying those modifiers and the number of
+/// times in a row they were tapped. Modifiers used as part of an ordinary chord never produce one,
+/// so modifier-only bindings (a [`KeyBinding`] whose `key` is `None`) don't fire during normal
+/// typing.
+fn modifier_tap_event(ev: &Event, current_keys: &[RDevKey]) -> Option<KeyEvent> {
+    let mut tap = lock(&MODIFIER_TAP);
+    match ev.event_type {
+        KeyPress(k) if is_modifier(&k) => {
+            if tap.mods.is_empty() {
+                tap.pressed = Some(ev.time);
+            }
+            tap.mods |= rdev_keys_to_odilia_modifiers(&[k]);
+        },
+        KeyPress(_) => tap.interrupted = true,
+        KeyRelease(_) if current_keys.is_empty() => {
+            let mods = std::mem::replace(&mut tap.mods, Modifiers::empty());
+            let interrupted = std::mem::replace(&mut tap.interrupted, false);
+            let held_too_long = match (tap.pressed.take(), tap.max_hold) {
+                (Some(pressed), Some(max_hold)) => {
+                    ev.time.duration_since(pressed).is_ok_and(|held| held > max_hold)
+                },
+                _ => false,
+            };
+            if interrupted || held_too_long || mods.is_empty() {
+                tap.last = None;
+                return None;
+            }
+            let repeat = match tap.last {
+                Some((last_mods, last_time, count))
+                    if last_mods == mods
+                        && ev.time.duration_since(last_time).is_ok_and(|gap| gap <= tap.window) =>
+                {
+                    count.saturating_add(1)
+                },
+                _ => 1,
+            };
+            tap.last = Some((mods, ev.time, repeat));
+            return Some(KeyEvent {
+                key: None,
+                mods,
+                repeat,
+            });
+        },
+        _ => {},
+    }
+    None
+}
+
+/// How many times in a row `mods` were just tapped on their own, i.e. the `repeat` a modifier-only
+/// binding would need to match the latest tap, or 0 if they weren't, or the multi-tap window has
+/// passed since.
+pub fn repeat_count(mods: Modifiers) -> u8 {
+    let tap = lock(&MODIFIER_TAP);
+    match tap.last {
+        Some((last_mods, last_time, count)) if last_mods == mods
+            && SystemTime::now().duration_since(last_time).is_ok_and(|gap| gap <= tap.window) => count,
+        _ => 0,
+    }
+}
+
+/// Forget the taps so far, so that the next modifier tap counts as the first one again, e.g. after
+/// switching modes.
+pub fn reset_repeat() {
+    lock(&MODIFIER_TAP).last = None;
+}
+
+/// What a binding for a number of repeated taps means while bindings for more taps of the same
+/// keys exist. Only modifier-only bindings (tapping e.g. the Odilia key on its own) can be
+/// repeated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RepeatPolicy {
+    /// Every tap fires the binding for the number of taps so far, so a double tap fires the
+    /// single-tap binding and then the double-tap one. Nothing is delayed. This is the default.
+    #[default]
+    EveryTap,
+    /// A tap only fires its binding once it is clear no further tap follows, so a double tap only
+    /// fires the double-tap binding. A tap is delayed by the multi-tap window (see
+    /// [`set_modifier_tap_timing`]) whenever a binding for more taps exists; otherwise it fires
+    /// right away.
+    Exact,
+}
+
+static REPEAT_POLICY: Lazy<Mutex<RepeatPolicy>> = Lazy::new(|| Mutex::new(RepeatPolicy::EveryTap));
+
+pub fn set_repeat_policy(policy: RepeatPolicy) {
+    *lock(&REPEAT_POLICY) = policy;
+}
+
+pub fn get_repeat_policy() -> RepeatPolicy {
+    *lock(&REPEAT_POLICY)
+}
+
+/// A modifier tap binding held back by [`RepeatPolicy::Exact`].
+struct PendingRepeat {
+    /// The tap which matched `keybind`.
+    tap_event: KeyEvent,
+    keybind: KeyBinding,
+    /// The manager `keybind` is registered with.
+    manager: InputManager,
+    /// Tells the timer whether its tap is still the pending one.
+    id: u64,
+}
+
+static PENDING_REPEAT: Lazy<Mutex<Option<PendingRepeat>>> = Lazy::new(|| Mutex::new(None));
+static NEXT_REPEAT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Decide on a modifier tap which came after any held back one.
+///
+/// A tap continuing the held back one supersedes it; any other tap lets it fire right away.
+fn settle_pending_repeat(tap_event: &KeyEvent) {
+    let pending = lock(&PENDING_REPEAT).take();
+    if let Some(earlier) = pending {
+        if earlier.tap_event.mods != tap_event.mods || tap_event.repeat <= earlier.tap_event.repeat {
+            notify_keybind(&earlier.manager, &earlier.keybind);
+        }
+    }
+}
+
+/// Hold back `keybind`, matched by `tap_event`, until the multi-tap window has passed.
+fn defer_repeat(manager: &InputManager, tap_event: KeyEvent, keybind: KeyBinding) {
+    let id = NEXT_REPEAT_ID.fetch_add(1, Ordering::Relaxed);
+    *lock(&PENDING_REPEAT) = Some(PendingRepeat {
+        tap_event,
+        keybind,
+        manager: manager.clone(),
+        id,
+    });
+    RUNTIME.get().unwrap().spawn(async move {
+        tokio::time::sleep(multi_tap_window()).await;
+        let pending = {
+            let mut pending = lock(&PENDING_REPEAT);
+            match pending.take() {
+                Some(p) if p.id == id => Some(p),
+                other => {
+                    *pending = other;
+                    None
+                }
+            }
+        };
+        if let Some(p) = pending {
+            // The sending thread must not be a runtime thread
+            let _ = tokio::task::spawn_blocking(move || notify_keybind(&p.manager, &p.keybind)).await;
+        }
+    });
+}
+
+/// Set how long a user may hesitate between the key presses of a chord.
+///
+/// If no binding fired and the next key press comes more than `timeout` after the previous one,
+/// the held keys are forgotten and the chord starts over from the new key. `None` (the default)
+/// waits forever.
+pub fn set_chord_timeout(timeout: Option<Duration>) {
+    *lock(&CHORD_TIMEOUT) = timeout;
+}
+
+/// Keep treating a released modifier as held for `grace`, or `None` (the default) to let go of it
+/// right away.
+///
+/// Fast typists sometimes lift a modifier a moment too early, or release and re-press it in the
+/// middle of a chord. Within the grace period, a key press still gets the released modifier, and
+/// pressing the modifier again continues the chord as if it had never been released. Releasing the
+/// last held key still completes a modifier tap right away.
+pub fn set_modifier_release_grace(grace: Option<Duration>) {
+    *lock(&MODIFIER_RELEASE_GRACE) = grace;
+}
+
+/// Ignore chords with more than `max` non-modifier keys held down at once, or `None` (the default)
+/// to allow any number.
+///
+/// Some keyboards report phantom keys ("ghosting") when several keys are held, which could make a
+/// wrong binding fire. A chord over the limit is translated to a [`KeyEvent`] without a key, so no
+/// binding or combo matches it and it is passed through. This also applies to [`translate_event`].
+pub fn set_max_chord_keys(max: Option<usize>) {
+    *lock(&MAX_CHORD_KEYS) = max;
+}
+
+/// The key which acts as the [`Modifiers::ODILIA`] modifier.
+static ODILIA_KEY: Lazy<Mutex<RDevKey>> = Lazy::new(|| Mutex::new(RDevKey::CapsLock));
+
+/// The key which acts as the [`Modifiers::ODILIA`] modifier, CapsLock by default.
+pub fn get_odilia_modifier_key() -> RDevKey {
+    *lock(&ODILIA_KEY)
 }
 
-static CURRENT_KEYS: Lazy<Mutex<Vec<RDevKey>>> = Lazy::new(|| Mutex::new(Vec::new()));
-static LAST_KEYS: Lazy<Mutex<Vec<RDevKey>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Make `key` act as the [`Modifiers::ODILIA`] modifier instead of the current one, taking effect
+/// with the next event.
+///
+/// The previous key goes back to being an ordinary key. If it is held down while switching, it is
+/// forgotten, so that it can't leave the Odilia modifier stuck.
+pub fn set_odilia_modifier_key(key: RDevKey) {
+    // Same lock order as the input monitoring thread
+    let mut state = lock(&KEY_STATE);
+    let mut tap = lock(&MODIFIER_TAP);
+    let mut odilia_key = lock(&ODILIA_KEY);
+    let old = std::mem::replace(&mut *odilia_key, key);
+    state.current.retain(|&k| k != old);
+    state.released.retain(|&(k, _)| k != old);
+    state.spent.retain(|&k| k != old);
+    state.press_times.remove(&old);
+    tap.mods.remove(Modifiers::ODILIA);
+}
 
 fn rdev_keys_to_odilia_modifiers(keys: &[RDevKey]) -> Modifiers {
+    let odilia_key = get_odilia_modifier_key();
     let mut modifiers = Modifiers::empty();
     for k in keys {
         modifiers |= match *k {
-            k if k == RDevKey::CapsLock => Modifiers::ODILIA,
-            k if k == RDevKey::Alt => Modifiers::ALT_L,
-            k if k == RDevKey::AltGr => Modifiers::ALT_R,
-            k if k == RDevKey::ControlLeft => Modifiers::CONTROL_L,
-            k if k == RDevKey::ControlRight => Modifiers::CONTROL_R,
-            k if k == RDevKey::ShiftLeft => Modifiers::SHIFT_L,
-            k if k == RDevKey::ShiftRight => Modifiers::SHIFT_R,
-            k if k == RDevKey::MetaLeft => Modifiers::META_L,
-            k if k == RDevKey::MetaRight => Modifiers::META_R,
+            k if k == odilia_key => Modifiers::ODILIA,
+            RDevKey::Alt => Modifiers::ALT_L,
+            RDevKey::AltGr => Modifiers::ALT_R,
+            RDevKey::ControlLeft => Modifiers::CONTROL_L,
+            RDevKey::ControlRight => Modifiers::CONTROL_R,
+            RDevKey::ShiftLeft => Modifiers::SHIFT_L,
+            RDevKey::ShiftRight => Modifiers::SHIFT_R,
+            RDevKey::MetaLeft => Modifiers::META_L,
+            RDevKey::MetaRight => Modifiers::META_R,
             _ => Modifiers::empty(),
         }
     }
+    let ctrl_alt = Modifiers::CONTROL_L | Modifiers::ALT_L;
+    if get_alt_gr_policy() == AltGrPolicy::CtrlAltIsAltGr && modifiers.contains(ctrl_alt) {
+        modifiers.remove(ctrl_alt);
+        modifiers |= Modifiers::ALT_R;
+    }
     modifiers
 }
 
+/// How AltGr is recognised. Either way, the `AltGr` key itself is [`Modifiers::ALT_R`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AltGrPolicy {
+    /// Left Control and Alt held together are just that. This is the default.
+    #[default]
+    Native,
+    /// Left Control and Alt held together are AltGr ([`Modifiers::ALT_R`]), for keyboards which
+    /// report AltGr that way. Bindings for a genuine Ctrl+Alt can then no longer be pressed with
+    /// the left Control key.
+    CtrlAltIsAltGr,
+}
+
+static ALT_GR_POLICY: Lazy<Mutex<AltGrPolicy>> = Lazy::new(|| Mutex::new(AltGrPolicy::Native));
+
+pub fn set_alt_gr_policy(policy: AltGrPolicy) {
+    *lock(&ALT_GR_POLICY) = policy;
+}
+
+pub fn get_alt_gr_policy() -> AltGrPolicy {
+    *lock(&ALT_GR_POLICY)
+}
+
+/// Modifier flags in the order they are shown to the user, with the physical key for each.
+const MODIFIER_KEYS: [(Modifiers, RDevKey); 8] = [
+    (Modifiers::CONTROL_L, RDevKey::ControlLeft),
+    (Modifiers::CONTROL_R, RDevKey::ControlRight),
+    (Modifiers::ALT_L, RDevKey::Alt),
+    (Modifiers::ALT_R, RDevKey::AltGr),
+    (Modifiers::SHIFT_L, RDevKey::ShiftLeft),
+    (Modifiers::SHIFT_R, RDevKey::ShiftRight),
+    (Modifiers::META_L, RDevKey::MetaLeft),
+    (Modifiers::META_R, RDevKey::MetaRight),
+];
+
+/// The physical keys to press for `mods`, e.g. to tell the user how to type a binding.
+/// [`Modifiers::ODILIA`] becomes the [configured Odilia key][set_odilia_modifier_key], which comes
+/// first, as screen readers usually show it; the others follow as Control, Alt, Shift and Meta.
+pub fn modifiers_to_rdev_keys(mods: Modifiers) -> Vec<RDevKey> {
+    let odilia = mods.contains(Modifiers::ODILIA).then(get_odilia_modifier_key);
+    odilia
+        .into_iter()
+        .chain(MODIFIER_KEYS.iter().filter(|(m, _)| mods.contains(*m)).map(|&(_, k)| k))
+        .collect()
+}
+
+/// A name for a physical key fit for showing to the user, e.g. `"Ctrl"` or `"Caps Lock"`.
+fn rdev_key_display_name(key: RDevKey) -> String {
+    match key {
+        RDevKey::ControlLeft => "Ctrl".to_string(),
+        RDevKey::ControlRight => "Right Ctrl".to_string(),
+        RDevKey::Alt => "Alt".to_string(),
+        RDevKey::AltGr => "AltGr".to_string(),
+        RDevKey::ShiftLeft => "Shift".to_string(),
+        RDevKey::ShiftRight => "Right Shift".to_string(),
+        RDevKey::MetaLeft => "Super".to_string(),
+        RDevKey::MetaRight => "Right Super".to_string(),
+        RDevKey::CapsLock => "Caps Lock".to_string(),
+        RDevKey::NumLock => "Num Lock".to_string(),
+        RDevKey::ScrollLock => "Scroll Lock".to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+/// `mods` as the user would type them, joined with `+`, e.g. `"Caps Lock+Ctrl+Shift"` for
+/// `ODILIA | CONTROL_L | SHIFT_L` with CapsLock as the Odilia key. Left-hand modifiers get the
+/// plain name, right-hand ones are called out (`"Right Ctrl"`, `"AltGr"`). Empty for no
+/// modifiers.
+///
+/// ```
+/// # use odilia_common::input::Modifiers;
+/// # use odilia_input::events::modifiers_to_display_string;
+/// let mods = Modifiers::CONTROL_L | Modifiers::SHIFT_L;
+/// assert_eq!(modifiers_to_display_string(mods), "Ctrl+Shift");
+/// ```
+pub fn modifiers_to_display_string(mods: Modifiers) -> String {
+    modifiers_to_rdev_keys(mods).into_iter().map(rdev_key_display_name).collect::<Vec<_>>().join("+")
+}
+
+fn is_modifier(key: &RDevKey) -> bool {
+    !rdev_keys_to_odilia_modifiers(&[*key]).is_empty()
+}
+
+/// Translate a physical key to an Odilia [`Key`].
+///
+/// Keys are translated by their position on a US QWERTY layout, ignoring Shift: `SemiColon` is
+/// always `Key::Other(';')`, never `':'`. To bind the key that types a colon, bind `';'` together
+/// with a shift modifier (`SHIFT_L` and/or `SHIFT_R`). Shifted glyphs are never produced, so a
+/// binding to e.g. `Key::Other(':')` can never match.
+///
+/// The Odilia key is a modifier, so it has no [`Key`] even if it would otherwise, e.g. ScrollLock.
+fn rdev_key_to_odilia_key(key: &RDevKey) -> Option<Key> {
+    if *key == get_odilia_modifier_key() {
+        return None;
+    }
+    match key {
+        RDevKey::Backspace => Some(Key::Backspace),
+        RDevKey::Delete => Some(Key::Delete),
+        RDevKey::DownArrow => Some(Key::Down),
+        RDevKey::UpArrow => Some(Key::Up),
+        RDevKey::LeftArrow => Some(Key::Left),
+        RDevKey::RightArrow => Some(Key::Right),
+        RDevKey::End => Some(Key::End),
+        RDevKey::Escape => Some(Key::Escape),
+        RDevKey::F1 => Some(Key::F1),
+        RDevKey::F2 => Some(Key::F2),
+        RDevKey::F3 => Some(Key::F3),
+        RDevKey::F4 => Some(Key::F4),
+        RDevKey::F5 => Some(Key::F5),
+        RDevKey::F6 => Some(Key::F6),
+        RDevKey::F7 => Some(Key::F7),
+        RDevKey::F8 => Some(Key::F8),
+        RDevKey::F9 => Some(Key::F9),
+        RDevKey::F10 => Some(Key::F10),
+        RDevKey::F11 => Some(Key::F11),
+        RDevKey::F12 => Some(Key::F12),
+        RDevKey::Home => Some(Key::Home),
+        RDevKey::PageDown => Some(Key::PageDown),
+        RDevKey::PageUp => Some(Key::PageUp),
+        RDevKey::Return => Some(Key::Return),
+        RDevKey::Space => Some(Key::Space),
+        RDevKey::Tab => Some(Key::Tab),
+        RDevKey::PrintScreen => Some(Key::PrintScreen),
+        RDevKey::ScrollLock => Some(Key::ScrollLock),
+        RDevKey::Pause => Some(Key::Pause),
+        RDevKey::NumLock => Some(Key::NumLock),
+        RDevKey::BackQuote => Some(Key::Other('`')),
+        RDevKey::Num0 => Some(Key::Kp0),
+        RDevKey::Num1 => Some(Key::Kp1),
+        RDevKey::Num2 => Some(Key::Kp2),
+        RDevKey::Num3 => Some(Key::Kp3),
+        RDevKey::Num4 => Some(Key::Kp4),
+        RDevKey::Num5 => Some(Key::Kp5),
+        RDevKey::Num6 => Some(Key::Kp6),
+        RDevKey::Num7 => Some(Key::Kp7),
+        RDevKey::Num8 => Some(Key::Kp8),
+        RDevKey::Num9 => Some(Key::Kp9),
+        RDevKey::Minus => Some(Key::Other('-')),
+        RDevKey::Equal => Some(Key::Other('=')),
+        RDevKey::KeyQ => Some(Key::Other('q')),
+        RDevKey::KeyW => Some(Key::Other('w')),
+        RDevKey::KeyE => Some(Key::Other('e')),
+        RDevKey::KeyR => Some(Key::Other('r')),
+        RDevKey::KeyT => Some(Key::Other('t')),
+        RDevKey::KeyY => Some(Key::Other('y')),
+        RDevKey::KeyU => Some(Key::Other('u')),
+        RDevKey::KeyI => Some(Key::Other('i')),
+        RDevKey::KeyO => Some(Key::Other('o')),
+        RDevKey::KeyP => Some(Key::Other('p')),
+        RDevKey::LeftBracket => Some(Key::Other('[')),
+        RDevKey::RightBracket => Some(Key::Other(']')),
+        RDevKey::KeyA => Some(Key::Other('a')),
+        RDevKey::KeyS => Some(Key::Other('s')),
+        RDevKey::KeyD => Some(Key::Other('d')),
+        RDevKey::KeyF => Some(Key::Other('f')),
+        RDevKey::KeyG => Some(Key::Other('g')),
+        RDevKey::KeyH => Some(Key::Other('h')),
+        RDevKey::KeyJ => Some(Key::Other('j')),
+        RDevKey::KeyK => Some(Key::Other('k')),
+        RDevKey::KeyL => Some(Key::Other('l')),
+        RDevKey::SemiColon => Some(Key::Other(';')),
+        RDevKey::Quote => Some(Key::Other('\'')),
+        RDevKey::BackSlash => Some(Key::Other('\\')),
+        // TODO: check if correct belo)w
+        RDevKey::IntlBackslash => Some(Key::Other('\\')),
+        RDevKey::KeyZ => Some(Key::Other('z')),
+        RDevKey::KeyX => Some(Key::Other('x')),
+        RDevKey::KeyC => Some(Key::Other('c')),
+        RDevKey::KeyV => Some(Key::Other('v')),
+        RDevKey::KeyB => Some(Key::Other('b')),
+        RDevKey::KeyN => Some(Key::Other('n')),
+        RDevKey::KeyM => Some(Key::Other('m')),
+        RDevKey::Comma => Some(Key::Other(',')),
+        RDevKey::Dot => Some(Key::Other('.')),
+        RDevKey::Slash => Some(Key::Other('/')),
+        RDevKey::Insert => Some(Key::Insert),
+        RDevKey::Kp0 => Some(Key::Kp0),
+        RDevKey::Kp1 => Some(Key::Kp1),
+        RDevKey::Kp2 => Some(Key::Kp2),
+        RDevKey::Kp3 => Some(Key::Kp3),
+        RDevKey::Kp4 => Some(Key::Kp4),
+        RDevKey::Kp5 => Some(Key::Kp5),
+        RDevKey::Kp6 => Some(Key::Kp6),
+        RDevKey::Kp7 => Some(Key::Kp7),
+        RDevKey::Kp8 => Some(Key::Kp8),
+        RDevKey::Kp9 => Some(Key::Kp9),
+        RDevKey::KpReturn => Some(Key::KpReturn),
+        RDevKey::KpMinus => Some(Key::KpMinus),
+        RDevKey::KpPlus => Some(Key::KpPlus),
+        RDevKey::KpMultiply => Some(Key::KpMultiply),
+        RDevKey::KpDivide => Some(Key::KpDivide),
+        RDevKey::KpDelete => Some(Key::KpDelete),
+        RDevKey::Function => Some(Key::Function),
+        _ => None,
+    }
+}
+
 /* NOTE: this breaks if a user pressed a combination with two letters, i.e.: Ctrl+Shift+a+n, or CapsLock+a+s.
 This function will always return the first pressed key (a and a in our examples).
 */
 fn rdev_keys_to_single_odilia_key(keys: &[RDevKey]) -> Option<Key> {
-    for k in keys {
-        let m = match k {
-            RDevKey::Backspace => Some(Key::Backspace),
-            RDevKey::Delete => Some(Key::Delete),
-            RDevKey::DownArrow => Some(Key::Down),
-            RDevKey::UpArrow => Some(Key::Up),
-            RDevKey::LeftArrow => Some(Key::Left),
-            RDevKey::RightArrow => Some(Key::Right),
-            RDevKey::End => Some(Key::End),
-            RDevKey::Escape => Some(Key::Escape),
-            RDevKey::F1 => Some(Key::F1),
-            RDevKey::F2 => Some(Key::F2),
-            RDevKey::F3 => Some(Key::F3),
-            RDevKey::F4 => Some(Key::F4),
-            RDevKey::F5 => Some(Key::F5),
-            RDevKey::F6 => Some(Key::F6),
-            RDevKey::F7 => Some(Key::F7),
-            RDevKey::F8 => Some(Key::F8),
-            RDevKey::F9 => Some(Key::F9),
-            RDevKey::F10 => Some(Key::F10),
-            RDevKey::F11 => Some(Key::F11),
-            RDevKey::F12 => Some(Key::F12),
-            RDevKey::Home => Some(Key::Home),
-            RDevKey::PageDown => Some(Key::PageDown),
-            RDevKey::PageUp => Some(Key::PageUp),
-            RDevKey::Return => Some(Key::Return),
-            RDevKey::Space => Some(Key::Space),
-            RDevKey::Tab => Some(Key::Tab),
-            RDevKey::PrintScreen => Some(Key::PrintScreen),
-            RDevKey::ScrollLock => Some(Key::ScrollLock),
-            RDevKey::Pause => Some(Key::Pause),
-            RDevKey::NumLock => Some(Key::NumLock),
-            RDevKey::BackQuote => Some(Key::Other('`')),
-            RDevKey::Num0 => Some(Key::Kp0),
-            RDevKey::Num1 => Some(Key::Kp1),
-            RDevKey::Num2 => Some(Key::Kp2),
-            RDevKey::Num3 => Some(Key::Kp3),
-            RDevKey::Num4 => Some(Key::Kp4),
-            RDevKey::Num5 => Some(Key::Kp5),
-            RDevKey::Num6 => Some(Key::Kp6),
-            RDevKey::Num7 => Some(Key::Kp7),
-            RDevKey::Num8 => Some(Key::Kp8),
-            RDevKey::Num9 => Some(Key::Kp9),
-            RDevKey::Minus => Some(Key::Other('-')),
-            RDevKey::Equal => Some(Key::Other('=')),
-            RDevKey::KeyQ => Some(Key::Other('q')),
-            RDevKey::KeyW => Some(Key::Other('w')),
-            RDevKey::KeyE => Some(Key::Other('e')),
-            RDevKey::KeyR => Some(Key::Other('r')),
-            RDevKey::KeyT => Some(Key::Other('t')),
-            RDevKey::KeyY => Some(Key::Other('y')),
-            RDevKey::KeyU => Some(Key::Other('u')),
-            RDevKey::KeyI => Some(Key::Other('i')),
-            RDevKey::KeyO => Some(Key::Other('o')),
-            RDevKey::KeyP => Some(Key::Other('p')),
-            RDevKey::LeftBracket => Some(Key::Other('[')),
-            RDevKey::RightBracket => Some(Key::Other(']')),
-            RDevKey::KeyA => Some(Key::Other('a')),
-            RDevKey::KeyS => Some(Key::Other('s')),
-            RDevKey::KeyD => Some(Key::Other('d')),
-            RDevKey::KeyF => Some(Key::Other('f')),
-            RDevKey::KeyG => Some(Key::Other('g')),
-            RDevKey::KeyH => Some(Key::Other('h')),
-            RDevKey::KeyJ => Some(Key::Other('j')),
-            RDevKey::KeyK => Some(Key::Other('k')),
-            RDevKey::KeyL => Some(Key::Other('l')),
-            RDevKey::SemiColon => Some(Key::Other(';')),
-            RDevKey::Quote => Some(Key::Other('\'')),
-            RDevKey::BackSlash => Some(Key::Other('\\')),
-            // TODO: check if correct belo)w
-            RDevKey::IntlBackslash => Some(Key::Other('\\')),
-            RDevKey::KeyZ => Some(Key::Other('z')),
-            RDevKey::KeyX => Some(Key::Other('x')),
-            RDevKey::KeyC => Some(Key::Other('c')),
-            RDevKey::KeyV => Some(Key::Other('v')),
-            RDevKey::KeyB => Some(Key::Other('b')),
-            RDevKey::KeyN => Some(Key::Other('n')),
-            RDevKey::Comma => Some(Key::Other(',')),
-            RDevKey::Dot => Some(Key::Other('.')),
-            RDevKey::Slash => Some(Key::Other('/')),
-            RDevKey::Insert => Some(Key::Insert),
-            RDevKey::KpReturn => Some(Key::KpReturn),
-            RDevKey::KpMinus => Some(Key::KpMinus),
-            RDevKey::KpPlus => Some(Key::KpPlus),
-            RDevKey::KpMultiply => Some(Key::KpMultiply),
-            RDevKey::KpDivide => Some(Key::KpDivide),
-            RDevKey::KpDelete => Some(Key::KpDelete),
-            RDevKey::Function => Some(Key::Function),
-            _ => None,
-        };
-        if let Some(m2) = m {
-            return Some(m2);
-        }
-    }
-    None
+    keys.iter().find_map(rdev_key_to_odilia_key)
 }
 
-fn rdev_event_to_odilia_event(events: &Vec<RDevKey>) -> KeyEvent {
+/// Whether more non-modifier keys are held than [`set_max_chord_keys`] allows.
+fn is_ghosting(held: &[RDevKey]) -> bool {
+    // An implausible number of keys is more likely ghosting than a real chord
+    lock(&MAX_CHORD_KEYS).is_some_and(|max| held.iter().filter(|k| !is_modifier(k)).count() > max)
+}
+
+fn rdev_event_to_odilia_event(events: &[RDevKey]) -> KeyEvent {
     KeyEvent {
-        key: rdev_keys_to_single_odilia_key(events),
+        key: if is_ghosting(events) { None } else { rdev_keys_to_single_odilia_key(events) },
         mods: rdev_keys_to_odilia_modifiers(events),
         /* TODO: set repeat properly */
         repeat: 1,
     }
 }
 
-fn is_new_key_event(event: &Event, current_keys: &mut Vec<RDevKey>, last_keys: &mut Vec<RDevKey>) -> bool {
+/// Translate a key press to the [`KeyEvent`] this crate would match bindings against, given the
+/// keys which were already held down before it, in the order they were pressed.
+///
+/// This does not touch the input monitoring system's own state. Returns `None` for anything but
+/// a key press, and for presses which don't complete a key combination, i.e. modifiers and keys
+/// without an Odilia [`Key`].
+pub fn translate_event(ev: &Event, held: &[RDevKey]) -> Option<KeyEvent> {
+    let KeyPress(pressed) = ev.event_type else {
+        return None;
+    };
+    rdev_key_to_odilia_key(&pressed)?;
+    let mut keys = held.to_vec();
+    if !keys.contains(&pressed) {
+        keys.push(pressed);
+    }
+    Some(rdev_event_to_odilia_event(&keys))
+}
+
+fn is_new_key_event(event: &Event, current_keys: &mut Vec<RDevKey>) -> bool {
   match event.event_type {
     KeyPress(x) => {
-      *last_keys = current_keys.clone();
-      current_keys.push(x);
-      current_keys.dedup();
-      // if there is a new key pressed/released and it is not a repeat event
-      if last_keys != current_keys {
-        println!("KEYS: {:?}", current_keys);
-        true
-      } else {
-        false
+      // if there is a new key pressed and it is not a repeat event of the last one
+      let is_new = current_keys.last() != Some(&x);
+      if is_new {
+        current_keys.push(x);
       }
+      is_new
     },
     KeyRelease(x) => {
-      *last_keys = current_keys.clone();
       // remove just released key from curent keys
       current_keys.retain(|&k| k != x);
       false
@@ -186,68 +763,1611 @@ fn is_new_key_event(event: &Event, current_keys: &mut Vec<RDevKey>, last_keys: &
   }
 }
 
-/// The maximum number of `[rdev::Event`]s that can be in the input queue at one time.
+/// Every named [`RDevKey`], i.e. all but [`RDevKey::Unknown`].
+const ALL_RDEV_KEYS: &[RDevKey] = &[
+    RDevKey::Alt, RDevKey::AltGr, RDevKey::Backspace, RDevKey::CapsLock, RDevKey::ControlLeft,
+    RDevKey::ControlRight, RDevKey::Delete, RDevKey::DownArrow, RDevKey::End, RDevKey::Escape,
+    RDevKey::F1, RDevKey::F2, RDevKey::F3, RDevKey::F4, RDevKey::F5, RDevKey::F6, RDevKey::F7,
+    RDevKey::F8, RDevKey::F9, RDevKey::F10, RDevKey::F11, RDevKey::F12, RDevKey::Home,
+    RDevKey::LeftArrow, RDevKey::MetaLeft, RDevKey::MetaRight, RDevKey::PageDown, RDevKey::PageUp,
+    RDevKey::Return, RDevKey::RightArrow, RDevKey::ShiftLeft, RDevKey::ShiftRight, RDevKey::Space,
+    RDevKey::Tab, RDevKey::UpArrow, RDevKey::PrintScreen, RDevKey::ScrollLock, RDevKey::Pause,
+    RDevKey::NumLock, RDevKey::BackQuote, RDevKey::Num1, RDevKey::Num2, RDevKey::Num3,
+    RDevKey::Num4, RDevKey::Num5, RDevKey::Num6, RDevKey::Num7, RDevKey::Num8, RDevKey::Num9,
+    RDevKey::Num0, RDevKey::Minus, RDevKey::Equal, RDevKey::KeyQ, RDevKey::KeyW, RDevKey::KeyE,
+    RDevKey::KeyR, RDevKey::KeyT, RDevKey::KeyY, RDevKey::KeyU, RDevKey::KeyI, RDevKey::KeyO,
+    RDevKey::KeyP, RDevKey::LeftBracket, RDevKey::RightBracket, RDevKey::KeyA, RDevKey::KeyS,
+    RDevKey::KeyD, RDevKey::KeyF, RDevKey::KeyG, RDevKey::KeyH, RDevKey::KeyJ, RDevKey::KeyK,
+    RDevKey::KeyL, RDevKey::SemiColon, RDevKey::Quote, RDevKey::BackSlash, RDevKey::IntlBackslash,
+    RDevKey::KeyZ, RDevKey::KeyX, RDevKey::KeyC, RDevKey::KeyV, RDevKey::KeyB, RDevKey::KeyN,
+    RDevKey::KeyM, RDevKey::Comma, RDevKey::Dot, RDevKey::Slash, RDevKey::Insert,
+    RDevKey::KpReturn, RDevKey::KpMinus, RDevKey::KpPlus, RDevKey::KpMultiply, RDevKey::KpDivide,
+    RDevKey::Kp0, RDevKey::Kp1, RDevKey::Kp2, RDevKey::Kp3, RDevKey::Kp4, RDevKey::Kp5,
+    RDevKey::Kp6, RDevKey::Kp7, RDevKey::Kp8, RDevKey::Kp9, RDevKey::KpDelete, RDevKey::Function,
+];
+
+/// Every [`Key`] that a physical key can be translated to, i.e. every key a [`KeyBinding`] can
+/// usefully be bound to.
+pub fn supported_keys() -> Vec<Key> {
+    let mut keys: Vec<Key> = Vec::new();
+    for key in ALL_RDEV_KEYS.iter().filter_map(rdev_key_to_odilia_key) {
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// Every single [`Modifiers`] flag that a physical key can be translated to.
+pub fn supported_modifiers() -> Vec<Modifiers> {
+    let mut modifiers: Vec<Modifiers> = Vec::new();
+    for k in ALL_RDEV_KEYS {
+        let m = rdev_keys_to_odilia_modifiers(&[*k]);
+        if !m.is_empty() && !modifiers.contains(&m) {
+            modifiers.push(m);
+        }
+    }
+    modifiers
+}
+
+/// A snapshot of the non-modifier keys currently held down, in the order they were pressed.
+///
+/// Keys without an Odilia equivalent are left out; held modifiers are available through
+/// [`currently_pressed_modifiers`].
+pub fn currently_pressed() -> Vec<Key> {
+    let state = lock(&KEY_STATE);
+    state.held().iter().filter_map(rdev_key_to_odilia_key).collect()
+}
+
+/// The [`Modifiers`] currently held down.
+pub fn currently_pressed_modifiers() -> Modifiers {
+    let state = lock(&KEY_STATE);
+    rdev_keys_to_odilia_modifiers(&state.held())
+}
+
+/// The default maximum number of notifications that can be in the queue at one time.
 /// The queue could be unbounded, but this allows for backpressure, which allows us to catch up if
 /// we get spammed with events.
 ///
-/// On x86_64-unknown-linux-gnu, [`rdev::Event`] is 64 bytes, so this is 16 KiB of queue.
+/// The queue holds [`KeyBinding`]s; see [`estimated_queue_bytes`] for what that adds up to.
 const MAX_EVENTS: usize = 256;
 
-/// Initialise the input monitoring system, returning an [`mpsc::Receiver`] which can be used to
-/// recieve input events.
+/// How many notifications the channel can hold; see [`InputConfig::queue_capacity`].
+pub fn queue_capacity() -> usize {
+    config().queue_capacity.max(1)
+}
+
+/// Roughly how much memory the channel's queue takes up when full, in bytes.
 ///
-/// `decide_action` will be used to determine whether the [`Event`][rdev::Event] is consumed, and
-/// also whether we are notified about it via the channel.
-/// # Panics
-/// * If called more than once in the same program.
-pub fn create_keybind_channel() -> mpsc::Receiver<KeyBinding>
-where
-{
-    // Create the channel for communication between the input monitoring thread and async tasks
-    let (tx, rx) = mpsc::channel(MAX_EVENTS);
+/// This only counts the [`KeyBinding`]s themselves, not the mode names they may point to.
+pub fn estimated_queue_bytes() -> usize {
+    queue_capacity() * std::mem::size_of::<KeyBinding>()
+}
 
-    // Spawn a synchronous input monitoring thread
-    std::thread::spawn(move || {
-        // should work as long as called from a tokio runtime
-        // Set the thread-local variables
-        TX.with(|global| global.set(tx).unwrap());
-        // Start the event loop
-        rdev::grab(move |ev| {
-            let mut current_keys = CURRENT_KEYS.lock().unwrap();
-            let mut last_keys = LAST_KEYS.lock().unwrap();
-            
-            // if the event is not new (i.e. a held key), just passthrough the event
-            if !is_new_key_event(&ev, &mut current_keys, &mut last_keys) {
-                return Some(ev);
+/// Options for [`create_keybind_channel_with_config`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputConfig {
+    /// How many of the most recent key events [`recent_events`] keeps. 0 disables recording.
+    pub recent_events: usize,
+    /// Whether character keys are recorded as `Key::Other('*')` rather than the actual character,
+    /// so that passwords don't end up in bug reports.
+    pub redact_recent_events: bool,
+    /// How many handlers spawned for key events may run at once, or `None` for no limit.
+    ///
+    /// This covers [`run_keybind_func`][crate::keybinds::run_keybind_func] as well as the handlers
+    /// the input monitoring system spawns itself, across all
+    /// [`InputManager`][crate::keybinds::InputManager]s.
+    pub max_running_handlers: Option<usize>,
+    /// With a [`max_running_handlers`][Self::max_running_handlers] limit, whether a handler
+    /// triggered while the limit is reached is dropped rather than waiting for its turn.
+    pub drop_excess_handlers: bool,
+    /// How many notifications the channel can hold before the input monitoring thread waits for
+    /// the receiver to catch up. 0 is treated as 1.
+    pub queue_capacity: usize,
+    /// Which kinds of events are run through the keybinding pipeline. Any other event is passed
+    /// through untouched, without reaching [event filters][add_event_filter], though it still
+    /// counts as input for [`on_idle`]. Only key presses and releases by default, since e.g. mouse
+    /// moves come in continuously and no binding can match them.
+    pub monitored_events: Vec<EventKind>,
+}
+
+/// A kind of [`EventType`], for [`InputConfig::monitored_events`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    KeyPress,
+    KeyRelease,
+    ButtonPress,
+    ButtonRelease,
+    Wheel,
+    MouseMove,
+}
+
+impl EventKind {
+    pub fn of(event_type: &EventType) -> Self {
+        match event_type {
+            KeyPress(_) => EventKind::KeyPress,
+            KeyRelease(_) => EventKind::KeyRelease,
+            EventType::ButtonPress(_) => EventKind::ButtonPress,
+            EventType::ButtonRelease(_) => EventKind::ButtonRelease,
+            EventType::Wheel { .. } => EventKind::Wheel,
+            EventType::MouseMove { .. } => EventKind::MouseMove,
+        }
+    }
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        InputConfig {
+            recent_events: 64,
+            redact_recent_events: true,
+            max_running_handlers: None,
+            drop_excess_handlers: false,
+            queue_capacity: MAX_EVENTS,
+            monitored_events: vec![EventKind::KeyPress, EventKind::KeyRelease],
+        }
+    }
+}
+
+static CONFIG: OnceCell<InputConfig> = OnceCell::new();
+static DEFAULT_CONFIG: Lazy<InputConfig> = Lazy::new(InputConfig::default);
+
+/// The [`InputConfig`] given at initialisation, or the default one before that.
+fn config() -> &'static InputConfig {
+    CONFIG.get().unwrap_or(&DEFAULT_CONFIG)
+}
+
+/// Spawn `fut` like the handlers the input monitoring system spawns itself, returning false if
+/// it is not initialised yet.
+pub(crate) fn spawn_handler<F: Future<Output = ()> + Send + 'static>(fut: F) -> bool {
+    let Some(runtime) = RUNTIME.get() else {
+        return false;
+    };
+    runtime.spawn(run_handler(fut));
+    true
+}
+
+/// Limits how many handlers run at once; unset means no limit.
+static HANDLER_SLOTS: OnceCell<Arc<Semaphore>> = OnceCell::new();
+
+/// Run a handler's future once a slot is free, or not at all if excess handlers are dropped.
+pub(crate) async fn run_handler<F: Future<Output = ()>>(fut: F) {
+    let Some(slots) = HANDLER_SLOTS.get() else {
+        return fut.await;
+    };
+    let _permit = if config().drop_excess_handlers {
+        match slots.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                tracing::debug!("Too many handlers running; dropping one");
+                return;
             }
+        }
+    } else {
+        // The semaphore is never closed
+        slots.clone().acquire_owned().await.unwrap()
+    };
+    fut.await
+}
+
+static RECENT_EVENTS: Lazy<Mutex<VecDeque<KeyEvent>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+/// The most recent event, never redacted, for [`replay_last_event`].
+static LAST_EVENT: Lazy<Mutex<Option<KeyEvent>>> = Lazy::new(|| Mutex::new(None));
+
+/// Remember `kbm` for [`recent_events`], dropping the oldest event once the buffer is full.
+fn record_event(kbm: &KeyEvent) {
+    *lock(&LAST_EVENT) = Some(kbm.clone());
+    let config = config();
+    if config.recent_events == 0 {
+        return;
+    }
+    let mut kbm = kbm.clone();
+    if config.redact_recent_events {
+        if let Some(Key::Other(_)) = kbm.key {
+            kbm.key = Some(Key::Other('*'));
+        }
+    }
+    let mut recent = lock(&RECENT_EVENTS);
+    while recent.len() >= config.recent_events {
+        recent.pop_front();
+    }
+    recent.push_back(kbm);
+}
+
+/// The most recent key events, oldest first, e.g. to attach to a bug report.
+///
+/// At most [`InputConfig::recent_events`] events are kept.
+pub fn recent_events() -> Vec<KeyEvent> {
+    lock(&RECENT_EVENTS).iter().cloned().collect()
+}
+
+/// Match the most recent key event again and send the binding it matches down the channel, e.g.
+/// for a "repeat last command" shortcut, returning that binding.
+///
+/// The binding is matched as things are now, so it may differ from the first time. Only the
+/// notification is repeated: nothing reaches (or is kept from) the rest of the system, and the
+/// binding is sent even if it has `notify` unset or is throttled, since replaying it is the whole
+/// point. Returns `None` before initialisation, if there was no event yet, or if it matches nothing
+/// now. This blocks, so it must not be called from async code.
+pub fn replay_last_event() -> Option<KeyBinding> {
+    let tx = TX.get()?;
+    let kbm = lock(&LAST_EVENT).clone()?;
+    let keybind = InputManager::active().keyevent_match_sync(&kbm)?;
+    if let Err(e) = tx.blocking_send(keybind.clone()) {
+        tracing::warn!("Failed to replay key event: {}", e);
+    }
+    Some(keybind)
+}
+
+/// What an [`EventFilter`] decided to do with an [`Event`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterDecision {
+    /// Pass the (possibly modified) event on to the next filter, then to keybinding matching.
+    Keep,
+    /// Discard the event entirely: it is neither matched nor passed through to the system.
+    Drop,
+    /// Continue with this event instead.
+    Replace(Event),
+}
+
+/// A filter run on every [`Event`] before it is translated and matched, e.g. to ignore auto-repeat
+/// or to remap one key to another.
+pub type EventFilter = Box<dyn Fn(&mut Event) -> FilterDecision + Send + Sync>;
+
+static FILTERS: Lazy<Mutex<Vec<EventFilter>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Append a filter to the chain of [`EventFilter`]s. Filters run in the order they were added.
+pub fn add_event_filter(filter: EventFilter) {
+    lock(&FILTERS).push(filter);
+}
+
+/// Remove all [`EventFilter`]s.
+pub fn clear_event_filters() {
+    lock(&FILTERS).clear();
+}
+
+/// Run `ev` through the filter chain, returning `None` if a filter dropped it.
+fn filter_event(mut ev: Event) -> Option<Event> {
+    let filters = lock(&FILTERS);
+    for filter in filters.iter() {
+        match filter(&mut ev) {
+            FilterDecision::Keep => {},
+            FilterDecision::Drop => return None,
+            FilterDecision::Replace(replacement) => ev = replacement,
+        }
+    }
+    Some(ev)
+}
+
+static LEARN_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Turn "learn mode" on or off, e.g. for a keyboard exploration feature which speaks every key.
+///
+/// In learn mode, no binding fires and no key is consumed (except the Odilia key, which never
+/// reaches the system). Instead, every key event is sent down the notification channel as a
+/// [`KeyBinding`] describing the keys pressed, with `consume` set to `false`. The switch takes
+/// effect from the next event on.
+pub fn set_learn_mode(enabled: bool) {
+    LEARN_MODE.store(enabled, Ordering::SeqCst);
+}
+
+pub fn is_learn_mode() -> bool {
+    LEARN_MODE.load(Ordering::SeqCst)
+}
+
+static OBSERVE_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Turn "observe only" mode on or off, e.g. for a diagnostic state.
+///
+/// Matched bindings are still sent down the notification channel, but no key is consumed (except
+/// the Odilia key, which never reaches the system) and no handler runs: not the handlers spawned by
+/// the input monitoring system (combos, holds, the unhandled key handler), and not
+/// [`run_keybind_func`][crate::keybinds::run_keybind_func] either. Remappings are suspended too.
+/// The switch takes effect from the next event on.
+pub fn set_observe_only(enabled: bool) {
+    OBSERVE_ONLY.store(enabled, Ordering::SeqCst);
+}
+
+pub fn is_observe_only() -> bool {
+    OBSERVE_ONLY.load(Ordering::SeqCst)
+}
 
-            // Decide what to do with this `Event`
-            let o_event = rdev_event_to_odilia_event(&current_keys);
-            let keybind: Option<KeyBinding> = keyevent_match_sync(&o_event);
-            /* if a matching keybinding is not found, pass through the event */
-            if keybind.is_none() {
-              return Some(ev);
+/// Send `kbm` down the channel as it is, for learn mode.
+fn notify_learned(manager: &InputManager, kbm: &KeyEvent) {
+    notify_keybind(manager, &KeyBinding {
+        key: kbm.key.clone(),
+        mods: kbm.mods,
+        repeat: kbm.repeat,
+        consume: false,
+        notify: true,
+        mode: None,
+    });
+}
+
+/// Whether the Odilia modifier key also keeps its native behaviour, e.g. toggling CapsLock.
+///
+/// This applies to whichever key is the Odilia key (see [`set_odilia_modifier_key`]); it is named
+/// for the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CapsLockPolicy {
+    /// The key only ever acts as the Odilia modifier. This is the default.
+    #[default]
+    AlwaysOdilia,
+    /// Tapping the key twice in quick succession passes the second tap through, e.g. to toggle
+    /// CapsLock; otherwise it only acts as the Odilia modifier.
+    DoubleTapToggles,
+    /// The key acts as the Odilia modifier, but every press is also passed through.
+    PassThrough,
+}
+
+/// State for [`CapsLockPolicy::DoubleTapToggles`].
+struct OdiliaTaps {
+    policy: CapsLockPolicy,
+    /// Whether the key is held down.
+    held: bool,
+    /// Whether no other key was pressed since the key went down.
+    clean: bool,
+    /// When a tap (a press and release with no other key in between) last ended.
+    last_tap: Option<SystemTime>,
+    /// Whether the current press is being passed through.
+    passing: bool,
+}
+
+static ODILIA_TAPS: Lazy<Mutex<OdiliaTaps>> = Lazy::new(|| Mutex::new(OdiliaTaps {
+    policy: CapsLockPolicy::AlwaysOdilia,
+    held: false,
+    clean: false,
+    last_tap: None,
+    passing: false,
+}));
+
+pub fn set_caps_lock_policy(policy: CapsLockPolicy) {
+    lock(&ODILIA_TAPS).policy = policy;
+}
+
+pub fn get_caps_lock_policy() -> CapsLockPolicy {
+    lock(&ODILIA_TAPS).policy
+}
+
+/// Whether `ev` should reach the rest of the system despite being a press or release of the Odilia
+/// key, according to the [`CapsLockPolicy`]. Other events are only observed.
+fn odilia_key_passes_through(ev: &Event, odilia_key: RDevKey) -> bool {
+    let mut taps = lock(&ODILIA_TAPS);
+    match ev.event_type {
+        KeyPress(k) if k == odilia_key => {
+            // Auto-repeat keeps doing what the first press did
+            if !taps.held {
+                taps.held = true;
+                taps.clean = true;
+                taps.passing = taps.policy == CapsLockPolicy::DoubleTapToggles
+                    && taps.last_tap.is_some_and(|last| {
+                        ev.time.duration_since(last).is_ok_and(|gap| gap <= multi_tap_window())
+                    });
             }
-            let keybind = keybind.unwrap(); // should never panic due to above if
+            taps.policy == CapsLockPolicy::PassThrough || taps.passing
+        },
+        KeyRelease(k) if k == odilia_key => {
+            let passing = std::mem::replace(&mut taps.passing, false);
+            // The passed through tap completes the double tap, so it doesn't start another one
+            taps.last_tap = (taps.held && taps.clean && !passing).then_some(ev.time);
+            taps.held = false;
+            taps.clean = false;
+            taps.policy == CapsLockPolicy::PassThrough || passing
+        },
+        KeyPress(_) => {
+            taps.clean = false;
+            taps.last_tap = None;
+            false
+        },
+        _ => false,
+    }
+}
 
-            TX.with(|tx| {
-                let tx = tx.get().unwrap();
+static CHAR_ECHO: Lazy<Mutex<Option<mpsc::Sender<char>>>> = Lazy::new(|| Mutex::new(None));
 
-                if keybind.notify {
-                    // Notify us by sending the `Event` down the channel
-                    if let Err(e) = tx.blocking_send(keybind.clone()) {
-                        eprintln!("Warning: Failed to process key event: {}", e);
-                    }
-                }
-                // Decide whether to consume the action or pass it through
-                if keybind.consume {
-                    None
-                } else {
-                    Some(ev)
-                }
-            })
-        })
+/// Send every character typed from now on to `sender`, e.g. to speak typed characters.
+///
+/// Only key presses which reach the rest of the system are echoed, with Shift applied; keys which
+/// don't type anything (arrows, function keys...) and presses with Control, Alt, Meta or the Odilia
+/// modifier held are skipped. Nothing is consumed. When `sender` is full, characters are dropped
+/// rather than holding up input.
+pub fn enable_char_echo(sender: mpsc::Sender<char>) {
+    *lock(&CHAR_ECHO) = Some(sender);
+}
+
+pub fn disable_char_echo() {
+    *lock(&CHAR_ECHO) = None;
+}
+
+/// The character Shift turns `c` into on a US QWERTY layout.
+fn shifted(c: char) -> char {
+    match c {
+        '`' => '~', '1' => '!', '2' => '@', '3' => '#', '4' => '$', '5' => '%', '6' => '^',
+        '7' => '&', '8' => '*', '9' => '(', '0' => ')', '-' => '_', '=' => '+', '[' => '{',
+        ']' => '}', '\\' => '|', ';' => ':', '\'' => '"', ',' => '<', '.' => '>', '/' => '?',
+        c => c.to_ascii_uppercase(),
+    }
+}
+
+/// Echo the character a passed through key press typed, if any.
+fn echo_char(ev: &Event) {
+    let KeyPress(key) = ev.event_type else {
+        return;
+    };
+    let echo = lock(&CHAR_ECHO);
+    let Some(sender) = echo.as_ref() else {
+        return;
+    };
+    let mods = currently_pressed_modifiers();
+    if mods.intersects(!(Modifiers::SHIFT_L | Modifiers::SHIFT_R)) {
+        return;
+    }
+    // The OS knows the active layout; otherwise fall back to US QWERTY
+    let c = produced_char(ev).or_else(|| match rdev_key_to_odilia_key(&key)? {
+        Key::Other(c) if mods.is_empty() => Some(c),
+        Key::Other(c) => Some(shifted(c)),
+        Key::Space => Some(' '),
+        _ => None,
     });
+    if let Some(c) = c {
+        let _ = sender.try_send(c);
+    }
+}
+
+static RAW_CAPTURE: Lazy<Mutex<Option<mpsc::Sender<KeyEvent>>>> = Lazy::new(|| Mutex::new(None));
 
-    rx // Return the receiving end of the channel
+/// Send every key press to `sender` instead of the rest of the system, until [`end_raw_capture`]
+/// is called or Escape is pressed, e.g. for a prompt where the user types a command.
+///
+/// While capturing, no binding, combo or unhandled handler fires, and learn mode and
+/// [`capture_next_keybinding`][crate::keybinds::capture_next_keybinding] wait until it ends.
+/// Key presses are consumed; modifiers are sent along with the key they modify, not on their own.
+/// Key releases still pass through, so that keys held when the capture began don't get stuck.
+///
+/// Pressing Escape without modifiers ends the capture without sending it, so the receiver sees the
+/// channel close. When `sender` is full, key presses are dropped rather than holding up input.
+/// Beginning a new capture ends the previous one.
+pub fn begin_raw_capture(sender: mpsc::Sender<KeyEvent>) {
+    *lock(&RAW_CAPTURE) = Some(sender);
+}
+
+pub fn end_raw_capture() {
+    *lock(&RAW_CAPTURE) = None;
+}
+
+pub fn is_raw_capturing() -> bool {
+    lock(&RAW_CAPTURE).is_some()
+}
+
+/// Hand `kbm` to the raw capture, ending it on Escape or once the receiver is gone.
+fn capture_raw(kbm: KeyEvent) {
+    let mut capture = lock(&RAW_CAPTURE);
+    let Some(sender) = capture.as_ref() else {
+        return;
+    };
+    if kbm.key == Some(Key::Escape) && kbm.mods.is_empty() {
+        *capture = None;
+        return;
+    }
+    if let Err(mpsc::error::TrySendError::Closed(_)) = sender.try_send(kbm) {
+        *capture = None;
+    }
+}
+
+/// Why [`remap_key`] refused a remapping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemapError {
+    /// The target key or one of its modifiers has no physical key which could be simulated. This
+    /// includes [`Modifiers::ODILIA`], which only exists inside the screen reader.
+    Unsimulatable,
+    /// The target is itself remapped, or the source is the target of another remapping, which
+    /// could make keys remap each other forever.
+    Loop,
+}
+
+impl std::fmt::Display for RemapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RemapError::Unsimulatable => write!(f, "the target key can't be simulated"),
+            RemapError::Loop => write!(f, "the remapping would chain into another remapping"),
+        }
+    }
+}
+
+impl std::error::Error for RemapError {}
+
+/// The key presses a [`remap_key`] source is turned into: the modifiers, then the key.
+struct Remap {
+    from: KeyBinding,
+    to: KeyEvent,
+    keys: Vec<RDevKey>,
+}
+
+static REMAPS: Lazy<Mutex<Vec<Remap>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Physical keys held down whose press was remapped; their repeats and release are consumed too.
+static REMAPPED_HELD: Lazy<Mutex<Vec<RDevKey>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Simulated events which have not come back through the input monitoring system yet.
+static SIMULATED: Lazy<Mutex<Vec<EventType>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// The physical key which translates to `key`, if any.
+fn odilia_key_to_rdev_key(key: &Key) -> Option<RDevKey> {
+    ALL_RDEV_KEYS.iter().copied().find(|k| rdev_key_to_odilia_key(k).as_ref() == Some(key))
+}
+
+/// Physical modifier keys which together give `mods`, if there are any. The Odilia modifier key
+/// is never used, since pressing it for real may e.g. toggle CapsLock.
+fn simulatable_modifier_keys(mods: Modifiers) -> Option<Vec<RDevKey>> {
+    let odilia_key = get_odilia_modifier_key();
+    let mut covered = Modifiers::empty();
+    let mut keys = Vec::new();
+    for &k in ALL_RDEV_KEYS.iter().filter(|&&k| k != odilia_key) {
+        let m = rdev_keys_to_odilia_modifiers(&[k]);
+        if !m.is_empty() && mods.contains(m) && !covered.contains(m) {
+            covered |= m;
+            keys.push(k);
+        }
+    }
+    (covered == mods).then_some(keys)
+}
+
+/// Remap key presses matching `from` to `to`: whenever `from` would match, the key press is
+/// consumed and `to` is simulated instead, e.g. to swap two keys. The `repeat` of `to` is ignored.
+/// Simulating goes through the [backend's simulator][crate::backend::InputBackend::simulator].
+///
+/// Remappings apply before any binding is matched, but not in [learn mode][set_learn_mode] or while
+/// [capturing][begin_raw_capture]. While the remapped key is held, its auto-repeat and release are
+/// consumed too. Modifiers which are part of `from` are still held down, so the rest of the system
+/// sees them together with `to`; remapping works best for keys without modifiers.
+///
+/// Remapping `from` again replaces its target.
+/// # Errors
+/// * [`RemapError::Unsimulatable`] if `to` can't be typed on a keyboard.
+/// * [`RemapError::Loop`] if `to` is the source of a remapping, or `from` the target of one.
+pub fn remap_key(from: KeyBinding, to: KeyEvent) -> Result<(), RemapError> {
+    let mut keys = simulatable_modifier_keys(to.mods).ok_or(RemapError::Unsimulatable)?;
+    keys.push(to.key.as_ref().and_then(odilia_key_to_rdev_key).ok_or(RemapError::Unsimulatable)?);
+    let mut remaps = lock(&REMAPS);
+    let loops = remaps.iter().any(|remap| {
+        remap.from != from
+            && (remap.from.key == to.key && remap.from.mods == to.mods
+                || from.key == remap.to.key && from.mods == remap.to.mods)
+    });
+    if loops || from.key == to.key && from.mods == to.mods {
+        return Err(RemapError::Loop);
+    }
+    remaps.retain(|remap| remap.from != from);
+    remaps.push(Remap { from, to, keys });
+    Ok(())
+}
+
+/// Stop remapping `from`, returning whether it was remapped.
+pub fn unmap_key(from: &KeyBinding) -> bool {
+    let mut remaps = lock(&REMAPS);
+    let before = remaps.len();
+    remaps.retain(|remap| remap.from != *from);
+    remaps.len() != before
+}
+
+/// The remapping `kbm` triggers in the current mode of `manager`, if any.
+fn find_remap<'a>(remaps: &'a [Remap], manager: &InputManager, kbm: &KeyEvent) -> Option<&'a Remap> {
+    if remaps.is_empty() {
+        return None;
+    }
+    let sr_mode = manager.get_sr_mode_sync();
+    remaps.iter().find(|remap| matches(&remap.from, kbm, &sr_mode))
+}
+
+/// If `kbm` is remapped, simulate its target on another thread and return true.
+fn simulate_remap(manager: &InputManager, kbm: &KeyEvent) -> bool {
+    let remaps = lock(&REMAPS);
+    let Some(remap) = find_remap(&remaps, manager, kbm) else {
+        return false;
+    };
+    let events: Vec<EventType> = remap.keys.iter().map(|&k| KeyPress(k))
+        .chain(remap.keys.iter().rev().map(|&k| KeyRelease(k)))
+        .collect();
+    lock(&SIMULATED).extend(events.iter().copied());
+    std::thread::spawn(move || {
+        for event in events {
+            if let Err(e) = simulate(&event) {
+                tracing::warn!("Failed to simulate a remapped key: {}", e);
+                let mut simulated = lock(&SIMULATED);
+                if let Some(i) = simulated.iter().position(|pending| *pending == event) {
+                    simulated.remove(i);
+                }
+            }
+            // Give the OS time to process each event, as rdev recommends
+            std::thread::sleep(Duration::from_millis(SIMULATE_DELAY_MS));
+        }
+    });
+    true
+}
+
+const SIMULATE_DELAY_MS: u64 = 20;
+
+/// How the running backend simulates events.
+static SIMULATOR: OnceCell<Simulator> = OnceCell::new();
+
+fn simulate(event: &EventType) -> Result<(), BackendError> {
+    let simulator = SIMULATOR.get().ok_or("no input backend was started")?;
+    simulator(event)
+}
+
+/// Whether `ev` is an event simulated for a remapping; each one is only recognised once.
+fn is_simulated(ev: &Event) -> bool {
+    let mut simulated = lock(&SIMULATED);
+    match simulated.iter().position(|e| *e == ev.event_type) {
+        Some(i) => {
+            simulated.remove(i);
+            true
+        },
+        None => false,
+    }
+}
+
+/// When the last event of any kind came in.
+static LAST_INPUT: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
+/// Wakes idle watchers on every event.
+static INPUT_ACTIVITY: Lazy<Notify> = Lazy::new(Notify::new);
+
+/// Stops the idle callback set with [`on_idle`] when dropped.
+#[must_use = "the callback is cancelled as soon as the handle is dropped"]
+pub struct IdleHandle(JoinHandle<()>);
+
+impl IdleHandle {
+    /// Stop the idle callback; the same as dropping the handle.
+    pub fn cancel(self) {}
+}
+
+impl Drop for IdleHandle {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Run `callback` whenever no input event of any kind (key or mouse) came in for `duration`, e.g.
+/// to announce that the screen reader is still running.
+///
+/// The callback runs once per quiet period: after it ran, the next input event starts a new one.
+/// Time is measured with [`tokio::time`], so a paused clock can be advanced in tests. The
+/// callback runs on the input monitoring system's runtime, or, before initialisation, on the
+/// current one.
+/// # Panics
+/// If called before the input monitoring system was initialised and outside of a tokio runtime.
+pub fn on_idle(duration: Duration, callback: AsyncFn) -> IdleHandle {
+    let runtime = RUNTIME.get().cloned().unwrap_or_else(Handle::current);
+    IdleHandle(runtime.spawn(watch_idle(duration, callback)))
+}
+
+async fn watch_idle(duration: Duration, callback: AsyncFn) {
+    let started = Instant::now();
+    // The input which started the quiet period the callback last ran for
+    let mut fired_for = None;
+    loop {
+        // Listen before looking, so that no event can slip in between
+        let notified = INPUT_ACTIVITY.notified();
+        let mut notified = std::pin::pin!(notified);
+        notified.as_mut().enable();
+        let last = lock(&LAST_INPUT).map_or(started, |last| last.max(started));
+        let deadline = last + duration;
+        if Instant::now() < deadline {
+            tokio::time::sleep_until(deadline).await;
+        } else if fired_for != Some(last) {
+            fired_for = Some(last);
+            callback().await;
+        } else {
+            notified.await;
+        }
+    }
+}
+
+static ALWAYS_CONSUMED: Lazy<Mutex<Vec<Key>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Keep presses and releases of `keys` from the rest of the system, whether or not a binding
+/// matches them, e.g. for a key only ever used for screen reader commands. This replaces the keys
+/// given before; an empty list consumes nothing extra.
+///
+/// Bindings for these keys still fire as usual. [Observe only mode][set_observe_only] still lets
+/// them through.
+pub fn always_consume_keys(keys: Vec<Key>) {
+    *lock(&ALWAYS_CONSUMED) = keys;
+}
+
+/// Whether `ev` presses or releases one of the keys given to [`always_consume_keys`].
+fn is_always_consumed(ev: &Event) -> bool {
+    let (KeyPress(k) | KeyRelease(k)) = ev.event_type else {
+        return false;
+    };
+    rdev_key_to_odilia_key(&k).is_some_and(|key| is_always_consumed_key(&key))
+}
+
+fn is_always_consumed_key(key: &Key) -> bool {
+    let always_consumed = lock(&ALWAYS_CONSUMED);
+    !always_consumed.is_empty() && !is_observe_only() && always_consumed.contains(key)
+}
+
+/// Whether NumLock is on, as far as the presses we passed through tell.
+static NUM_LOCK: AtomicBool = AtomicBool::new(false);
+
+/// Tell the crate whether NumLock is on, e.g. at startup, since it only sees the NumLock presses
+/// made after it started listening. Presses of NumLock that are passed through toggle it from
+/// there.
+pub fn set_num_lock(on: bool) {
+    NUM_LOCK.store(on, Ordering::Relaxed);
+}
+
+/// Whether NumLock is on, going by [`set_num_lock`] and the NumLock presses seen since.
+pub fn is_num_lock_on() -> bool {
+    NUM_LOCK.load(Ordering::Relaxed)
+}
+
+/// What a numpad key means under the current NumLock state: with NumLock off the classic
+/// navigation layout (7 is [`Key::Home`], 8 is [`Key::Up`], 9 is [`Key::PageUp`], and so on), with
+/// it on the digit it types. Any other key, and a `Kp5` with NumLock off, is returned as is.
+/// The number row also translates to `Kp0`-`Kp9`, so its digits are treated the same way.
+///
+/// Hosts can use this to give the numpad the review commands classic screen readers put there.
+pub fn numpad_key(key: &Key) -> Key {
+    if is_num_lock_on() {
+        return match key {
+            Key::Kp0 => Key::Other('0'),
+            Key::Kp1 => Key::Other('1'),
+            Key::Kp2 => Key::Other('2'),
+            Key::Kp3 => Key::Other('3'),
+            Key::Kp4 => Key::Other('4'),
+            Key::Kp5 => Key::Other('5'),
+            Key::Kp6 => Key::Other('6'),
+            Key::Kp7 => Key::Other('7'),
+            Key::Kp8 => Key::Other('8'),
+            Key::Kp9 => Key::Other('9'),
+            Key::KpDelete => Key::Other('.'),
+            other => other.clone(),
+        };
+    }
+    match key {
+        Key::Kp0 => Key::Insert,
+        Key::Kp1 => Key::End,
+        Key::Kp2 => Key::Down,
+        Key::Kp3 => Key::PageDown,
+        Key::Kp4 => Key::Left,
+        Key::Kp6 => Key::Right,
+        Key::Kp7 => Key::Home,
+        Key::Kp8 => Key::Up,
+        Key::Kp9 => Key::PageUp,
+        Key::KpDelete => Key::Delete,
+        other => other.clone(),
+    }
+}
+
+/// The modes in which digits typed before a binding count how often it should run.
+static COUNT_PREFIX_MODES: Lazy<Mutex<Vec<ScreenReaderMode>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// The count typed so far, and when its last digit was typed.
+static COUNT_PREFIX: Lazy<Mutex<Option<(u32, SystemTime)>>> = Lazy::new(|| Mutex::new(None));
+/// The count each binding was last triggered with, until its handler takes it.
+static BINDING_COUNTS: Lazy<Mutex<HashMap<KeyBinding, u32>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// How long a typed count waits for the next digit or the binding it is for.
+const COUNT_PREFIX_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Let digits typed before a binding in one of `modes` count how often it should run, like "3 j"
+/// in Vim; an empty list (the default) turns counts off.
+///
+/// In these modes, a digit key (on the number row or the keypad) pressed without modifiers and not
+/// bound to anything itself is consumed and added to the count. The next binding which fires gets
+/// the count; handlers added with
+/// [`add_counted_keybind`][crate::keybinds::add_counted_keybind] receive it. Any other unbound key,
+/// or waiting for more than two seconds, drops the count. Counts stop growing at [`u32::MAX`].
+pub fn set_count_prefix_modes(modes: Vec<ScreenReaderMode>) {
+    *lock(&COUNT_PREFIX_MODES) = modes;
+    *lock(&COUNT_PREFIX) = None;
+}
+
+/// The digit `kbm` types, if it is a plain digit key.
+fn count_digit(kbm: &KeyEvent) -> Option<u32> {
+    if !kbm.mods.is_empty() {
+        return None;
+    }
+    match kbm.key.as_ref()? {
+        Key::Other(c) => c.to_digit(10),
+        Key::Kp0 => Some(0),
+        Key::Kp1 => Some(1),
+        Key::Kp2 => Some(2),
+        Key::Kp3 => Some(3),
+        Key::Kp4 => Some(4),
+        Key::Kp5 => Some(5),
+        Key::Kp6 => Some(6),
+        Key::Kp7 => Some(7),
+        Key::Kp8 => Some(8),
+        Key::Kp9 => Some(9),
+        _ => None,
+    }
+}
+
+/// Add `kbm` to the count if it is a digit and counts are on in the current mode, returning
+/// whether it was. Any other key drops the count.
+fn accumulate_count(manager: &InputManager, kbm: &KeyEvent, at: SystemTime) -> bool {
+    let mut count = lock(&COUNT_PREFIX);
+    let Some(digit) = counted_digit(manager, kbm) else {
+        *count = None;
+        return false;
+    };
+    let so_far = count
+        .filter(|(_, last)| at.duration_since(*last).is_ok_and(|waited| waited <= COUNT_PREFIX_TIMEOUT))
+        .map_or(0, |(so_far, _)| so_far);
+    *count = Some((so_far.saturating_mul(10).saturating_add(digit), at));
+    true
+}
+
+/// The digit `kbm` adds to the count, if it is a digit and counts are on in the current mode.
+fn counted_digit(manager: &InputManager, kbm: &KeyEvent) -> Option<u32> {
+    count_digit(kbm).filter(|_| {
+        let modes = lock(&COUNT_PREFIX_MODES);
+        !modes.is_empty() && modes.contains(&manager.get_sr_mode_sync())
+    })
+}
+
+/// Hand the count typed so far, if any and recent enough, to `keybind`.
+fn assign_count(keybind: &KeyBinding, at: SystemTime) {
+    let count = lock(&COUNT_PREFIX)
+        .take()
+        .filter(|(_, last)| at.duration_since(*last).is_ok_and(|waited| waited <= COUNT_PREFIX_TIMEOUT));
+    let mut counts = lock(&BINDING_COUNTS);
+    match count {
+        Some((count, _)) => counts.insert(keybind.clone(), count),
+        None => counts.remove(keybind),
+    };
+}
+
+/// The count `keybind` was last triggered with, or 1 without one; see [`set_count_prefix_modes`].
+pub(crate) fn take_count(keybind: &KeyBinding) -> u32 {
+    lock(&BINDING_COUNTS).remove(keybind).unwrap_or(1)
+}
+
+/// A translated key event together with every non-modifier key held down, which the event itself
+/// only carries the first of; see [`enable_chord_events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChordEvent {
+    pub event: KeyEvent,
+    /// The held keys, in the order they were pressed. Keys without an Odilia equivalent are left
+    /// out.
+    pub held: Vec<Key>,
+}
+
+static CHORD_EVENTS: Lazy<Mutex<Option<mpsc::Sender<ChordEvent>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Send a [`ChordEvent`] to `sender` for every key event from now on, e.g. for a chord visualizer
+/// which wants to show A and N when both are held.
+///
+/// Events are sent before matching, whatever happens to them afterwards; modifier taps, and key
+/// presses taken by a [raw capture][begin_raw_capture], are not sent. When `sender` is full,
+/// events are dropped rather than holding up input.
+pub fn enable_chord_events(sender: mpsc::Sender<ChordEvent>) {
+    *lock(&CHORD_EVENTS) = Some(sender);
+}
+
+pub fn disable_chord_events() {
+    *lock(&CHORD_EVENTS) = None;
+}
+
+fn send_chord_event(kbm: &KeyEvent, current_keys: &[RDevKey]) {
+    let chord_events = lock(&CHORD_EVENTS);
+    let Some(sender) = chord_events.as_ref() else {
+        return;
+    };
+    let held = current_keys
+        .iter()
+        .filter(|k| !is_modifier(k))
+        .filter_map(rdev_key_to_odilia_key)
+        .collect();
+    let _ = sender.try_send(ChordEvent { event: kbm.clone(), held });
+}
+
+/// How long the keybinding pipeline took per event, as recorded while
+/// [latency tracking][set_latency_tracking] is on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencyStats {
+    /// How many events were timed.
+    pub count: u64,
+    /// How long the most recent event took.
+    pub last: Duration,
+    /// How long the slowest event took.
+    pub max: Duration,
+    /// How long all timed events took together.
+    pub total: Duration,
+}
+
+impl LatencyStats {
+    /// How long an event took on average, or `None` if none were timed.
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| self.total / self.count as u32)
+    }
+
+    fn record(&mut self, took: Duration) {
+        self.count += 1;
+        self.last = took;
+        self.max = self.max.max(took);
+        self.total += took;
+    }
+}
+
+static LATENCY_TRACKING: AtomicBool = AtomicBool::new(false);
+static LATENCY: Lazy<Mutex<LatencyStats>> = Lazy::new(|| Mutex::new(LatencyStats::default()));
+
+/// Start or stop timing how long the keybinding pipeline takes for each input event, e.g. to catch
+/// matching becoming slow. See [`grab_latency_stats`]. Off by default; while off, the only cost is
+/// checking this flag.
+pub fn set_latency_tracking(enabled: bool) {
+    LATENCY_TRACKING.store(enabled, Ordering::Relaxed);
+}
+
+/// How long the keybinding pipeline took per event since [latency tracking][set_latency_tracking]
+/// was turned on, or since [`reset_latency_stats`].
+pub fn grab_latency_stats() -> LatencyStats {
+    *lock(&LATENCY)
+}
+
+/// Forget the timings [`grab_latency_stats`] reports.
+pub fn reset_latency_stats() {
+    *lock(&LATENCY) = LatencyStats::default();
+}
+
+/// The state the keybinding pipeline carries from one event to the next, saved by
+/// [`snapshot_state`]: the keys held down, the modifier taps counted so far, a pending count prefix
+/// and the [active manager][InputManager::activate]'s screen reader mode. Settings (policies,
+/// timeouts and the like) are not part of it.
+#[derive(Clone, Debug)]
+pub struct InputState {
+    keys: KeyState,
+    tap_mods: Modifiers,
+    tap_interrupted: bool,
+    tap_last: Option<(Modifiers, SystemTime, u8)>,
+    tap_pressed: Option<SystemTime>,
+    count_prefix: Option<(u32, SystemTime)>,
+    mode: ScreenReaderMode,
+}
+
+/// Save the state of the keybinding pipeline, e.g. halfway through a chord, to put it back later
+/// with [`restore_state`]. Useful for tests driving [`MockBackend`][crate::backend::MockBackend],
+/// or to pick up where a restarted input thread left off.
+///
+/// Like [`get_sr_mode_sync`][crate::keybinds::get_sr_mode_sync], this blocks; call it from a
+/// synchronous context, not directly from async code.
+pub fn snapshot_state() -> InputState {
+    let keys = lock(&KEY_STATE).clone();
+    let tap = lock(&MODIFIER_TAP);
+    InputState {
+        keys,
+        tap_mods: tap.mods,
+        tap_interrupted: tap.interrupted,
+        tap_last: tap.last,
+        tap_pressed: tap.pressed,
+        count_prefix: *lock(&COUNT_PREFIX),
+        mode: InputManager::active().get_sr_mode_sync(),
+    }
+}
+
+/// Put back the state saved by [`snapshot_state`], so that the next event is matched as if
+/// nothing had happened since. A pending tap/hold or repeat binding is not part of the state, and
+/// fires (or not) as it would have.
+///
+/// Blocks, like [`snapshot_state`].
+///
+/// # Errors
+/// If the saved mode is no longer [registered][crate::keybinds::register_mode], the mode is left
+/// as it is, but the rest of the state is still restored.
+pub fn restore_state(state: InputState) -> Result<(), UnknownMode> {
+    *lock(&KEY_STATE) = state.keys;
+    {
+        let mut tap = lock(&MODIFIER_TAP);
+        tap.mods = state.tap_mods;
+        tap.interrupted = state.tap_interrupted;
+        tap.last = state.tap_last;
+        tap.pressed = state.tap_pressed;
+    }
+    *lock(&COUNT_PREFIX) = state.count_prefix;
+    InputManager::active().set_sr_mode_sync(state.mode)
+}
+
+/// Run a single [`Event`] through the keybinding pipeline.
+///
+/// Returns the event if it should be passed through to the rest of the system, or `None` if it was
+/// consumed.
+pub(crate) fn process_event(ev: Event) -> Option<Event> {
+    if !config().monitored_events.contains(&EventKind::of(&ev.event_type)) {
+        // Even an ignored event shows that the user is there and the backend is capturing
+        note_input();
+        return Some(ev);
+    }
+    if !LATENCY_TRACKING.load(Ordering::Relaxed) {
+        return handle_event(ev);
+    }
+    let start = Instant::now();
+    let result = handle_event(ev);
+    lock(&LATENCY).record(start.elapsed());
+    result
+}
+
+/// Record that an event came in, for [`on_idle`] and [`backend_ready`].
+fn note_input() {
+    *lock(&LAST_INPUT) = Some(Instant::now());
+    INPUT_ACTIVITY.notify_waiters();
+    if !BACKEND_READY.load(Ordering::Relaxed) {
+        mark_backend_ready();
+    }
+}
+
+fn handle_event(ev: Event) -> Option<Event> {
+    // Simulated events must not be remapped or matched again
+    if SHUTDOWN.is_requested() || is_simulated(&ev) {
+        return Some(ev);
+    }
+    note_input();
+    let ev = filter_event(ev)?;
+    let odilia_key = get_odilia_modifier_key();
+    let is_odilia_key = matches!(ev.event_type, KeyPress(k) | KeyRelease(k) if k == odilia_key);
+    let native = odilia_key_passes_through(&ev, odilia_key);
+    // Read once, so that the whole event is matched against the same bindings
+    let manager = InputManager::active();
+    let passthrough = route_event(&manager, ev.clone()).filter(|passed| !is_always_consumed(passed));
+    if let Some(passed) = &passthrough {
+        echo_char(passed);
+        if let KeyPress(RDevKey::NumLock) = passed.event_type {
+            NUM_LOCK.fetch_xor(true, Ordering::Relaxed);
+        }
+    }
+    // Unless the policy says otherwise, the Odilia key only acts as a modifier; it must not also
+    // e.g. toggle CapsLock
+    if is_odilia_key {
+        native.then_some(ev)
+    } else {
+        passthrough
+    }
+}
+
+fn route_event(manager: &InputManager, ev: Event) -> Option<Event> {
+    let mut state = lock(&KEY_STATE);
+    let grace = *lock(&MODIFIER_RELEASE_GRACE);
+    state.expire_released(Some(ev.time), grace);
+    let KeyState { current: current_keys, last_press, press_times, released, spent } = &mut *state;
+    // Read once, so that the whole event is handled in the same mode
+    let learn_mode = is_learn_mode();
+    let observe_only = is_observe_only();
+
+    if let KeyPress(_) = ev.event_type {
+        // Forget a half-finished chord if the user hesitated for too long
+        let timeout = *lock(&CHORD_TIMEOUT);
+        if let (Some(timeout), Some(last)) = (timeout, *last_press) {
+            if ev.time.duration_since(last).is_ok_and(|waited| waited > timeout) {
+                current_keys.clear();
+                released.clear();
+                spent.clear();
+            }
+        }
+        *last_press = Some(ev.time);
+    }
+
+    let is_new = match ev.event_type {
+        // Pressing a modifier again within its grace period continues the chord
+        KeyPress(k) if released.iter().any(|&(r, _)| r == k) => {
+            released.retain(|&(r, _)| r != k);
+            false
+        },
+        KeyRelease(k) if grace.is_some() && is_modifier(&k) && current_keys.contains(&k) => {
+            released.push((k, ev.time));
+            false
+        },
+        _ => is_new_key_event(&ev, current_keys),
+    };
+    // What is physically held, for telling modifier taps apart
+    let without_grace = (!released.is_empty()).then(|| without_released(current_keys, released));
+    let held: &[RDevKey] = without_grace.as_deref().unwrap_or(current_keys);
+    match ev.event_type {
+        KeyPress(k) if is_new => {
+            press_times.entry(k).or_insert(ev.time);
+        },
+        KeyRelease(k) => {
+            press_times.remove(&k);
+            spent.retain(|&s| s != k);
+        },
+        _ => {},
+    }
+    // Releasing a tap/hold key decides between the two; the release goes the same way as the press
+    if let Some(consume) = release_tap_hold(&ev) {
+        return if consume { None } else { Some(ev) };
+    }
+    if is_raw_capturing() {
+        // Keep the modifier tap state up to date, but nothing fires
+        let _ = modifier_tap_event(&ev, held);
+        let KeyPress(_) = ev.event_type else {
+            return Some(ev);
+        };
+        let o_event = rdev_event_to_odilia_event(&chord_keys(current_keys, spent));
+        if is_new && o_event.key.is_some() {
+            end_chord(current_keys, spent);
+            *last_press = None;
+            capture_raw(o_event);
+        }
+        return None;
+    }
+    // The repeats and release of a remapped key belong to its target
+    if let KeyPress(k) | KeyRelease(k) = ev.event_type {
+        let mut remapped = lock(&REMAPPED_HELD);
+        if remapped.contains(&k) {
+            if let KeyRelease(_) = ev.event_type {
+                remapped.retain(|&held| held != k);
+            }
+            let _ = modifier_tap_event(&ev, held);
+            return None;
+        }
+    }
+    // Modifiers tapped on their own can only be matched once they are released
+    if let Some(tap_event) = modifier_tap_event(&ev, held) {
+        // The tap ends the chord, so the grace period is over
+        for (k, _) in released.drain(..) {
+            current_keys.retain(|&held| held != k);
+        }
+        record_event(&tap_event);
+        wake_waiters_sync(&tap_event);
+        if learn_mode {
+            notify_learned(manager, &tap_event);
+            return Some(ev);
+        }
+        settle_pending_repeat(&tap_event);
+        match manager.keyevent_match_sync(&tap_event) {
+            // Another tap may still follow; decide on the notification once it's clear
+            Some(keybind)
+                if !observe_only
+                    && get_repeat_policy() == RepeatPolicy::Exact
+                    && manager.has_higher_repeat_sync(&tap_event) =>
+            {
+                defer_repeat(manager, tap_event, keybind);
+            },
+            Some(keybind) => notify_keybind(manager, &keybind),
+            None => {},
+        }
+        // The presses were passed through, so the release is too
+        return Some(ev);
+    }
+    // if the event is not new (i.e. a held key), just passthrough the event
+    if !is_new {
+        return Some(ev);
+    }
+
+    // Phantom keys end chords of their own, so count everything held
+    if is_ghosting(current_keys) {
+        return Some(ev);
+    }
+    // Decide what to do with this `Event`
+    let chord = chord_keys(current_keys, spent);
+    let o_event = rdev_event_to_odilia_event(&chord);
+    // so that modifier-only bindings don't fire while a chord is still being pressed
+    if o_event.key.is_none() {
+        return Some(ev);
+    }
+    record_event(&o_event);
+    wake_waiters_sync(&o_event);
+    // Keys which ended an earlier chord are still held, so they are shown too
+    send_chord_event(&o_event, current_keys);
+    if learn_mode {
+        end_chord(current_keys, spent);
+        *last_press = None;
+        notify_learned(manager, &o_event);
+        return Some(ev);
+    }
+    // A pending capture swallows the next complete key combination
+    if capture_keyevent_sync(&o_event) {
+        *last_press = None;
+        return None;
+    }
+    if let (KeyPress(k), false) = (ev.event_type, observe_only) {
+        if simulate_remap(manager, &o_event) {
+            lock(&REMAPPED_HELD).push(k);
+            end_chord(current_keys, spent);
+            *last_press = None;
+            return None;
+        }
+    }
+    // Combos take precedence once their last key goes down; their other keys may have ended a
+    // chord of their own already
+    let combo = held_combo(manager, current_keys, press_times, o_event.mods).filter(|_| !observe_only);
+    if let Some(combo) = combo {
+        end_chord(current_keys, spent);
+        *last_press = None;
+        let consume = combo.consume;
+        let manager = manager.clone();
+        RUNTIME.get().unwrap().spawn(async move { manager.run_combo_func(&combo).await });
+        return if consume { None } else { Some(ev) };
+    }
+    let keybind: Option<KeyBinding> = manager.keyevent_match_char_sync(&o_event, produced_char(&ev));
+    /* if a matching keybinding is not found, pass through the event */
+    if keybind.is_none() {
+      // an unmatched key ends the chord; only the modifiers still held carry over
+      end_chord(current_keys, spent);
+      if !observe_only && accumulate_count(manager, &o_event, ev.time) {
+        return None;
+      }
+      if let Some(handler) = manager.unhandled_keyevent_sync(&o_event).filter(|_| !observe_only) {
+        RUNTIME.get().unwrap().spawn(run_handler(handler));
+      }
+      return Some(ev);
+    }
+    let keybind = keybind.unwrap(); // should never panic due to above if
+    *last_press = None;
+    assign_count(&keybind, ev.time);
+
+    if let (Some(threshold), KeyPress(key), false) =
+        (manager.hold_threshold_sync(&keybind), ev.event_type, observe_only)
+    {
+        // Whether this is a tap or a hold is only known later
+        let consume = manager.consumes_sync(&keybind, &o_event);
+        start_tap_hold(manager, keybind, consume, key, ev.time, threshold);
+        return if consume { None } else { Some(ev) };
+    }
+
+    dispatch_keybind(manager, ev, &o_event, &keybind, observe_only)
+}
+
+/// Send a matched [`KeyBinding`] of `manager` down the channel, if it wants to be notified about.
+fn notify_keybind(manager: &InputManager, keybind: &KeyBinding) {
+    let tx = TX.get().unwrap();
+    if keybind.notify && manager.throttle_allows_sync(keybind, SystemTime::now()) {
+        // Notify us by sending the `Event` down the channel
+        if let Err(e) = tx.blocking_send(keybind.clone()) {
+            eprintln!("Warning: Failed to process key event: {}", e);
+        }
+    }
+}
+
+/// The [`Combo`] completed by the non-modifier keys in `current_keys`, if any.
+fn held_combo(
+    manager: &InputManager,
+    current_keys: &[RDevKey],
+    press_times: &HashMap<RDevKey, SystemTime>,
+    mods: Modifiers,
+) -> Option<Combo> {
+    let held: Vec<(Key, SystemTime)> = current_keys
+        .iter()
+        .filter_map(|k| Some((rdev_key_to_odilia_key(k)?, *press_times.get(k)?)))
+        .collect();
+    if held.len() < 2 {
+        return None;
+    }
+    manager.combo_match_sync(&held, mods)
+}
+
+/// The single character a key press typed in the active layout, if it typed one.
+fn produced_char(ev: &Event) -> Option<char> {
+    let mut chars = ev.name.as_deref()?.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if !c.is_control() => Some(c),
+        _ => None,
+    }
+}
+
+/// Act on a matched [`KeyBinding`], returning the event if it should be passed through.
+fn dispatch_keybind(
+    manager: &InputManager,
+    ev: Event,
+    kbm: &KeyEvent,
+    keybind: &KeyBinding,
+    observe_only: bool,
+) -> Option<Event> {
+    notify_keybind(manager, keybind);
+    // Decide whether to consume the action or pass it through
+    if !observe_only && manager.consumes_sync(keybind, kbm) {
+        None
+    } else {
+        Some(ev)
+    }
+}
+
+/// What the input monitoring system does with a key event; see [`classify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventAction {
+    /// The event reaches the rest of the system and nothing is sent down the channel.
+    PassThrough,
+    /// The event reaches the rest of the system and a binding is sent down the channel.
+    Notify,
+    /// The event is kept from the rest of the system and nothing is sent down the channel.
+    Consume,
+    /// The event is kept from the rest of the system and a binding is sent down the channel.
+    NotifyAndConsume,
+}
+
+impl EventAction {
+    fn new(notify: bool, consume: bool) -> Self {
+        match (notify, consume) {
+            (false, false) => EventAction::PassThrough,
+            (true, false) => EventAction::Notify,
+            (false, true) => EventAction::Consume,
+            (true, true) => EventAction::NotifyAndConsume,
+        }
+    }
+
+    pub fn consumes(self) -> bool {
+        matches!(self, EventAction::Consume | EventAction::NotifyAndConsume)
+    }
+
+    pub fn notifies(self) -> bool {
+        matches!(self, EventAction::Notify | EventAction::NotifyAndConsume)
+    }
+}
+
+/// What the input monitoring system would do with `kbm` right now, and the binding it matched, if
+/// any, without running anything or changing any state.
+///
+/// This makes the same decisions as the input monitoring thread: it accounts for
+/// [raw capture][begin_raw_capture] and a [pending capture][crate::keybinds::capture_next_keybinding]
+/// (the key is consumed), [learn mode][set_learn_mode] (everything is notified and passed through),
+/// [remapped keys][remap_key] and count prefix digits (consumed),
+/// [observe only mode][set_observe_only] (nothing is consumed), modifier taps (never consumed, see
+/// [`set_modifier_tap_timing`]), [`always_consume_keys`] and
+/// [throttling][crate::keybinds::set_keybind_throttle]. It does not account for tap/hold bindings
+/// or combos, which depend on how long keys are held. Bindings are those of the
+/// [active manager][InputManager::activate]. Like
+/// [`keyevent_match_sync`][crate::keybinds::keyevent_match_sync], this must not be called from
+/// async code.
+pub fn classify(kbm: &KeyEvent) -> (EventAction, Option<KeyBinding>) {
+    decide(&InputManager::active(), kbm)
+}
+
+/// [`classify`] for the bindings of `manager`, mirroring [`route_event`].
+pub(crate) fn decide(manager: &InputManager, kbm: &KeyEvent) -> (EventAction, Option<KeyBinding>) {
+    // Modifier taps only come on release, which raw capture and captures let through
+    let has_key = kbm.key.is_some();
+    if has_key && is_raw_capturing() {
+        return (EventAction::Consume, None);
+    }
+    if is_learn_mode() {
+        return (EventAction::Notify, None);
+    }
+    if has_key && capture_pending_sync() {
+        return (EventAction::Consume, None);
+    }
+    let observe_only = is_observe_only();
+    if has_key && !observe_only && find_remap(&lock(&REMAPS), manager, kbm).is_some() {
+        return (EventAction::Consume, None);
+    }
+    let always_consumed = kbm.key.as_ref().is_some_and(is_always_consumed_key);
+    match manager.predict_sync(kbm) {
+        Some((keybind, consume, notify)) => {
+            let consumed = has_key && !observe_only && (consume || always_consumed);
+            (EventAction::new(notify, consumed), Some(keybind))
+        },
+        None => {
+            let counted = has_key && !observe_only && counted_digit(manager, kbm).is_some();
+            (EventAction::new(false, always_consumed || counted), None)
+        },
+    }
+}
+
+/// An error encountered while initialising the input monitoring system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitError {
+    /// Not called from within a tokio runtime, which is needed to spawn handlers.
+    NoRuntime,
+    /// The input monitoring system was already initialised.
+    AlreadyInitialized,
+    /// [`init_sync`] could not start its runtime.
+    RuntimeFailed,
+}
+
+impl std::fmt::Display for InitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InitError::NoRuntime => {
+                write!(f, "input monitoring must be initialised from within a tokio runtime")
+            },
+            InitError::AlreadyInitialized => write!(f, "input monitoring was already initialised"),
+            InitError::RuntimeFailed => write!(f, "could not start a runtime for input monitoring"),
+        }
+    }
+}
+
+impl std::error::Error for InitError {}
+
+/// Initialise the input monitoring system, returning an [`mpsc::Receiver`] which can be used to
+/// recieve input events.
+///
+/// This must be called from within a tokio runtime, which is used to run handlers spawned by the
+/// input monitoring system.
+///
+/// With the `grab` feature (the default), this spawns a thread which grabs all input through
+/// [`RdevBackend`]. Without it, no OS input is monitored: [`MockBackend`] is started instead, and
+/// events are fed in through [`MockBackend::push_event`]. Another backend can be started with
+/// [`create_keybind_channel_with_backend`].
+/// # Errors
+/// * [`InitError::NoRuntime`] if called outside of a tokio runtime.
+/// * [`InitError::AlreadyInitialized`] if called more than once in the same program.
+pub fn create_keybind_channel_with_config(
+    config: InputConfig,
+) -> Result<mpsc::Receiver<KeyBinding>, InitError> {
+    let rx = init_channel(config)?;
+    #[cfg(feature = "grab")]
+    start_backend(Box::new(RdevBackend));
+    #[cfg(not(feature = "grab"))]
+    start_backend(Box::new(MockBackend));
+    Ok(rx)
+}
+
+/// Like [`create_keybind_channel_with_config`], but reads input from `backend` instead of the
+/// default one.
+pub fn create_keybind_channel_with_backend(
+    config: InputConfig,
+    backend: Box<dyn InputBackend>,
+) -> Result<mpsc::Receiver<KeyBinding>, InitError> {
+    let rx = init_channel(config)?;
+    start_backend(backend);
+    Ok(rx)
+}
+
+/// Spawn a synchronous input monitoring thread running `backend`.
+fn start_backend(backend: Box<dyn InputBackend>) {
+    let shutdown = SHUTDOWN.clone();
+    let (tx, rx) = oneshot::channel();
+    *lock(&BACKEND_EXIT) = Some(rx);
+    let _ = SIMULATOR.set(backend.simulator());
+    std::thread::spawn(move || {
+        if backend.ready_on_start() {
+            mark_backend_ready();
+        }
+        // Start the event loop
+        let result = backend.run(process_event, shutdown);
+        if let Err(e) = &result {
+            tracing::error!("Input monitoring stopped: {}", e);
+        }
+        let _ = tx.send(result);
+    });
+}
+
+/// Wait for the input monitoring backend to stop, returning why it did, e.g. so that the host can
+/// tell the user that input could not be grabbed.
+///
+/// Most backends only stop on an error, or after [`shutdown_input`]. Returns `None` if no backend
+/// was started, or if its result was already taken by an earlier call.
+pub async fn backend_stopped() -> Option<Result<(), BackendError>> {
+    let rx = lock(&BACKEND_EXIT).take()?;
+    // The thread always sends its result, unless it panicked
+    Some(rx.await.unwrap_or_else(|_| Err("input monitoring thread panicked".into())))
+}
+
+static BACKEND_READY: AtomicBool = AtomicBool::new(false);
+static BACKEND_READY_NOTIFY: Lazy<Notify> = Lazy::new(Notify::new);
+
+fn mark_backend_ready() {
+    if !BACKEND_READY.swap(true, Ordering::SeqCst) {
+        BACKEND_READY_NOTIFY.notify_waiters();
+    }
+}
+
+/// Wait until the input monitoring backend is capturing input, e.g. before simulating input that
+/// must not be missed.
+///
+/// Backends which capture input right away (like [`MockBackend`][crate::backend::MockBackend])
+/// are ready as soon as their thread starts. The default [`rdev::grab`] backend takes a moment to
+/// grab the devices and does not say when it is done, so it only counts as ready once the first
+/// event has come through it. Waits forever if no backend is ever started.
+pub async fn backend_ready() {
+    loop {
+        // Listen before looking, so that the backend can't become ready in between
+        let notified = BACKEND_READY_NOTIFY.notified();
+        let mut notified = std::pin::pin!(notified);
+        notified.as_mut().enable();
+        if BACKEND_READY.load(Ordering::SeqCst) {
+            return;
+        }
+        notified.await;
+    }
+}
+
+fn init_channel(config: InputConfig) -> Result<mpsc::Receiver<KeyBinding>, InitError> {
+    let runtime = Handle::try_current().map_err(|_| InitError::NoRuntime)?;
+    let slots = config.max_running_handlers.map(|max| Arc::new(Semaphore::new(max)));
+    // tokio panics on an empty channel
+    let capacity = config.queue_capacity.max(1);
+    CONFIG.set(config).map_err(|_| InitError::AlreadyInitialized)?;
+    if let Some(slots) = slots {
+        let _ = HANDLER_SLOTS.set(slots);
+    }
+    // Create the channel for communication between the input monitoring thread and async tasks
+    let (tx, rx) = mpsc::channel(capacity);
+    TX.set(tx).map_err(|_| InitError::AlreadyInitialized)?;
+    RUNTIME.set(runtime).map_err(|_| InitError::AlreadyInitialized)?;
+    Ok(rx) // Return the receiving end of the channel
+}
+
+/// Like [`create_keybind_channel_with_config`], for hosts which don't use tokio, or call this from
+/// outside of async code.
+///
+/// The input monitoring system gets a tokio runtime of its own on a dedicated thread, which runs
+/// every handler. Receive notifications with [`mpsc::Receiver::blocking_recv`], run their handlers
+/// with [`run_keybind_func_sync`][crate::keybinds::run_keybind_func_sync], and bind synchronous
+/// handlers with [`add_sync_keybind`][crate::keybinds::add_sync_keybind]. The other `_sync`
+/// functions can be called from any thread which is not running async code.
+/// # Errors
+/// * [`InitError::RuntimeFailed`] if the runtime could not be started.
+/// * [`InitError::AlreadyInitialized`] if the input monitoring system was already initialised.
+pub fn init_sync(config: InputConfig) -> Result<mpsc::Receiver<KeyBinding>, InitError> {
+    #[cfg(feature = "grab")]
+    return init_sync_with_backend(config, Box::new(RdevBackend));
+    #[cfg(not(feature = "grab"))]
+    init_sync_with_backend(config, Box::new(MockBackend))
+}
+
+/// Like [`init_sync`], but reads input from `backend` instead of the default one.
+pub fn init_sync_with_backend(
+    config: InputConfig,
+    backend: Box<dyn InputBackend>,
+) -> Result<mpsc::Receiver<KeyBinding>, InitError> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .map_err(|_| InitError::RuntimeFailed)?;
+    let rx = {
+        let _entered = runtime.enter();
+        create_keybind_channel_with_backend(config, backend)?
+    };
+    // A current thread runtime only runs its tasks while something blocks on it
+    std::thread::spawn(move || runtime.block_on(std::future::pending::<()>()));
+    Ok(rx)
+}
+
+/// Like [`create_keybind_channel_with_config`], with the default [`InputConfig`].
+pub fn try_create_keybind_channel() -> Result<mpsc::Receiver<KeyBinding>, InitError> {
+    create_keybind_channel_with_config(InputConfig::default())
+}
+
+/// Whether the input monitoring system was already initialised, in which case creating the channel
+/// again would fail.
+pub fn is_initialized() -> bool {
+    TX.get().is_some()
+}
+
+/// Like [`try_create_keybind_channel`], but panics on error.
+/// # Panics
+/// * If called more than once in the same program.
+/// * If called outside of a tokio runtime.
+pub fn create_keybind_channel() -> mpsc::Receiver<KeyBinding>
+where
+{
+    try_create_keybind_channel().unwrap()
+}
+
+/// Discard the notifications currently waiting in `rx`, e.g. stale ones left over from before
+/// suspending, returning how many were dropped.
+///
+/// At most one channel's worth of notifications is dropped, so a notification which arrives while
+/// draining may or may not be among them, but a steady stream of new ones can't keep this from
+/// returning. Anything sent after this returns is received as usual.
+pub fn drain(rx: &mut mpsc::Receiver<KeyBinding>) -> usize {
+    let mut dropped = 0;
+    while dropped < queue_capacity() && rx.try_recv().is_ok() {
+        dropped += 1;
+    }
+    dropped
+}
+
+/// Take up to `max` notifications already waiting in `rx` at once, without waiting for more, e.g.
+/// to handle a burst of key presses in one go. The notifications are returned in the order they
+/// were sent, each with the [`EventAction`] its binding asks for. That is what the binding says, not
+/// necessarily what happened: in [observe only mode][set_observe_only], the key event was passed
+/// through all the same.
+pub fn try_recv_batch(rx: &mut mpsc::Receiver<KeyBinding>, max: usize) -> Vec<(KeyBinding, EventAction)> {
+    let mut batch = Vec::new();
+    while batch.len() < max {
+        let Ok(keybind) = rx.try_recv() else {
+            break;
+        };
+        let action = EventAction::new(keybind.notify, keybind.consume);
+        batch.push((keybind, action));
+    }
+    batch
+}
+
+/// Stop the input monitoring system: from now on, every event is passed through untouched and no
+/// binding fires.
+///
+/// The backend thread is asked to stop as well, though not every backend can (see
+/// [`InputBackend::run`]). The input monitoring system cannot be started again afterwards.
+pub fn shutdown_input() {
+    SHUTDOWN.request();
 }
@@ -1,5 +1,7 @@
+use crate::events::{decide, is_observe_only, lock, run_handler, spawn_handler, supported_keys, take_count};
 use odilia_common::{
   input::{
+    Key,
     KeyBinding,
     KeyEvent,
     Modifiers,
@@ -9,21 +11,75 @@ use odilia_common::{
   },
 };
 use tokio::{
-  sync::Mutex,
+  sync::oneshot,
+  task::JoinHandle,
 };
 use std::{
   future::Future,
-  collections::HashMap,
+  collections::{HashMap, HashSet},
+  sync::{Arc, Mutex},
+  time::{Duration, SystemTime},
 };
 
 lazy_static! {
-  static ref KB_MAP: Mutex<HashMap<KeyBinding, AsyncFn>> = Mutex::new(HashMap::new());
-  static ref SR_MODE: Mutex<ScreenReaderMode> = Mutex::new(ScreenReaderMode::new("CommandMoode"));
+  static ref DEFAULT_MANAGER: InputManager = InputManager::new();
+  static ref ACTIVE_MANAGER: Mutex<InputManager> = Mutex::new(DEFAULT_MANAGER.clone());
+  static ref CAPTURE: Mutex<Option<oneshot::Sender<KeyBinding>>> = Mutex::new(None);
+  /* held for as long as a capture is pending, so it has to be an async lock */
+  static ref CAPTURE_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+  static ref WAITERS: Mutex<Vec<(KeyEventPredicate, oneshot::Sender<KeyEvent>)>> = Mutex::new(Vec::new());
 }
 
+type KeyEventPredicate = Box<dyn Fn(&KeyEvent) -> bool + Send + Sync + 'static>;
+
 pub type AsyncFn = Box<dyn Fn() -> Box<dyn Future<Output = ()> + Unpin + Send + 'static> + Send + Sync + 'static>;
+/// Like [`AsyncFn`], but receives the [`KeyEvent`] which triggered it.
+pub type AsyncFnCtx = Box<dyn Fn(KeyEvent) -> Box<dyn Future<Output = ()> + Unpin + Send + 'static> + Send + Sync + 'static>;
+
+/// The error a fallible handler (see [`add_fallible_keybind`]) can fail with.
+pub type HandlerError = Box<dyn std::error::Error + Send + Sync + 'static>;
+/// Told the outcome of every fallible handler; see [`set_outcome_observer`].
+pub type OutcomeObserver = Box<dyn Fn(&KeyBinding, &Result<(), HandlerError>) + Send + Sync + 'static>;
+
+/// A change to the bindings of an [`InputManager`]; see [`set_keymap_observer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeymapChange {
+  /// A global binding was added, or its handler replaced.
+  Added(KeyBinding),
+  /// A global binding was removed.
+  Removed(KeyBinding),
+  /// The keymap of a mode was replaced with [`load_mode_keymap`].
+  ModeKeymapLoaded(ScreenReaderMode),
+}
+
+/// Told about every [`KeymapChange`]; see [`set_keymap_observer`].
+pub type KeymapObserver = Box<dyn Fn(&KeymapChange) + Send + Sync + 'static>;
+
+/// A host-defined condition a binding only fires under; see [`set_keybind_guard`].
+pub type KeybindGuard = Box<dyn Fn() -> bool + Send + Sync + 'static>;
+
+/// Tells whether the focused application would use a key event itself; see
+/// [`set_app_key_predicate`].
+pub type AppKeyPredicate = Box<dyn Fn(&KeyEvent) -> bool + Send + Sync + 'static>;
 
-async fn boxit<T, F>(func: T) -> AsyncFn 
+/// Where the current [`ScreenReaderMode`] comes from, for hosts which keep track of it themselves;
+/// see [`set_mode_provider`].
+pub type ModeProvider = Box<dyn Fn() -> ScreenReaderMode + Send + Sync + 'static>;
+
+/// Wrap an ordinary `async fn` or a closure returning an `async` block into an [`AsyncFn`].
+///
+/// The returned futures are pinned on the heap, so there is no need to worry about the `Unpin`
+/// bound on [`AsyncFn`] when building keymaps by hand.
+///
+/// ```
+/// use odilia_input::keybinds::{async_fn, AsyncFn};
+///
+/// async fn read_line() {}
+///
+/// let plain: AsyncFn = async_fn(read_line);
+/// let closure: AsyncFn = async_fn(|| async { println!("Hello!") });
+/// ```
+pub fn async_fn<T, F>(func: T) -> AsyncFn
 where
   T: Fn() -> F + Send + Sync + 'static,
   F: Future<Output=()> + Send + 'static
@@ -36,81 +92,1675 @@ where
   })
 }
 
+/// The [`AsyncFnCtx`] counterpart of [`async_fn`].
+pub fn async_fn_ctx<T, F>(func: T) -> AsyncFnCtx
+where
+  T: Fn(KeyEvent) -> F + Send + Sync + 'static,
+  F: Future<Output=()> + Send + 'static
+{
+  Box::new(move |kbm| {
+    Box::new(Box::pin(
+      func(kbm)
+    ))
+  })
+}
+
+/// The keybindings and screen reader mode of one input configuration.
+///
+/// Several managers can coexist, e.g. for separate profiles or in tests. Cloning a manager gives
+/// another handle to the same configuration. The input monitoring thread matches against the
+/// [active manager][InputManager::activate], which is the [global one][InputManager::global]
+/// unless another was activated. The free functions in this module always operate on the global
+/// manager.
+#[derive(Clone)]
+pub struct InputManager {
+  inner: Arc<ManagerInner>,
+}
+
+struct ManagerInner {
+  /* every binding with its settings, behind a single lock so that each change is seen all at once */
+  keymap: Mutex<Keymap>,
+  /* what matching needs of the keymap, republished on every change so that matching only waits to clone it */
+  matching: Mutex<Arc<MatchSnapshot>>,
+  sr_mode: Mutex<ScreenReaderMode>,
+  unhandled_handler: Mutex<Option<AsyncFnCtx>>,
+  combos: Mutex<Vec<(Combo, AsyncFn)>>,
+  mode_provider: Mutex<Option<ModeProvider>>,
+  outcome_observer: Mutex<Option<OutcomeObserver>>,
+  keymap_observer: Mutex<Option<KeymapObserver>>,
+  /* the modes set_sr_mode accepts; while empty, it accepts any */
+  modes: Mutex<HashSet<ScreenReaderMode>>,
+  app_key_predicate: Mutex<Option<AppKeyPredicate>>,
+}
+
+#[derive(Default)]
+struct Keymap {
+  /* the handlers of the global bindings */
+  global: HashMap<KeyBinding, AsyncFn>,
+  /* the handlers of each mode's own keymap */
+  modes: HashMap<ScreenReaderMode, HashMap<KeyBinding, AsyncFn>>,
+  settings: HashMap<KeyBinding, BindingSettings>,
+}
+
+/* everything about a binding besides its handler; shared by its entries in every keymap */
+#[derive(Default)]
+struct BindingSettings {
+  matching: MatchSettings,
+  /* the hold threshold and handler of a tap/hold binding; its tap handler is the binding's own */
+  hold: Option<(Duration, AsyncFn)>,
+  timeout: Option<Duration>,
+  /* description and category */
+  help: Option<(String, String)>,
+  /* minimum interval between fires, and when the binding last fired */
+  throttle: Option<(Duration, Option<SystemTime>)>,
+  /* handlers run besides the binding's own, with their priority */
+  handlers: Vec<(i32, AsyncFn)>,
+  dispatch: HandlerDispatch,
+  /* the name of the action the binding runs, for exporting the keymap */
+  action_name: Option<String>,
+  /* the mode to switch to once the binding's handlers have run */
+  then_mode: Option<ScreenReaderMode>,
+}
+
+/* the settings of a binding which decide whether it matches an event, and whether it consumes it */
+#[derive(Clone, Default)]
+struct MatchSettings {
+  priority: i32,
+  disabled: bool,
+  /* extra modes the binding is active in, besides its own `mode` */
+  scope: Option<Vec<ScreenReaderMode>>,
+  strategy: MatchStrategy,
+  guard: Option<Arc<KeybindGuard>>,
+  consume_policy: ConsumePolicy,
+}
+
+/* the settings of a binding nothing was set for */
+static NO_SETTINGS: BindingSettings = BindingSettings {
+  matching: NO_MATCH_SETTINGS,
+  hold: None,
+  timeout: None,
+  help: None,
+  throttle: None,
+  handlers: Vec::new(),
+  dispatch: HandlerDispatch::Concurrent,
+  action_name: None,
+  then_mode: None,
+};
+
+const NO_MATCH_SETTINGS: MatchSettings = MatchSettings {
+  priority: 0,
+  disabled: false,
+  scope: None,
+  strategy: MatchStrategy::Physical,
+  guard: None,
+  consume_policy: ConsumePolicy::Always,
+};
+
+impl Keymap {
+  fn settings(&self, kb: &KeyBinding) -> &BindingSettings {
+    self.settings.get(kb).unwrap_or(&NO_SETTINGS)
+  }
+
+  fn settings_mut(&mut self, kb: &KeyBinding) -> &mut BindingSettings {
+    self.settings.entry(kb.clone()).or_default()
+  }
+
+  fn is_bound(&self, kb: &KeyBinding) -> bool {
+    self.global.contains_key(kb) || self.modes.values().any(|m| m.contains_key(kb))
+  }
+
+  fn snapshot(&self) -> MatchSnapshot {
+    MatchSnapshot {
+      global: self.global.keys().cloned().collect(),
+      modes: self.modes.iter().map(|(mode, m)| (mode.clone(), m.keys().cloned().collect())).collect(),
+      settings: self.settings.iter().map(|(kb, settings)| (kb.clone(), settings.matching.clone())).collect(),
+    }
+  }
+}
+
+/* the bindings of a keymap without their handlers, with the settings matching looks at */
+#[derive(Default)]
+struct MatchSnapshot {
+  global: Vec<KeyBinding>,
+  modes: HashMap<ScreenReaderMode, Vec<KeyBinding>>,
+  settings: HashMap<KeyBinding, MatchSettings>,
+}
+
+impl MatchSnapshot {
+  fn settings(&self, kb: &KeyBinding) -> &MatchSettings {
+    self.settings.get(kb).unwrap_or(&NO_MATCH_SETTINGS)
+  }
+
+  /* the bindings which can be active in sr_mode, paired with whether they are from its own keymap */
+  fn candidates<'a>(&'a self, sr_mode: &ScreenReaderMode) -> impl Iterator<Item = (&'a KeyBinding, bool)> + 'a {
+    let mode_kbs = self.modes.get(sr_mode).into_iter().flatten().map(|kb| (kb, true));
+    mode_kbs.chain(self.global.iter().map(|kb| (kb, false)))
+  }
+}
+
+/// How a binding's key is compared to a key press; see [`set_match_strategy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MatchStrategy {
+  /// Match the physical key, by its position on a US QWERTY layout, whatever the active layout
+  /// makes it type. Best for navigation keys. This is the default.
+  #[default]
+  Physical,
+  /// Match the character the key press produced in the active layout, e.g. for mnemonic command
+  /// letters. Since Shift is already reflected in the character, it is ignored when checking
+  /// modifiers. Keys which produce no character are matched physically.
+  Logical,
+}
+
+/// When a binding with `consume` set keeps its key from the rest of the system; see
+/// [`set_consume_policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ConsumePolicy {
+  /// Always. This is the default.
+  #[default]
+  Always,
+  /// Only if the focused application would not use the key itself, going by the
+  /// [host's predicate][set_app_key_predicate]; otherwise the key passes through, though the
+  /// binding still fires. Without a predicate, this is the same as `Always`.
+  IfUnhandled,
+}
+
+/// How the handlers of a binding with more than one (see [`add_handler`]) are run; see
+/// [`set_handler_dispatch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum HandlerDispatch {
+  /// Start every handler at once, in order of priority, without waiting for one to finish before
+  /// starting the next. This is the default.
+  #[default]
+  Concurrent,
+  /// Run the handlers one after another in order of priority, each only once the one before it
+  /// has finished.
+  Sequential,
+}
+
+/* aborts the handlers started for a concurrent dispatch if it is given up on, e.g. on a timeout */
+struct AbortOnDrop(Vec<JoinHandle<()>>);
+
+impl Drop for AbortOnDrop {
+  fn drop(&mut self) {
+    for task in &self.0 {
+      task.abort();
+    }
+  }
+}
+
+/// Puts back the handler replaced by [`override_keybind`] when dropped.
+#[must_use = "the override ends as soon as the guard is dropped"]
+pub struct OverrideGuard {
+  manager: InputManager,
+  kb: KeyBinding,
+  /* the replaced handler, if any; only None once restored */
+  original: Option<Option<AsyncFn>>,
+}
+
+impl OverrideGuard {
+  fn restore(kb_map: &mut HashMap<KeyBinding, AsyncFn>, kb: KeyBinding, original: Option<AsyncFn>) {
+    match original {
+      Some(func) => kb_map.insert(kb, func),
+      None => kb_map.remove(&kb),
+    };
+  }
+}
+
+impl Drop for OverrideGuard {
+  fn drop(&mut self) {
+    /* the keymap is never locked across an await, so this only ever waits for a moment, even inside async code */
+    if let Some(original) = self.original.take() {
+      let kb = self.kb.clone();
+      self.manager.edit_keymap(|keymap| Self::restore(&mut keymap.global, kb, original));
+    }
+  }
+}
+
+/// A binding's entry in a keyboard shortcut list; see [`keybind_help`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeybindHelp {
+  pub keybind: KeyBinding,
+  /// What the binding does, e.g. "Read current line".
+  pub description: String,
+  /// What to group the binding under, e.g. "Reading".
+  pub category: String,
+  /// The mode the binding switches to; see [`set_then_mode`].
+  pub then_mode: Option<ScreenReaderMode>,
+}
+
+/// Several non-modifier keys pressed together, like J and K at once; see [`add_combo`].
+///
+/// Unlike a sequence of bindings, all keys must be held down at the same time, and pressed within
+/// `window` of each other, in any order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Combo {
+  pub keys: Vec<Key>,
+  pub mods: Modifiers,
+  pub window: Duration,
+  /// Whether the key press completing the combo is kept from the rest of the system.
+  pub consume: bool,
+}
+
+impl Default for InputManager {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl InputManager {
+  pub fn new() -> Self {
+    InputManager {
+      inner: Arc::new(ManagerInner {
+        keymap: Mutex::new(Keymap::default()),
+        matching: Mutex::new(Arc::default()),
+        sr_mode: Mutex::new(ScreenReaderMode::new("CommandMoode")),
+        unhandled_handler: Mutex::new(None),
+        combos: Mutex::new(Vec::new()),
+        mode_provider: Mutex::new(None),
+        outcome_observer: Mutex::new(None),
+        keymap_observer: Mutex::new(None),
+        modes: Mutex::new(HashSet::new()),
+        app_key_predicate: Mutex::new(None),
+      }),
+    }
+  }
+
+  /// The manager the free functions in this module operate on, and which is active until another
+  /// one is [activated][Self::activate].
+  pub fn global() -> &'static InputManager {
+    &DEFAULT_MANAGER
+  }
+
+  /// Make the input monitoring thread match key events against this manager from the next event
+  /// on, e.g. to switch between profiles. The bindings it sends down the channel are then this
+  /// manager's, so their handlers have to be run through its
+  /// [`run_keybind_func`][Self::run_keybind_func] rather than the free function.
+  ///
+  /// Throttling, tap/hold and repeat bindings pending when switching are still decided on by the
+  /// manager whose binding they are.
+  pub fn activate(&self) {
+    *lock(&ACTIVE_MANAGER) = self.clone();
+  }
+
+  /// The manager the input monitoring thread currently matches against; see
+  /// [`activate`][Self::activate].
+  pub fn active() -> InputManager {
+    lock(&ACTIVE_MANAGER).clone()
+  }
+
+  /// Whether this manager is the [active][Self::activate] one.
+  pub fn is_active(&self) -> bool {
+    Arc::ptr_eq(&self.inner, &lock(&ACTIVE_MANAGER).inner)
+  }
+
+  /* change the keymap in a way matching has to see; don't lock it directly for that */
+  fn edit_keymap<R>(&self, edit: impl FnOnce(&mut Keymap) -> R) -> R {
+    let mut keymap = lock(&self.inner.keymap);
+    let result = edit(&mut keymap);
+    /* still under the keymap lock, so that snapshots are published in the order of the changes */
+    *lock(&self.inner.matching) = Arc::new(keymap.snapshot());
+    result
+  }
+
+  /* the keymap as matching sees it */
+  fn matching(&self) -> Arc<MatchSnapshot> {
+    Arc::clone(&lock(&self.inner.matching))
+  }
+
+  /* add kb or replace its handler, adjusting its settings under the same lock */
+  fn bind(&self, kb: KeyBinding, func: AsyncFn, configure: impl FnOnce(&mut BindingSettings)) -> bool {
+    if let Some(key) = &kb.key {
+      if !supported_keys().contains(key) {
+        tracing::warn!("Key binding {:?} uses a key which can never be pressed", kb);
+      }
+    }
+    /* WTF? Why can't I check if it didn't workk? I guess tokio mutexes are better somehow? */
+    self.edit_keymap(|keymap| {
+      configure(keymap.settings_mut(&kb));
+      keymap.global.insert(kb.clone(), func);
+    });
+    self.keymap_changed(KeymapChange::Added(kb));
+    true
+  }
+
+  pub async fn add_keybind<T, F>(&self, kb: KeyBinding, func: T) -> bool
+  where
+    T: Fn() -> F + Send + Sync + 'static,
+    F: Future<Output=()> + Send + 'static
+  {
+    self.bind(kb, async_fn(func), |_| {})
+  }
+
+  /// See [`add_fallible_keybind`].
+  pub async fn add_fallible_keybind<T, F>(&self, kb: KeyBinding, func: T) -> bool
+  where
+    T: Fn() -> F + Send + Sync + 'static,
+    F: Future<Output=Result<(), HandlerError>> + Send + 'static
+  {
+    /* weak, since the handler is stored inside the manager */
+    let inner = Arc::downgrade(&self.inner);
+    let binding = kb.clone();
+    self.add_keybind(kb, move || {
+      let fut = func();
+      let inner = inner.clone();
+      let binding = binding.clone();
+      async move {
+        let result = fut.await;
+        if let Err(e) = &result {
+          tracing::warn!("Handler for key binding {:?} failed: {}", binding, e);
+        }
+        if let Some(inner) = inner.upgrade() {
+          if let Some(observer) = &*lock(&inner.outcome_observer) {
+            observer(&binding, &result);
+          }
+        }
+      }
+    }).await
+  }
+
+  /// See [`set_outcome_observer`].
+  pub async fn set_outcome_observer(&self, observer: Option<OutcomeObserver>) {
+    *lock(&self.inner.outcome_observer) = observer;
+  }
+
+  /// See [`add_sync_keybind`].
+  pub fn add_sync_keybind<T>(&self, kb: KeyBinding, func: T) -> bool
+  where
+    T: Fn() + Send + Sync + 'static
+  {
+    let func = Arc::new(func);
+    let handler = async_fn(move || {
+      let func = Arc::clone(&func);
+      async move {
+        if let Err(e) = tokio::task::spawn_blocking(move || func()).await {
+          tracing::warn!("Synchronous key binding handler panicked: {}", e);
+        }
+      }
+    });
+    self.bind(kb, handler, |_| {})
+  }
+
+  /// See [`add_counted_keybind`].
+  pub async fn add_counted_keybind<T, F>(&self, kb: KeyBinding, func: T) -> bool
+  where
+    T: Fn(u32) -> F + Send + Sync + 'static,
+    F: Future<Output=()> + Send + 'static
+  {
+    let binding = kb.clone();
+    self.add_keybind(kb, move || func(take_count(&binding))).await
+  }
+
+  /// See [`set_keymap_observer`].
+  pub async fn set_keymap_observer(&self, observer: Option<KeymapObserver>) {
+    *lock(&self.inner.keymap_observer) = observer;
+  }
+
+  /* must not be called with any of the keymaps locked */
+  fn keymap_changed(&self, change: KeymapChange) {
+    if let Some(observer) = &*lock(&self.inner.keymap_observer) {
+      observer(&change);
+    }
+  }
+
+  /// See [`add_keybinds`].
+  pub async fn add_keybinds(&self, keybinds: HashMap<KeyBinding, AsyncFn>) -> Vec<KeyBinding> {
+    let supported = supported_keys();
+    let mut conflicts = Vec::new();
+    let mut added = Vec::new();
+    self.edit_keymap(|keymap| {
+      for (kb, func) in keybinds {
+        if keymap.global.contains_key(&kb) {
+          conflicts.push(kb);
+          continue;
+        }
+        if kb.key.as_ref().is_some_and(|key| !supported.contains(key)) {
+          tracing::warn!("Key binding {:?} uses a key which can never be pressed", kb);
+        }
+        keymap.global.insert(kb.clone(), func);
+        added.push(kb);
+      }
+    });
+    for kb in added {
+      self.keymap_changed(KeymapChange::Added(kb));
+    }
+    conflicts
+  }
+
+  /// Like [`add_keybind`][Self::add_keybind], but when several bindings match the same event, the
+  /// one with the highest `priority` wins. Bindings added without a priority have priority 0.
+  pub async fn add_keybind_with_priority<T, F>(&self, kb: KeyBinding, priority: i32, func: T) -> bool
+  where
+    T: Fn() -> F + Send + Sync + 'static,
+    F: Future<Output=()> + Send + 'static
+  {
+    self.bind(kb, async_fn(func), |settings| settings.matching.priority = priority)
+  }
+
+  pub async fn remove_keybind(&self, kb: KeyBinding) -> bool {
+    let removed = self.edit_keymap(|keymap| {
+      keymap.settings.remove(&kb);
+      keymap.global.remove(&kb).is_some()
+    });
+    if removed {
+      self.keymap_changed(KeymapChange::Removed(kb));
+    }
+    true
+  }
+
+  /// See [`add_handler`].
+  pub async fn add_handler<T, F>(&self, kb: &KeyBinding, priority: i32, func: T) -> bool
+  where
+    T: Fn() -> F + Send + Sync + 'static,
+    F: Future<Output=()> + Send + 'static
+  {
+    let mut keymap = lock(&self.inner.keymap);
+    if !keymap.is_bound(kb) {
+      return false;
+    }
+    keymap.settings_mut(kb).handlers.push((priority, async_fn(func)));
+    true
+  }
+
+  /// See [`set_handler_dispatch`].
+  pub async fn set_handler_dispatch(&self, kb: &KeyBinding, dispatch: HandlerDispatch) {
+    lock(&self.inner.keymap).settings_mut(kb).dispatch = dispatch;
+  }
+
+  /// See [`set_keybind_throttle`].
+  pub async fn set_keybind_throttle(&self, kb: &KeyBinding, interval: Option<Duration>) {
+    lock(&self.inner.keymap).settings_mut(kb).throttle = interval.map(|interval| (interval, None));
+  }
+
+  /* used from the input monitoring thread; records a fire at `now` unless kb fired too recently */
+  pub fn throttle_allows_sync(&self, kb: &KeyBinding, now: SystemTime) -> bool {
+    let mut keymap = lock(&self.inner.keymap);
+    let Some((interval, last_fired)) = keymap.settings.get_mut(kb).and_then(|settings| settings.throttle.as_mut()) else {
+      return true;
+    };
+    if fired_within(*last_fired, *interval, now) {
+      return false;
+    }
+    *last_fired = Some(now);
+    true
+  }
+
+  /* like throttle_allows_sync, without recording anything */
+  fn is_throttled(&self, kb: &KeyBinding, now: SystemTime) -> bool {
+    lock(&self.inner.keymap).settings(kb).throttle.is_some_and(|(interval, last_fired)| fired_within(last_fired, interval, now))
+  }
+
+  /// See [`set_match_strategy`].
+  pub async fn set_match_strategy(&self, kb: &KeyBinding, strategy: MatchStrategy) {
+    self.edit_keymap(|keymap| keymap.settings_mut(kb).matching.strategy = strategy);
+  }
+
+  /// See [`set_keybind_guard`].
+  pub async fn set_keybind_guard(&self, kb: &KeyBinding, guard: Option<KeybindGuard>) {
+    self.edit_keymap(|keymap| keymap.settings_mut(kb).matching.guard = guard.map(Arc::new));
+  }
+
+  /// See [`override_keybind`].
+  pub async fn override_keybind<T, F>(&self, kb: KeyBinding, func: T) -> OverrideGuard
+  where
+    T: Fn() -> F + Send + Sync + 'static,
+    F: Future<Output=()> + Send + 'static
+  {
+    let original = self.edit_keymap(|keymap| keymap.global.insert(kb.clone(), async_fn(func)));
+    OverrideGuard {
+      manager: self.clone(),
+      kb,
+      original: Some(original),
+    }
+  }
+
+  /// See [`set_consume_policy`].
+  pub async fn set_consume_policy(&self, kb: &KeyBinding, policy: ConsumePolicy) {
+    self.edit_keymap(|keymap| keymap.settings_mut(kb).matching.consume_policy = policy);
+  }
+
+  /// See [`set_app_key_predicate`].
+  pub async fn set_app_key_predicate(&self, predicate: Option<AppKeyPredicate>) {
+    *lock(&self.inner.app_key_predicate) = predicate;
+  }
+
+  /* used from the input monitoring thread; whether kbm, matched by kb, is kept from the rest of the system */
+  pub fn consumes_sync(&self, kb: &KeyBinding, kbm: &KeyEvent) -> bool {
+    self.consumes_in(&self.matching(), kb, kbm)
+  }
+
+  fn consumes_in(&self, matching: &MatchSnapshot, kb: &KeyBinding, kbm: &KeyEvent) -> bool {
+    if !kb.consume {
+      return false;
+    }
+    match matching.settings(kb).consume_policy {
+      ConsumePolicy::Always => true,
+      ConsumePolicy::IfUnhandled => !lock(&self.inner.app_key_predicate).as_ref().is_some_and(|app_wants| app_wants(kbm)),
+    }
+  }
+
+  /// See [`add_keybind_described`].
+  pub async fn add_keybind_described<T, F>(&self, kb: KeyBinding, description: &str, category: &str, func: T) -> bool
+  where
+    T: Fn() -> F + Send + Sync + 'static,
+    F: Future<Output=()> + Send + 'static
+  {
+    let help = (description.to_string(), category.to_string());
+    self.bind(kb, async_fn(func), |settings| settings.help = Some(help))
+  }
+
+  /// See [`keybind_help`].
+  pub async fn keybind_help(&self) -> Vec<KeybindHelp> {
+    let keymap = lock(&self.inner.keymap);
+    let mut entries: Vec<KeybindHelp> = keymap.settings.iter()
+      .filter_map(|(kb, settings)| {
+        let (description, category) = settings.help.as_ref()?;
+        Some(KeybindHelp {
+          keybind: kb.clone(),
+          description: description.clone(),
+          category: category.clone(),
+          then_mode: settings.then_mode.clone(),
+        })
+      })
+      .collect();
+    entries.sort_by(|a, b| (&a.category, &a.description).cmp(&(&b.category, &b.description)));
+    entries
+  }
+
+  /// See [`active_keybinds`].
+  pub async fn active_keybinds(&self) -> Vec<KeyBinding> {
+    let sr_mode = self.get_sr_mode_sync();
+    let matching = self.matching();
+    matching.candidates(&sr_mode)
+      .filter(|(kb, from_mode_map)| *from_mode_map || in_mode(kb, matching.settings(kb).scope.as_ref(), &sr_mode))
+      .filter(|(kb, _)| !matching.settings(kb).disabled)
+      .map(|(kb, _)| kb.clone())
+      .collect()
+  }
+
+  /// See [`set_then_mode`].
+  pub async fn set_then_mode(&self, kb: &KeyBinding, mode: Option<ScreenReaderMode>) {
+    lock(&self.inner.keymap).settings_mut(kb).then_mode = mode;
+  }
+
+  /// See [`then_mode`].
+  pub async fn then_mode(&self, kb: &KeyBinding) -> Option<ScreenReaderMode> {
+    lock(&self.inner.keymap).settings(kb).then_mode.clone()
+  }
+
+  /// See [`set_action_name`].
+  pub async fn set_action_name(&self, kb: &KeyBinding, name: Option<&str>) {
+    lock(&self.inner.keymap).settings_mut(kb).action_name = name.map(str::to_string);
+  }
+
+  /// See [`named_keybinds`].
+  pub async fn named_keybinds(&self) -> Vec<(KeyBinding, Option<String>)> {
+    let keymap = lock(&self.inner.keymap);
+    keymap.global.keys().map(|kb| (kb.clone(), keymap.settings(kb).action_name.clone())).collect()
+  }
+
+  /// See [`set_keybind_timeout`].
+  pub async fn set_keybind_timeout(&self, kb: &KeyBinding, timeout: Option<Duration>) {
+    lock(&self.inner.keymap).settings_mut(kb).timeout = timeout;
+  }
+
+  /* run a handler's future for kb, giving up on it after kb's timeout */
+  async fn run_with_timeout(&self, kb: &KeyBinding, fut: Box<dyn Future<Output = ()> + Unpin + Send + 'static>) {
+    let timeout = lock(&self.inner.keymap).settings(kb).timeout;
+    run_handler(async move {
+      match timeout {
+        Some(timeout) => {
+          if tokio::time::timeout(timeout, fut).await.is_err() {
+            tracing::warn!("Handler for key binding {:?} took longer than {:?} and was aborted", kb, timeout);
+          }
+        },
+        None => fut.await,
+      }
+    }).await;
+  }
+
+  /// See [`add_combo`].
+  pub async fn add_combo<T, F>(&self, combo: Combo, func: T)
+  where
+    T: Fn() -> F + Send + Sync + 'static,
+    F: Future<Output=()> + Send + 'static
+  {
+    let mut combos = lock(&self.inner.combos);
+    combos.retain(|(c, _)| *c != combo);
+    combos.push((combo, async_fn(func)));
+  }
+
+  pub async fn remove_combo(&self, combo: &Combo) {
+    lock(&self.inner.combos).retain(|(c, _)| c != combo);
+  }
+
+  /* used from the input monitoring thread; `held` is every non-modifier key held down, with the time it was pressed */
+  pub fn combo_match_sync(&self, held: &[(Key, SystemTime)], mods: Modifiers) -> Option<Combo> {
+    let combos = lock(&self.inner.combos);
+    combos.iter()
+      .map(|(combo, _)| combo)
+      .find(|combo| combo_matches(combo, held, mods))
+      .cloned()
+  }
+
+  /// Run the handler of `combo`, if it is still registered.
+  pub async fn run_combo_func(&self, combo: &Combo) {
+    let fut = match lock(&self.inner.combos).iter().find(|(c, _)| c == combo) {
+      Some((_, func)) => func(),
+      None => {
+        tracing::warn!("Combo {:?} not found; it may have been removed", combo);
+        return;
+      }
+    };
+    run_handler(fut).await;
+  }
+
+  /// See [`add_keybind_for_modes`].
+  pub async fn add_keybind_for_modes<T, F>(&self, kb: KeyBinding, modes: Vec<ScreenReaderMode>, func: T) -> bool
+  where
+    T: Fn() -> F + Send + Sync + 'static,
+    F: Future<Output=()> + Send + 'static
+  {
+    self.bind(kb, async_fn(func), |settings| settings.matching.scope = Some(modes))
+  }
+
+  /// See [`add_tap_hold_keybind`].
+  pub async fn add_tap_hold_keybind<T, F, U, G>(&self, kb: KeyBinding, threshold: Duration, tap: T, hold: U) -> bool
+  where
+    T: Fn() -> F + Send + Sync + 'static,
+    F: Future<Output=()> + Send + 'static,
+    U: Fn() -> G + Send + Sync + 'static,
+    G: Future<Output=()> + Send + 'static
+  {
+    let hold = (threshold, async_fn(hold));
+    self.bind(kb, async_fn(tap), |settings| settings.hold = Some(hold))
+  }
+
+  /* used from the input monitoring thread; Some if kb is a tap/hold binding */
+  pub fn hold_threshold_sync(&self, kb: &KeyBinding) -> Option<Duration> {
+    lock(&self.inner.keymap).settings(kb).hold.as_ref().map(|(threshold, _)| *threshold)
+  }
+
+  /// Run the hold handler of the tap/hold binding `kb`, if it has one.
+  pub async fn run_hold_func(&self, kb: &KeyBinding) {
+    let fut = match &lock(&self.inner.keymap).settings(kb).hold {
+      Some((_, func)) => func(),
+      None => {
+        tracing::warn!("Tap/hold binding {:?} not found; it may have been removed", kb);
+        return;
+      }
+    };
+    self.run_with_timeout(kb, fut).await;
+  }
+
+  /// See [`set_keybind_enabled`].
+  pub async fn set_keybind_enabled(&self, kb: &KeyBinding, enabled: bool) {
+    self.edit_keymap(|keymap| keymap.settings_mut(kb).matching.disabled = !enabled);
+  }
+
+  pub async fn is_keybind_enabled(&self, kb: &KeyBinding) -> bool {
+    !lock(&self.inner.keymap).settings(kb).matching.disabled
+  }
+
+  /// See [`set_unhandled_handler`].
+  pub async fn set_unhandled_handler(&self, handler: AsyncFnCtx) {
+    *lock(&self.inner.unhandled_handler) = Some(handler);
+  }
+
+  pub async fn remove_unhandled_handler(&self) {
+    *lock(&self.inner.unhandled_handler) = None;
+  }
+
+  /* used from the input monitoring thread; returns the future to spawn for an unmatched key, if a handler is set */
+  pub fn unhandled_keyevent_sync(&self, kbm: &KeyEvent) -> Option<Box<dyn Future<Output = ()> + Unpin + Send + 'static>> {
+    let handler = lock(&self.inner.unhandled_handler);
+    handler.as_ref().map(|func| func(kbm.clone()))
+  }
+
+  /// See [`load_mode_keymap`].
+  pub async fn load_mode_keymap(&self, mode: ScreenReaderMode, keymap: HashMap<KeyBinding, AsyncFn>) {
+    self.edit_keymap(|maps| maps.modes.insert(mode.clone(), keymap));
+    self.keymap_changed(KeymapChange::ModeKeymapLoaded(mode));
+  }
+
+  pub async fn keyevent_match(&self, kbm: &KeyEvent) -> Option<KeyBinding>
+  {
+    self.keyevent_match_char_sync(kbm, None)
+  }
+
+  /* this will match with the bitflags */
+  pub fn keyevent_match_sync(&self, kbm: &KeyEvent) -> Option<KeyBinding>
+  {
+    self.keyevent_match_char_sync(kbm, None)
+  }
+
+  /// Like [`keyevent_match_sync`][Self::keyevent_match_sync], also knowing the character the key
+  /// press produced, if any, for [`MatchStrategy::Logical`] bindings.
+  pub fn keyevent_match_char_sync(&self, kbm: &KeyEvent, produced: Option<char>) -> Option<KeyBinding>
+  {
+    let sr_mode = self.get_sr_mode_sync();
+    find_keybind(&self.matching(), kbm, produced, &sr_mode)
+  }
+
+  /// See [`resolve_binding`].
+  pub async fn resolve_binding(&self, kbm: &KeyEvent) -> Option<(KeyBinding, Option<String>)> {
+    let kb = self.keyevent_match_sync(kbm)?;
+    let description = lock(&self.inner.keymap).settings(&kb).help.as_ref().map(|(description, _)| description.clone());
+    Some((kb, description))
+  }
+
+  /// See [`explain_match`].
+  pub async fn explain_match(&self, kbm: &KeyEvent) -> Vec<(KeyBinding, Vec<MatchFailure>)> {
+    let sr_mode = self.get_sr_mode_sync();
+    let matching = self.matching();
+    let mut explanations: Vec<(KeyBinding, Vec<MatchFailure>)> = matching.global.iter()
+      .map(|kb| (kb.clone(), match_failures(kb, matching.settings(kb).scope.as_ref(), kbm, &sr_mode)))
+      .collect();
+    for (mode, mode_kbs) in matching.modes.iter() {
+      for kb in mode_kbs {
+        let mut failures = match_failures(kb, matching.settings(kb).scope.as_ref(), kbm, &sr_mode);
+        /* bindings in another mode's map are inactive even without a mode of their own */
+        if *mode != sr_mode && !failures.contains(&MatchFailure::Mode) {
+          failures.push(MatchFailure::Mode);
+        }
+        explanations.push((kb.clone(), failures));
+      }
+    }
+    for (kb, failures) in explanations.iter_mut() {
+      if matching.settings(kb).disabled {
+        failures.push(MatchFailure::Disabled);
+      }
+    }
+    explanations
+  }
+
+  /* used from the input monitoring thread; whether a binding for more repeats of kbm's keys could still match */
+  pub fn has_higher_repeat_sync(&self, kbm: &KeyEvent) -> bool {
+    let sr_mode = self.get_sr_mode_sync();
+    let matching = self.matching();
+    let higher = matching.candidates(&sr_mode)
+      .map(|(kb, _)| (kb, matching.settings(kb)))
+      .filter(|(kb, settings)| kb.repeat > kbm.repeat && !settings.disabled)
+      .any(|(kb, settings)| match_failures(kb, settings.scope.as_ref(), kbm, &sr_mode) == [MatchFailure::Repeat]);
+    higher
+  }
+
+  /* the binding kbm matches, whether it consumes kbm and whether it would notify now, all from the same snapshot */
+  pub(crate) fn predict_sync(&self, kbm: &KeyEvent) -> Option<(KeyBinding, bool, bool)> {
+    let matching = self.matching();
+    let kb = find_keybind(&matching, kbm, None, &self.get_sr_mode_sync())?;
+    let consume = self.consumes_in(&matching, &kb, kbm);
+    let notify = kb.notify && !self.is_throttled(&kb, SystemTime::now());
+    Some((kb, consume, notify))
+  }
+
+  /// See [`would_consume`].
+  pub fn would_consume(&self, kbm: &KeyEvent) -> bool {
+    decide(self, kbm).0.consumes()
+  }
+
+  /// See [`would_notify`].
+  pub fn would_notify(&self, kbm: &KeyEvent) -> bool {
+    decide(self, kbm).0.notifies()
+  }
+
+  /// See [`register_mode`].
+  pub async fn register_mode(&self, mode: ScreenReaderMode) {
+    lock(&self.inner.modes).insert(mode);
+  }
+
+  /// See [`set_mode_provider`].
+  pub async fn set_mode_provider(&self, provider: Option<ModeProvider>) {
+    *lock(&self.inner.mode_provider) = provider;
+  }
+
+  pub fn get_sr_mode_sync(&self) -> ScreenReaderMode {
+    if let Some(provider) = &*lock(&self.inner.mode_provider) {
+      return provider();
+    }
+    lock(&self.inner.sr_mode).clone()
+  }
+  pub fn set_sr_mode_sync(&self, srm: ScreenReaderMode) -> Result<(), UnknownMode> {
+    check_mode(&lock(&self.inner.modes), &srm)?;
+    let mut sr_mode = lock(&self.inner.sr_mode);
+    *sr_mode = srm;
+    Ok(())
+  }
+  pub async fn get_sr_mode(&self) -> ScreenReaderMode {
+    self.get_sr_mode_sync()
+  }
+  pub async fn set_sr_mode(&self, srm: ScreenReaderMode) -> Result<(), UnknownMode> {
+    self.set_sr_mode_sync(srm)
+  }
+
+  /// Run the handler bound to `kb`.
+  ///
+  /// If there is none, e.g. because the binding was removed after it was matched, a warning is
+  /// logged and nothing is run. Nothing is run in
+  /// [observe only mode][crate::events::set_observe_only] either.
+  pub async fn run_keybind_func(&self, kb: &KeyBinding) {
+    if is_observe_only() {
+      return;
+    }
+    if self.trigger(kb).await.is_err() {
+      tracing::warn!("Key binding {:?} not found; it may have been removed", kb);
+    }
+  }
+
+  /// See [`trigger`].
+  pub async fn trigger(&self, kb: &KeyBinding) -> Result<(), NotBound> {
+    /* only hold the locks while creating the futures, not while they run */
+    let (mut futs, dispatch) = {
+      let sr_mode = self.get_sr_mode_sync();
+      let keymap = lock(&self.inner.keymap);
+      let func = keymap.modes.get(&sr_mode)
+        .and_then(|m| m.get(kb))
+        .or_else(|| keymap.global.get(kb))
+        .ok_or_else(|| NotBound(kb.clone()))?;
+      let settings = keymap.settings(kb);
+      let mut futs = vec![(0, func())];
+      futs.extend(settings.handlers.iter().map(|(priority, func)| (*priority, func())));
+      /* stable, so on a tie the binding's own handler goes first, then the others in the order they were added */
+      futs.sort_by_key(|(priority, _)| std::cmp::Reverse(*priority));
+      (futs.into_iter().map(|(_, fut)| fut).collect::<Vec<_>>(), settings.dispatch)
+    };
+    let fut: Box<dyn Future<Output = ()> + Unpin + Send + 'static> = match (futs.len(), dispatch) {
+      (1, _) => futs.remove(0),
+      (_, HandlerDispatch::Sequential) => Box::new(Box::pin(async move {
+        for fut in futs {
+          fut.await;
+        }
+      })),
+      (_, HandlerDispatch::Concurrent) => Box::new(Box::pin(async move {
+        let mut tasks = AbortOnDrop(futs.into_iter().map(tokio::spawn).collect());
+        for task in tasks.0.iter_mut() {
+          if let Err(e) = task.await {
+            tracing::warn!("Key binding handler panicked: {}", e);
+          }
+        }
+      })),
+    };
+    self.run_with_timeout(kb, fut).await;
+    let then_mode = lock(&self.inner.keymap).settings(kb).then_mode.clone();
+    if let Some(mode) = then_mode {
+      if let Err(e) = self.set_sr_mode(mode).await {
+        tracing::warn!("Key binding {:?} could not switch modes: {}", kb, e);
+      }
+    }
+    Ok(())
+  }
+}
+
+/// A binding with no handler, returned by [`trigger`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotBound(pub KeyBinding);
+
+impl std::fmt::Display for NotBound {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "no handler is bound to {:?}", self.0)
+  }
+}
+
+impl std::error::Error for NotBound {}
+
+/// A mode which was never registered with [`register_mode`], returned by [`set_sr_mode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownMode(pub ScreenReaderMode);
+
+impl std::fmt::Display for UnknownMode {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "unknown screen reader mode {:?}", self.0.name)
+  }
+}
+
+impl std::error::Error for UnknownMode {}
+
+fn check_mode(modes: &HashSet<ScreenReaderMode>, mode: &ScreenReaderMode) -> Result<(), UnknownMode> {
+  if modes.is_empty() || modes.contains(mode) {
+    Ok(())
+  } else {
+    Err(UnknownMode(mode.clone()))
+  }
+}
+
+/// One reason a [`KeyBinding`] does not match a [`KeyEvent`]; see [`explain_match`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchFailure {
+  /// The binding is for a different key.
+  Key,
+  /// The held modifiers don't satisfy the binding's.
+  Modifiers,
+  /// The binding belongs to a mode which is not active.
+  Mode,
+  /// The binding is for a different number of repeats.
+  Repeat,
+  /// The binding was turned off with [`set_keybind_enabled`].
+  Disabled,
+}
+
+/* every modifier of the binding must be held, so SHIFT_L | SHIFT_R needs both shift keys;
+a binding without modifiers only matches when none are held */
+fn mods_satisfied(wanted: Modifiers, held: Modifiers) -> bool {
+  (wanted == Modifiers::NONE && held == Modifiers::NONE) || (wanted != Modifiers::NONE && held.contains(wanted))
+}
+
+fn combo_matches(combo: &Combo, held: &[(Key, SystemTime)], mods: Modifiers) -> bool {
+  /* exactly the combo's keys must be down, no more */
+  if combo.keys.len() < 2 || held.len() != combo.keys.len() || !mods_satisfied(combo.mods, mods) {
+    return false;
+  }
+  if !combo.keys.iter().all(|key| held.iter().any(|(k, _)| k == key)) {
+    return false;
+  }
+  let first = held.iter().map(|(_, time)| *time).min();
+  let last = held.iter().map(|(_, time)| *time).max();
+  match (first, last) {
+    (Some(first), Some(last)) => last.duration_since(first).is_ok_and(|spread| spread <= combo.window),
+    _ => false,
+  }
+}
+
+/* whether a binding which last fired at last_fired is still throttled at now */
+fn fired_within(last_fired: Option<SystemTime>, interval: Duration, now: SystemTime) -> bool
+{
+  last_fired.is_some_and(|last| now.duration_since(last).is_ok_and(|since| since < interval))
+}
+
+/* a binding without any mode is active in all of them */
+fn in_mode(kb: &KeyBinding, scope: Option<&Vec<ScreenReaderMode>>, sr_mode: &ScreenReaderMode) -> bool
+{
+  match (&kb.mode, scope) {
+    (None, None) => true,
+    (mode, scope) => mode.as_ref() == Some(sr_mode) || scope.is_some_and(|modes| modes.contains(sr_mode)),
+  }
+}
+
+/* `scope` is the list of extra modes the binding was added for, if any */
+fn match_failures(kb: &KeyBinding, scope: Option<&Vec<ScreenReaderMode>>, kbm: &KeyEvent, sr_mode: &ScreenReaderMode) -> Vec<MatchFailure>
+{
+  let mut failures = Vec::new();
+  if kb.key != kbm.key {
+    failures.push(MatchFailure::Key);
+  }
+  if !mods_satisfied(kb.mods, kbm.mods) {
+    failures.push(MatchFailure::Modifiers);
+  }
+  if !in_mode(kb, scope, sr_mode) {
+    failures.push(MatchFailure::Mode);
+  }
+  if kb.repeat != kbm.repeat {
+    failures.push(MatchFailure::Repeat);
+  }
+  failures
+}
+
+/// Whether `kb` matches `kbm` while `sr_mode` is active.
+///
+/// Every modifier of the binding must be held; others may be held too. A binding for
+/// `SHIFT_L | SHIFT_R` therefore needs both Shift keys down, and one meant for either Shift key
+/// has to be added once for each side. A binding without modifiers only matches while none are
+/// held.
+///
+/// This is the rule every matching function in this crate uses. It only looks at the binding
+/// itself: being disabled, scoped to extra modes or loaded for another mode is up to the
+/// [`InputManager`] the binding is registered with.
+pub fn matches(kb: &KeyBinding, kbm: &KeyEvent, sr_mode: &ScreenReaderMode) -> bool
+{
+  match_failures(kb, None, kbm, sr_mode).is_empty()
+}
+
+/* the event as a binding with the given strategy sees it */
+fn event_for_strategy(strategy: MatchStrategy, kbm: &KeyEvent, produced: Option<char>) -> KeyEvent {
+  match (strategy, produced) {
+    /* Shift is already part of the produced character */
+    (MatchStrategy::Logical, Some(c)) => KeyEvent {
+      key: Some(Key::Other(c)),
+      mods: kbm.mods - (Modifiers::SHIFT_L | Modifiers::SHIFT_R),
+      repeat: kbm.repeat,
+    },
+    _ => kbm.clone(),
+  }
+}
+
+/* the active mode's own map, then the global one */
+fn find_keybind(matching: &MatchSnapshot, kbm: &KeyEvent, produced: Option<char>, sr_mode: &ScreenReaderMode) -> Option<KeyBinding>
+{
+  /* pick the same winner every time, no matter the HashMap iteration order */
+  matching.candidates(sr_mode)
+    .map(|(kb, from_mode_map)| (kb, from_mode_map, matching.settings(kb)))
+    .filter(|(kb, _, settings)| !settings.disabled
+      && match_failures(kb, settings.scope.as_ref(), &event_for_strategy(settings.strategy, kbm, produced), sr_mode).is_empty()
+      /* checked last, since guards may be costly */
+      && settings.guard.as_ref().is_none_or(|guard| guard()))
+    .max_by_key(|(kb, from_mode_map, settings)| (
+      settings.priority,
+      /* then exact modifier matches over partial ones */
+      kb.mods == event_for_strategy(settings.strategy, kbm, produced).mods,
+      /* then mode-specific bindings over global ones */
+      *from_mode_map || kb.mode.is_some() || settings.scope.is_some(),
+      /* then the most specific binding, i.e. the one needing the most modifiers */
+      kb.mods.bits().count_ones(),
+      kb.mods.bits(),
+      kb.consume,
+      kb.notify,
+    ))
+    .map(|(kb, _, _)| kb.clone())
+}
+
+/// Bind `func` to `kb`, replacing any handler already bound to it.
+///
+/// A binding with `mode: None` is global: it is checked in every mode, including modes with their
+/// own keymap (see [`load_mode_keymap`]). When a mode-specific binding matches the same key press,
+/// the mode-specific one wins; to keep a shortcut like "stop speech" working no matter what a mode
+/// binds, add it with [`add_keybind_with_priority`] and a priority above 0.
 pub async fn add_keybind<T, F>(kb: KeyBinding, func: T) -> bool 
 where
   T: Fn() -> F + Send + Sync + 'static,
   F: Future<Output=()> + Send + 'static
 {
-  /* WTF? Why can't I check if it didn't workk? I guess tokio mutexes are better somehow? */
-  let mut kbhm = KB_MAP.lock().await;
-  kbhm.insert(kb, boxit(func).await);
-  true
+  DEFAULT_MANAGER.add_keybind(kb, func).await
+}
+
+/// Add a whole batch of bindings at once, e.g. at startup or when reloading a config file.
+///
+/// All bindings are added under a single lock, so the input monitoring thread sees either none or
+/// all of them. Bindings which are already registered are left alone and returned as conflicts;
+/// the rest are added regardless.
+pub async fn add_keybinds(keybinds: HashMap<KeyBinding, AsyncFn>) -> Vec<KeyBinding> {
+  DEFAULT_MANAGER.add_keybinds(keybinds).await
+}
+
+/// Add several bindings with [`add_keybinds`], written as `Modifier + ... + key => handler`.
+///
+/// Expands to the future [`add_keybinds`] returns, so it has to be awaited. Every binding is for a
+/// single press, consumes its key and notifies the channel, in any mode.
+///
+/// * The modifiers are `Odilia`, `Ctrl` (or `Control`), `Shift`, `Alt`, `AltGr` and `Meta` (or
+///   `Super`), for the left-hand keys; any other name is a compile error.
+/// * A key typing a character is a `char` literal, e.g. `'k'`; any other key is the name of a
+///   [`Key`] variant, e.g. `Home` or `F5`.
+/// * A handler is anything [`async_fn`] takes, like an `async fn`.
+///
+/// ```
+/// use odilia_input::keybinds;
+///
+/// async fn read_line() {}
+/// async fn read_previous_line() {}
+/// async fn read_title() {}
+///
+/// # async fn setup() {
+/// let conflicts = keybinds! {
+///   Odilia + 'k' => read_line,
+///   Odilia + Shift + 'k' => read_previous_line,
+///   Odilia + Ctrl + F5 => read_title,
+/// }.await;
+/// assert!(conflicts.is_empty());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! keybinds {
+  (@modifier Odilia) => { $crate::__macro_support::Modifiers::ODILIA };
+  (@modifier Ctrl) => { $crate::__macro_support::Modifiers::CONTROL_L };
+  (@modifier Control) => { $crate::__macro_support::Modifiers::CONTROL_L };
+  (@modifier Shift) => { $crate::__macro_support::Modifiers::SHIFT_L };
+  (@modifier Alt) => { $crate::__macro_support::Modifiers::ALT_L };
+  (@modifier AltGr) => { $crate::__macro_support::Modifiers::ALT_R };
+  (@modifier Meta) => { $crate::__macro_support::Modifiers::META_L };
+  (@modifier Super) => { $crate::__macro_support::Modifiers::META_L };
+  (@modifier $other:ident) => {
+    compile_error!(concat!("unknown modifier `", stringify!($other), "`; expected Odilia, Ctrl, Shift, Alt, AltGr or Meta"))
+  };
+  /* every binding is parsed; `done` holds a (key, mods, handler) group for each */
+  (@entry [$(($key:expr, $mods:expr, $handler:expr))*] []) => {{
+    let mut keybinds: ::std::collections::HashMap<$crate::__macro_support::KeyBinding, $crate::keybinds::AsyncFn> = ::std::collections::HashMap::new();
+    $(
+      keybinds.insert($crate::__macro_support::KeyBinding {
+        key: Some($key),
+        mods: $mods,
+        repeat: 1,
+        consume: true,
+        notify: true,
+        mode: None,
+      }, $crate::keybinds::async_fn($handler));
+    )*
+    $crate::keybinds::add_keybinds(keybinds)
+  }};
+  (@entry [$($done:tt)*] [$($mods:tt)*] $modifier:ident + $($rest:tt)*) => {
+    $crate::keybinds!(@entry [$($done)*] [$($mods)* | $crate::keybinds!(@modifier $modifier)] $($rest)*)
+  };
+  (@entry [$($done:tt)*] [$($mods:tt)*] $key:literal => $handler:expr $(, $($rest:tt)*)?) => {
+    $crate::keybinds!(@entry
+      [$($done)* ($crate::__macro_support::Key::Other($key), $crate::__macro_support::Modifiers::empty() $($mods)*, $handler)]
+      []
+      $($($rest)*)?)
+  };
+  (@entry [$($done:tt)*] [$($mods:tt)*] $key:ident => $handler:expr $(, $($rest:tt)*)?) => {
+    $crate::keybinds!(@entry
+      [$($done)* ($crate::__macro_support::Key::$key, $crate::__macro_support::Modifiers::empty() $($mods)*, $handler)]
+      []
+      $($($rest)*)?)
+  };
+  (@entry [$($done:tt)*] [$($mods:tt)*] $($unexpected:tt)+) => {
+    compile_error!(concat!("expected `Modifier + ... + key => handler`, found `", stringify!($($unexpected)+), "`"))
+  };
+  ($($bindings:tt)*) => {
+    $crate::keybinds!(@entry [] [] $($bindings)*)
+  };
+}
+
+pub async fn add_keybind_with_priority<T, F>(kb: KeyBinding, priority: i32, func: T) -> bool
+where
+  T: Fn() -> F + Send + Sync + 'static,
+  F: Future<Output=()> + Send + 'static
+{
+  DEFAULT_MANAGER.add_keybind_with_priority(kb, priority, func).await
 }
 
 pub async fn remove_keybind(kb: KeyBinding) -> bool {
-  let mut kbhm = KB_MAP.lock().await;
-  kbhm.remove(&kb);
-  true
+  DEFAULT_MANAGER.remove_keybind(kb).await
+}
+
+/// Like [`add_keybind`], for a handler which can fail.
+///
+/// Failures are logged, and every outcome is passed to the observer set with
+/// [`set_outcome_observer`], e.g. to tell the user that an action failed. Apart from that, the
+/// binding behaves like any other.
+pub async fn add_fallible_keybind<T, F>(kb: KeyBinding, func: T) -> bool
+where
+  T: Fn() -> F + Send + Sync + 'static,
+  F: Future<Output=Result<(), HandlerError>> + Send + 'static
+{
+  DEFAULT_MANAGER.add_fallible_keybind(kb, func).await
+}
+
+/// Set (or with `None`, remove) the observer told about the outcome of every handler added with
+/// [`add_fallible_keybind`]. It runs on the handler's task, right after the handler finished.
+pub async fn set_outcome_observer(observer: Option<OutcomeObserver>) {
+  DEFAULT_MANAGER.set_outcome_observer(observer).await
+}
+
+/// Like [`add_keybind`], for a handler which receives the count typed before the binding, e.g. to
+/// move down three items on "3 j"; see
+/// [`set_count_prefix_modes`][crate::events::set_count_prefix_modes]. Without a count, it receives
+/// 1.
+pub async fn add_counted_keybind<T, F>(kb: KeyBinding, func: T) -> bool
+where
+  T: Fn(u32) -> F + Send + Sync + 'static,
+  F: Future<Output=()> + Send + 'static
+{
+  DEFAULT_MANAGER.add_counted_keybind(kb, func).await
+}
+
+/// Set (or with `None`, remove) the observer told whenever a global binding is added or removed,
+/// or a mode's keymap is loaded, e.g. to keep a cached help screen up to date.
+///
+/// It runs right after the change, on the task which made it, with none of the keymaps locked, so
+/// it may e.g. spawn a task which reads them. It must not set the keymap observer itself.
+pub async fn set_keymap_observer(observer: Option<KeymapObserver>) {
+  DEFAULT_MANAGER.set_keymap_observer(observer).await
+}
+
+/// Bind one action to a quick tap of `kb` and another to holding it down for at least
+/// `threshold`.
+///
+/// The tap action is only decided on once the key is released before `threshold` has passed; it
+/// then goes down the notification channel like any other binding, and [`run_keybind_func`] runs
+/// `tap`. The hold action fires as soon as `threshold` has passed with the key still down, and
+/// `hold` is run directly on the runtime, without a notification. Modifiers in `kb` only need to
+/// be held when the key is pressed.
+pub async fn add_tap_hold_keybind<T, F, U, G>(kb: KeyBinding, threshold: Duration, tap: T, hold: U) -> bool
+where
+  T: Fn() -> F + Send + Sync + 'static,
+  F: Future<Output=()> + Send + 'static,
+  U: Fn() -> G + Send + Sync + 'static,
+  G: Future<Output=()> + Send + 'static
+{
+  DEFAULT_MANAGER.add_tap_hold_keybind(kb, threshold, tap, hold).await
+}
+
+pub fn hold_threshold_sync(kb: &KeyBinding) -> Option<Duration> {
+  DEFAULT_MANAGER.hold_threshold_sync(kb)
+}
+
+pub async fn run_hold_func(kb: &KeyBinding) {
+  DEFAULT_MANAGER.run_hold_func(kb).await
+}
+
+/// Run `func` when all of `combo`'s keys are pressed within its `window` of each other.
+///
+/// Combos are checked before ordinary bindings when the last of their keys goes down, and `func`
+/// is run directly on the runtime, without a notification. The keys pressed before the last one
+/// have already been handled as usual by then, so combos work best with keys which are passed
+/// through or bound to something harmless on their own. Adding an equal combo replaces its
+/// handler.
+pub async fn add_combo<T, F>(combo: Combo, func: T)
+where
+  T: Fn() -> F + Send + Sync + 'static,
+  F: Future<Output=()> + Send + 'static
+{
+  DEFAULT_MANAGER.add_combo(combo, func).await
+}
+
+pub async fn remove_combo(combo: &Combo) {
+  DEFAULT_MANAGER.remove_combo(combo).await
+}
+
+pub fn combo_match_sync(held: &[(Key, SystemTime)], mods: Modifiers) -> Option<Combo> {
+  DEFAULT_MANAGER.combo_match_sync(held, mods)
+}
+
+pub async fn run_combo_func(combo: &Combo) {
+  DEFAULT_MANAGER.run_combo_func(combo).await
+}
+
+/// Add a binding which is only active in the given `modes`, rather than registering it once per
+/// mode.
+///
+/// If `kb.mode` is set, the binding is active in that mode as well. An empty list of modes with no
+/// `kb.mode` makes the binding inactive everywhere; to bind in all modes, use [`add_keybind`].
+pub async fn add_keybind_for_modes<T, F>(kb: KeyBinding, modes: Vec<ScreenReaderMode>, func: T) -> bool
+where
+  T: Fn() -> F + Send + Sync + 'static,
+  F: Future<Output=()> + Send + 'static
+{
+  DEFAULT_MANAGER.add_keybind_for_modes(kb, modes, func).await
+}
+
+/// Replace the handler of `kb` with `func` until the returned guard is dropped, e.g. for the
+/// duration of a modal dialog. The original handler, or the lack of one, is put back before the
+/// drop returns, even when dropping the guard because of a panic, so a key press right after it
+/// already runs the original.
+///
+/// Only handlers added with [`add_keybind`] (or its variants) can be overridden, not those in a
+/// mode's keymap. Overrides of the same binding should be dropped in the reverse order they were
+/// made.
+pub async fn override_keybind<T, F>(kb: KeyBinding, func: T) -> OverrideGuard
+where
+  T: Fn() -> F + Send + Sync + 'static,
+  F: Future<Output=()> + Send + 'static
+{
+  DEFAULT_MANAGER.override_keybind(kb, func).await
+}
+
+/// Like [`add_keybind`], with a description of what the binding does and a category to list it
+/// under in [`keybind_help`].
+pub async fn add_keybind_described<T, F>(kb: KeyBinding, description: &str, category: &str, func: T) -> bool
+where
+  T: Fn() -> F + Send + Sync + 'static,
+  F: Future<Output=()> + Send + 'static
+{
+  DEFAULT_MANAGER.add_keybind_described(kb, description, category, func).await
+}
+
+/// Every binding added with [`add_keybind_described`], sorted by category and then by
+/// description, e.g. for a "keyboard shortcuts" help screen.
+pub async fn keybind_help() -> Vec<KeybindHelp> {
+  DEFAULT_MANAGER.keybind_help().await
+}
+
+/// The enabled bindings which can fire in the current mode: those of the mode's own keymap (see
+/// [`load_mode_keymap`]), and the global bindings which are either for every mode or for this one.
+/// Useful for a help screen showing only what applies right now.
+pub async fn active_keybinds() -> Vec<KeyBinding> {
+  DEFAULT_MANAGER.active_keybinds().await
+}
+
+/// Switch to `mode` whenever `kb` fires, once its handlers have run (or timed out), e.g. for a key
+/// which enters browse mode. `None` stops the switch. Unlike calling [`set_sr_mode`] from the
+/// handler, this shows up in [`keybind_help`] and [`then_mode`].
+///
+/// The switch happens wherever the handler is run from: [`run_keybind_func`], [`trigger`] and
+/// their `_sync` variants. A mode which is not [registered][register_mode] is not switched to; a
+/// warning is logged instead.
+pub async fn set_then_mode(kb: &KeyBinding, mode: Option<ScreenReaderMode>) {
+  DEFAULT_MANAGER.set_then_mode(kb, mode).await
+}
+
+/// The mode `kb` switches to after firing, if any; see [`set_then_mode`].
+pub async fn then_mode(kb: &KeyBinding) -> Option<ScreenReaderMode> {
+  DEFAULT_MANAGER.then_mode(kb).await
+}
+
+/// Record that `kb` runs the action called `name`, so that it can be written to a keybinding file
+/// with [`export_keybinds`][crate::persist::export_keybinds]; `None` forgets the name. Bindings
+/// installed from a file with [`KeybindDocument::install`][crate::persist::KeybindDocument::install]
+/// are named already.
+pub async fn set_action_name(kb: &KeyBinding, name: Option<&str>) {
+  DEFAULT_MANAGER.set_action_name(kb, name).await
+}
+
+/// Every global binding (not those of [mode keymaps][load_mode_keymap]) with the name of the action
+/// it runs, if it has one (see [`set_action_name`]), in no particular order.
+pub async fn named_keybinds() -> Vec<(KeyBinding, Option<String>)> {
+  DEFAULT_MANAGER.named_keybinds().await
+}
+
+/// Choose whether `kb` matches the physical key or the character it produced; see
+/// [`MatchStrategy`]. A logical binding's `key` should be a [`Key::Other`] character.
+///
+/// Only the input monitoring thread knows the produced character; [`keyevent_match`] and
+/// [`keyevent_match_sync`] match every binding physically.
+pub async fn set_match_strategy(kb: &KeyBinding, strategy: MatchStrategy) {
+  DEFAULT_MANAGER.set_match_strategy(kb, strategy).await
+}
+
+/// Choose when `kb` keeps its key from the rest of the system; see [`ConsumePolicy`]. Only matters
+/// for bindings with `consume` set.
+pub async fn set_consume_policy(kb: &KeyBinding, policy: ConsumePolicy) {
+  DEFAULT_MANAGER.set_consume_policy(kb, policy).await
+}
+
+/// Set how to tell whether the focused application would use a key event itself, e.g. because a
+/// text field has focus, for bindings with [`ConsumePolicy::IfUnhandled`]; `None` removes it.
+///
+/// Like a [guard][set_keybind_guard], the predicate runs on the input monitoring thread, so it
+/// should be quick and must not call into this module.
+pub async fn set_app_key_predicate(predicate: Option<AppKeyPredicate>) {
+  DEFAULT_MANAGER.set_app_key_predicate(predicate).await
+}
+
+pub fn consumes_sync(kb: &KeyBinding, kbm: &KeyEvent) -> bool {
+  DEFAULT_MANAGER.consumes_sync(kb, kbm)
+}
+
+/// Only let `kb` match while `guard` returns true, e.g. only while a text field has focus; `None`
+/// removes the guard.
+///
+/// The guard is called whenever `kb` would otherwise match, before anything is consumed or sent.
+/// While it returns false, the binding is treated as if it didn't exist: another binding may match
+/// instead, or the event passes through. It runs on the input monitoring thread halfway through
+/// handling the event, so it should be quick and must not call into [`events`][crate::events].
+pub async fn set_keybind_guard(kb: &KeyBinding, guard: Option<KeybindGuard>) {
+  DEFAULT_MANAGER.set_keybind_guard(kb, guard).await
+}
+
+/// Let `kb` fire at most once per `interval`, e.g. so that mashing "announce time" doesn't queue
+/// up announcements. `None`, the default, removes the limit.
+///
+/// A throttled key press is still consumed as usual; it just doesn't notify. This is unlike a
+/// debounce: the first press always fires, and later ones only once the interval has passed since.
+pub async fn set_keybind_throttle(kb: &KeyBinding, interval: Option<Duration>) {
+  DEFAULT_MANAGER.set_keybind_throttle(kb, interval).await
+}
+
+pub fn throttle_allows_sync(kb: &KeyBinding, now: SystemTime) -> bool {
+  DEFAULT_MANAGER.throttle_allows_sync(kb, now)
+}
+
+/// Run `func` too whenever `kb` fires, besides the handler it was added with, e.g. to log a command
+/// before it is carried out. Returns `false`, adding nothing, if nothing is bound to `kb` yet.
+///
+/// The handlers run in order of `priority`, highest first; the binding's own handler has priority
+/// 0. Whether each waits for the one before it is up to [`set_handler_dispatch`]. The extra
+/// handlers go away with [`remove_keybind`], and are shared by `kb`'s handlers in every mode's
+/// keymap.
+pub async fn add_handler<T, F>(kb: &KeyBinding, priority: i32, func: T) -> bool
+where
+  T: Fn() -> F + Send + Sync + 'static,
+  F: Future<Output=()> + Send + 'static
+{
+  DEFAULT_MANAGER.add_handler(kb, priority, func).await
+}
+
+/// Choose whether the handlers of `kb` (see [`add_handler`]) run one after another or all at
+/// once. Either way they are started in order of priority, and [`set_keybind_timeout`] applies to
+/// all of them together. Bindings with a single handler are unaffected.
+pub async fn set_handler_dispatch(kb: &KeyBinding, dispatch: HandlerDispatch) {
+  DEFAULT_MANAGER.set_handler_dispatch(kb, dispatch).await
+}
+
+/// Abort the handler of `kb` (including the hold handler of a tap/hold binding) if it runs for
+/// longer than `timeout`, logging a warning. `None`, the default, lets it run for as long as it
+/// likes.
+pub async fn set_keybind_timeout(kb: &KeyBinding, timeout: Option<Duration>) {
+  DEFAULT_MANAGER.set_keybind_timeout(kb, timeout).await
+}
+
+/// Turn a binding off or back on without removing it, e.g. to let single letters through while
+/// the user is typing into a text field.
+///
+/// A disabled binding keeps its handler but is skipped when matching, so the next best binding
+/// (or none) handles the key instead. In particular, a disabled binding with `consume` set no
+/// longer keeps its key from the rest of the system; unless another binding matches, the key
+/// passes through to the focused application. Bindings are enabled when added.
+pub async fn set_keybind_enabled(kb: &KeyBinding, enabled: bool) {
+  DEFAULT_MANAGER.set_keybind_enabled(kb, enabled).await
+}
+
+pub async fn is_keybind_enabled(kb: &KeyBinding) -> bool {
+  DEFAULT_MANAGER.is_keybind_enabled(kb).await
+}
+
+/// Set a handler to run whenever a key is pressed which no binding matches in the current mode,
+/// e.g. to beep or to hand the key to a command interpreter.
+///
+/// This does not change whether the key is consumed: unhandled keys are always passed through.
+pub async fn set_unhandled_handler(handler: AsyncFnCtx) {
+  DEFAULT_MANAGER.set_unhandled_handler(handler).await
+}
+
+pub async fn remove_unhandled_handler() {
+  DEFAULT_MANAGER.remove_unhandled_handler().await
+}
+
+pub fn unhandled_keyevent_sync(kbm: &KeyEvent) -> Option<Box<dyn Future<Output = ()> + Unpin + Send + 'static>> {
+  DEFAULT_MANAGER.unhandled_keyevent_sync(kbm)
+}
+
+/// Load the keymap for a screen reader mode, replacing any map previously loaded for it.
+///
+/// The map is only consulted while `mode` is the active mode (see [`set_sr_mode`]); bindings added
+/// with [`add_keybind`] stay global and are considered in every mode.
+pub async fn load_mode_keymap(mode: ScreenReaderMode, keymap: HashMap<KeyBinding, AsyncFn>) {
+  DEFAULT_MANAGER.load_mode_keymap(mode, keymap).await
 }
 
 pub async fn keyevent_match(kbm: &KeyEvent) -> Option<KeyBinding>
 {
-  let kbhm = KB_MAP.lock().await;
-  let sr_mode = get_sr_mode().await;
-  for (kb, _) in kbhm.iter() {
-    let mut matches = true;
-    matches &= kb.key == kbm.key;
-    matches &= kb.repeat == kbm.repeat;
-    matches &= (kb.mods == Modifiers::NONE && kbm.mods == Modifiers::NONE) || kb.mods.intersects(kbm.mods);
-    if let Some(mode) = &kb.mode {
-      matches &= *mode == sr_mode;
-    }
-    if matches {
-      return Some(kb.clone());
-    }
-  }
-  None
+  DEFAULT_MANAGER.keyevent_match(kbm).await
 }
 
-/* this will match with the bitflags */
 pub fn keyevent_match_sync(kbm: &KeyEvent) -> Option<KeyBinding>
 {
-  let kbhm = KB_MAP.blocking_lock();
-  let sr_mode = get_sr_mode_sync();
-  for (kb, _) in kbhm.iter() {
-    let mut matches = true;
-    matches &= kb.key == kbm.key;
-    matches &= kb.repeat == kbm.repeat;
-    matches &= (kb.mods == Modifiers::NONE && kbm.mods == Modifiers::NONE) || kb.mods.intersects(kbm.mods);
-    if let Some(mode) = &kb.mode {
-      matches &= *mode == sr_mode;
-    }
-    if matches {
-      return Some(kb.clone());
-    }
-  }
-  None
-} 
+  DEFAULT_MANAGER.keyevent_match_sync(kbm)
+}
+
+pub fn keyevent_match_char_sync(kbm: &KeyEvent, produced: Option<char>) -> Option<KeyBinding>
+{
+  DEFAULT_MANAGER.keyevent_match_char_sync(kbm, produced)
+}
+
+pub fn has_higher_repeat_sync(kbm: &KeyEvent) -> bool {
+  DEFAULT_MANAGER.has_higher_repeat_sync(kbm)
+}
+
+/// Explain, for every registered binding, why it does or does not match `kbm` in the current mode.
+///
+/// Each binding comes with the list of reasons it fails to match; an empty list means it matches.
+/// This is meant for debugging bindings which don't fire as expected.
+pub async fn explain_match(kbm: &KeyEvent) -> Vec<(KeyBinding, Vec<MatchFailure>)> {
+  DEFAULT_MANAGER.explain_match(kbm).await
+}
+
+/// The binding which would fire for `kbm` in the current mode, with its description if it was
+/// added with [`add_keybind_described`], without running anything; e.g. for a settings screen
+/// saying what a key currently does. `None` if no enabled binding matches.
+///
+/// Like [`keyevent_match`], this matches every binding physically (see [`MatchStrategy`]).
+///
+/// The description is a `String`, not a `&'static str`: [`add_keybind_described`] takes any
+/// `&str`, e.g. one translated or read from a config file at runtime, and rebinding replaces it,
+/// so the keymap owns its descriptions and can only hand out a copy.
+pub async fn resolve_binding(kbm: &KeyEvent) -> Option<(KeyBinding, Option<String>)> {
+  DEFAULT_MANAGER.resolve_binding(kbm).await
+}
+
+/// Whether `kbm` would be consumed by the input monitoring thread, without running anything.
+///
+/// This is [`classify`][crate::events::classify]'s answer for the global manager, so it accounts
+/// for [observe only mode][crate::events::set_observe_only] and
+/// [`always_consume_keys`][crate::events::always_consume_keys] too.
+///
+/// This never waits for bindings being changed: it matches against the bindings as they were
+/// after the last change, which is what the input monitoring thread does too. It still blocks
+/// briefly, so it must not be called from async code.
+pub fn would_consume(kbm: &KeyEvent) -> bool {
+  DEFAULT_MANAGER.would_consume(kbm)
+}
+
+/// Whether `kbm` would be sent down the notification channel, without running anything.
+///
+/// Like [`would_consume`], this is [`classify`][crate::events::classify]'s answer, so a
+/// [throttled][set_keybind_throttle] binding which fired too recently doesn't notify. It never
+/// waits for bindings being changed, but must not be called from async code.
+pub fn would_notify(kbm: &KeyEvent) -> bool {
+  DEFAULT_MANAGER.would_notify(kbm)
+}
+
+/// Read the current mode from `provider` instead of keeping track of it here, so that a host which
+/// manages the mode elsewhere has a single source of truth. `None` goes back to the mode set with
+/// [`set_sr_mode`].
+///
+/// While a provider is set, [`set_sr_mode`] has no visible effect. The provider is called for
+/// every key event, from the input monitoring thread, so it should be quick and must not block on
+/// async code.
+pub async fn set_mode_provider(provider: Option<ModeProvider>) {
+  DEFAULT_MANAGER.set_mode_provider(provider).await
+}
 
 pub fn get_sr_mode_sync() -> ScreenReaderMode {
-  SR_MODE.blocking_lock().clone()
+  DEFAULT_MANAGER.get_sr_mode_sync()
 }
-pub fn set_sr_mode_sync(srm: ScreenReaderMode) { 
-  let mut sr_mode = SR_MODE.blocking_lock();
-  *sr_mode = srm;
+pub fn set_sr_mode_sync(srm: ScreenReaderMode) -> Result<(), UnknownMode> {
+  DEFAULT_MANAGER.set_sr_mode_sync(srm)
 }
 pub async fn get_sr_mode() -> ScreenReaderMode {
-  SR_MODE.lock().await.clone()
+  DEFAULT_MANAGER.get_sr_mode().await
+}
+/// Switch to `srm`.
+///
+/// # Errors
+/// [`UnknownMode`] if modes were registered with [`register_mode`], and `srm` is not one of them.
+/// Hosts which never register a mode can switch to any mode.
+pub async fn set_sr_mode(srm: ScreenReaderMode) -> Result<(), UnknownMode> {
+  DEFAULT_MANAGER.set_sr_mode(srm).await
+}
+
+/// Add `mode` to the modes [`set_sr_mode`] accepts, to catch misspelled mode names. Once any mode
+/// is registered, switching to a mode which isn't fails.
+pub async fn register_mode(mode: ScreenReaderMode) {
+  DEFAULT_MANAGER.register_mode(mode).await
+}
+
+/// Wait for the next complete key combination (a key plus any held modifiers) and return it as a
+/// [`KeyBinding`], for example to let a user pick a new shortcut in a settings dialog.
+///
+/// The captured event is consumed and does not trigger any existing binding. Only one capture can
+/// be pending at a time; further calls wait until the earlier capture has resolved.
+pub async fn capture_next_keybinding() -> KeyBinding {
+  let _pending = CAPTURE_LOCK.lock().await;
+  let (tx, rx) = oneshot::channel();
+  *lock(&CAPTURE) = Some(tx);
+  /* the sender is only dropped after sending, or when replaced by a later capture (which cannot happen while we hold CAPTURE_LOCK) */
+  rx.await.expect("Key capture was dropped without a key binding!")
+}
+
+/* whether capture_keyevent_sync would swallow the next key combination */
+pub(crate) fn capture_pending_sync() -> bool {
+  lock(&CAPTURE).as_ref().is_some_and(|tx| !tx.is_closed())
+}
+
+/* used from the input monitoring thread; returns true if the event was handed to a pending capture */
+pub fn capture_keyevent_sync(kbm: &KeyEvent) -> bool {
+  let mut capture = lock(&CAPTURE);
+  match capture.take() {
+    /* if the capturing future was dropped, let the event through as usual */
+    Some(tx) => tx.send(KeyBinding {
+      key: kbm.key.clone(),
+      mods: kbm.mods,
+      repeat: kbm.repeat,
+      consume: true,
+      notify: false,
+      mode: None,
+    }).is_ok(),
+    None => false,
+  }
+}
+
+/// Wait for the next key event for which `predicate` returns `true`, e.g. for a "press any
+/// navigation key to continue" prompt.
+///
+/// Nothing is registered permanently, and whether the event is consumed or passed through is left
+/// to the usual bindings.
+pub async fn wait_for<P>(predicate: P) -> KeyEvent
+where
+  P: Fn(&KeyEvent) -> bool + Send + Sync + 'static
+{
+  let (tx, rx) = oneshot::channel();
+  lock(&WAITERS).push((Box::new(predicate), tx));
+  /* senders are only dropped after sending, or once this future is gone */
+  rx.await.expect("Key event waiter was dropped without a key event!")
 }
-pub async fn set_sr_mode(srm: ScreenReaderMode) {
-  let mut sr_mode = SR_MODE.lock().await;
-  *sr_mode = srm;
+
+/// Like [`wait_for`], but gives up and returns `None` after `timeout`.
+pub async fn wait_for_timeout<P>(predicate: P, timeout: Duration) -> Option<KeyEvent>
+where
+  P: Fn(&KeyEvent) -> bool + Send + Sync + 'static
+{
+  tokio::time::timeout(timeout, wait_for(predicate)).await.ok()
+}
+
+/* used from the input monitoring thread; resolves every waiter whose predicate accepts the event */
+pub fn wake_waiters_sync(kbm: &KeyEvent) {
+  let mut waiters = lock(&WAITERS);
+  for (predicate, tx) in std::mem::take(&mut *waiters) {
+    /* the waiting future was dropped, e.g. because it timed out */
+    if tx.is_closed() {
+      continue;
+    }
+    if predicate(kbm) {
+      let _ = tx.send(kbm.clone());
+    } else {
+      waiters.push((predicate, tx));
+    }
+  }
 }
 
 /* this is to bridge with events.rs; now init_keyhandlers will be all handled within odilia-input */
-/* TODO: do sync version */
 pub async fn run_keybind_func(kb: &KeyBinding) {
-  let kbhm = KB_MAP.lock().await;
-  let func = kbhm.get(kb).expect("Key binding not found!");
-  func().await;
+  DEFAULT_MANAGER.run_keybind_func(kb).await
+}
+
+/// Run the handler bound to `kb` and wait for it to finish, e.g. to run an action picked from a
+/// menu or spoken as a voice command through the same bindings as the keyboard.
+///
+/// Unlike [`run_keybind_func`], this also runs in
+/// [observe only mode][crate::events::set_observe_only], since it doesn't come from a key press.
+/// The handler of the active mode's keymap wins over a global one, and the binding's timeout (see
+/// [`set_keybind_timeout`]) applies.
+/// # Errors
+/// [`NotBound`] if no handler is bound to `kb` in the global keymap or the active mode's.
+pub async fn trigger(kb: &KeyBinding) -> Result<(), NotBound> {
+  DEFAULT_MANAGER.trigger(kb).await
+}
+
+/// Like [`run_keybind_func`], for hosts without an async runtime (see
+/// [`init_sync`][crate::events::init_sync]): the handler is started on the input monitoring
+/// system's runtime, and this returns right away. Does nothing before the input monitoring system
+/// is initialised.
+pub fn run_keybind_func_sync(kb: &KeyBinding) {
+  let binding = kb.clone();
+  if !spawn_handler(async move { DEFAULT_MANAGER.run_keybind_func(&binding).await }) {
+    tracing::warn!("Input monitoring is not initialised; not running the handler of {:?}", kb);
+  }
+}
+
+/// Like [`add_keybind`], for a synchronous handler and callable from outside of async code.
+///
+/// The handler runs on a blocking thread of the input monitoring system's runtime, so it may
+/// block.
+pub fn add_sync_keybind<T>(kb: KeyBinding, func: T) -> bool
+where
+  T: Fn() + Send + Sync + 'static
+{
+  DEFAULT_MANAGER.add_sync_keybind(kb, func)
 }
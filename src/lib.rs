@@ -1,5 +1,13 @@
 #[macro_use]
 extern crate lazy_static;
 
+pub mod backend;
 pub mod events;
 pub mod keybinds;
+pub mod persist;
+
+// What `keybinds!` expands to, so that crates using it need not depend on odilia-common themselves
+#[doc(hidden)]
+pub mod __macro_support {
+    pub use odilia_common::input::{Key, KeyBinding, Modifiers};
+}
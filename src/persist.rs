@@ -0,0 +1,599 @@
+//! Saving and loading keybindings.
+//!
+//! Only the structure of each [`KeyBinding`] is stored, together with the name of the action it
+//! triggers. When loading, action names are resolved to handlers through an [`ActionRegistry`],
+//! since [`AsyncFn`]s themselves cannot be serialized.
+use crate::keybinds::{add_keybinds, async_fn, named_keybinds, set_action_name, AsyncFn};
+
+use odilia_common::{
+    input::{Key, KeyBinding, Modifiers},
+    modes::ScreenReaderMode,
+};
+use serde::{Deserialize, Serialize};
+use toml_edit::{value, Array, ArrayOfTables, DocumentMut, Item, Table, TomlError};
+
+use std::{collections::HashMap, fmt, future::Future, io, path::Path, sync::Arc};
+
+/// Names of the non-character [`Key`]s. Any other key is written as the character it produces.
+const KEY_NAMES: &[(Key, &str)] = &[
+    (Key::Backspace, "Backspace"),
+    (Key::Delete, "Delete"),
+    (Key::Down, "Down"),
+    (Key::Up, "Up"),
+    (Key::Left, "Left"),
+    (Key::Right, "Right"),
+    (Key::End, "End"),
+    (Key::Escape, "Escape"),
+    (Key::F1, "F1"),
+    (Key::F2, "F2"),
+    (Key::F3, "F3"),
+    (Key::F4, "F4"),
+    (Key::F5, "F5"),
+    (Key::F6, "F6"),
+    (Key::F7, "F7"),
+    (Key::F8, "F8"),
+    (Key::F9, "F9"),
+    (Key::F10, "F10"),
+    (Key::F11, "F11"),
+    (Key::F12, "F12"),
+    (Key::Home, "Home"),
+    (Key::PageDown, "PageDown"),
+    (Key::PageUp, "PageUp"),
+    (Key::Return, "Return"),
+    (Key::Space, "Space"),
+    (Key::Tab, "Tab"),
+    (Key::PrintScreen, "PrintScreen"),
+    (Key::ScrollLock, "ScrollLock"),
+    (Key::Pause, "Pause"),
+    (Key::NumLock, "NumLock"),
+    (Key::Kp0, "Kp0"),
+    (Key::Kp1, "Kp1"),
+    (Key::Kp2, "Kp2"),
+    (Key::Kp3, "Kp3"),
+    (Key::Kp4, "Kp4"),
+    (Key::Kp5, "Kp5"),
+    (Key::Kp6, "Kp6"),
+    (Key::Kp7, "Kp7"),
+    (Key::Kp8, "Kp8"),
+    (Key::Kp9, "Kp9"),
+    (Key::Insert, "Insert"),
+    (Key::KpReturn, "KpReturn"),
+    (Key::KpMinus, "KpMinus"),
+    (Key::KpPlus, "KpPlus"),
+    (Key::KpMultiply, "KpMultiply"),
+    (Key::KpDivide, "KpDivide"),
+    (Key::KpDelete, "KpDelete"),
+    (Key::Function, "Function"),
+];
+
+/// [`Modifiers`] are stored as a list of flag names rather than as raw bits, so that config files
+/// stay readable and don't depend on the bit layout.
+const MODIFIER_NAMES: &[(Modifiers, &str)] = &[
+    (Modifiers::ODILIA, "ODILIA"),
+    (Modifiers::META_L, "META_L"),
+    (Modifiers::META_R, "META_R"),
+    (Modifiers::ALT_L, "ALT_L"),
+    (Modifiers::ALT_R, "ALT_R"),
+    (Modifiers::CONTROL_L, "CONTROL_L"),
+    (Modifiers::CONTROL_R, "CONTROL_R"),
+    (Modifiers::SHIFT_L, "SHIFT_L"),
+    (Modifiers::SHIFT_R, "SHIFT_R"),
+];
+
+/// The name a [`Key`] is stored under.
+pub fn key_name(key: &Key) -> String {
+    match key {
+        Key::Other(c) => c.to_string(),
+        _ => KEY_NAMES
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, name)| name.to_string())
+            .unwrap_or_else(|| format!("{:?}", key)),
+    }
+}
+
+/// Parse a [`Key`] from the name it is stored under (see [`key_name`]).
+pub fn key_from_name(name: &str) -> Option<Key> {
+    if let Some((key, _)) = KEY_NAMES.iter().find(|(_, n)| *n == name) {
+        return Some(key.clone());
+    }
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(Key::Other(c)),
+        _ => None,
+    }
+}
+
+/// The names of the flags set in `mods`.
+pub fn modifier_names(mods: Modifiers) -> Vec<&'static str> {
+    MODIFIER_NAMES
+        .iter()
+        .filter(|(m, _)| mods.contains(*m))
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+/// Parse a single modifier flag from its name (see [`modifier_names`]).
+pub fn modifier_from_name(name: &str) -> Option<Modifiers> {
+    MODIFIER_NAMES
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|(m, _)| *m)
+}
+
+/// A [`KeyBinding`] as it is stored, together with the name of the action it triggers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeybindEntry {
+    pub action: String,
+    pub key: Option<String>,
+    #[serde(default)]
+    pub mods: Vec<String>,
+    pub repeat: u8,
+    pub consume: bool,
+    pub notify: bool,
+    #[serde(default)]
+    pub mode: Option<String>,
+}
+
+impl KeybindEntry {
+    pub fn new(kb: &KeyBinding, action: &str) -> Self {
+        KeybindEntry {
+            action: action.to_string(),
+            key: kb.key.as_ref().map(key_name),
+            mods: modifier_names(kb.mods).into_iter().map(String::from).collect(),
+            repeat: kb.repeat,
+            consume: kb.consume,
+            notify: kb.notify,
+            mode: kb.mode.as_ref().map(|mode| mode.name.clone()),
+        }
+    }
+
+    /// Rebuild the [`KeyBinding`] this entry describes.
+    ///
+    /// The `entry` of any error is 0, since the entry does not know where it came from.
+    pub fn to_keybinding(&self) -> Result<KeyBinding, ConfigError> {
+        let key = match &self.key {
+            Some(name) => Some(key_from_name(name).ok_or_else(|| ConfigError::UnknownKey {
+                name: name.clone(),
+                entry: 0,
+            })?),
+            None => None,
+        };
+        let mut mods = Modifiers::empty();
+        for token in &self.mods {
+            mods |= modifier_from_name(token).ok_or_else(|| ConfigError::BadModifier {
+                token: token.clone(),
+                entry: 0,
+            })?;
+        }
+        Ok(KeyBinding {
+            key,
+            mods,
+            repeat: self.repeat,
+            consume: self.consume,
+            notify: self.notify,
+            mode: self.mode.as_deref().map(ScreenReaderMode::new),
+        })
+    }
+}
+
+/// An error encountered while loading keybindings.
+///
+/// Errors about a single binding carry `entry`, the index of the offending entry in the file
+/// (counting from 0).
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The input is not valid JSON, or does not have the expected structure. The line and column
+    /// are available through [`location`][Self::location].
+    Parse(serde_json::Error),
+    UnknownKey { name: String, entry: usize },
+    BadModifier { token: String, entry: usize },
+    /// No handler is registered for the named action.
+    UnknownAction { name: String, entry: usize },
+    /// The binding was already defined by an earlier entry.
+    DuplicateBinding { binding: KeyBinding, entry: usize },
+    /// A TOML keybinding file (see [`KeybindDocument`]) is not valid TOML. The byte range of the
+    /// problem is available through [`TomlError::span`].
+    Toml(TomlError),
+    /// A field of an entry in a TOML keybinding file is missing or has the wrong type.
+    BadField { field: &'static str, entry: usize },
+}
+
+impl ConfigError {
+    /// The line and column (both counting from 1) of a [`Parse`][Self::Parse] error.
+    pub fn location(&self) -> Option<(usize, usize)> {
+        match self {
+            ConfigError::Parse(e) => Some((e.line(), e.column())),
+            _ => None,
+        }
+    }
+
+    /// The index of the entry an error is about, if it is about a single entry.
+    pub fn entry(&self) -> Option<usize> {
+        match self {
+            ConfigError::Parse(_) | ConfigError::Toml(_) => None,
+            ConfigError::UnknownKey { entry, .. }
+            | ConfigError::BadModifier { entry, .. }
+            | ConfigError::UnknownAction { entry, .. }
+            | ConfigError::DuplicateBinding { entry, .. }
+            | ConfigError::BadField { entry, .. } => Some(*entry),
+        }
+    }
+
+    fn at_entry(mut self, index: usize) -> Self {
+        match &mut self {
+            ConfigError::Parse(_) | ConfigError::Toml(_) => {},
+            ConfigError::UnknownKey { entry, .. }
+            | ConfigError::BadModifier { entry, .. }
+            | ConfigError::UnknownAction { entry, .. }
+            | ConfigError::DuplicateBinding { entry, .. }
+            | ConfigError::BadField { entry, .. } => *entry = index,
+        }
+        self
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Parse(e) => write!(f, "invalid keybinding file: {}", e),
+            ConfigError::UnknownKey { name, entry } => write!(f, "entry {}: unknown key: {:?}", entry, name),
+            ConfigError::BadModifier { token, entry } => write!(f, "entry {}: unknown modifier: {:?}", entry, token),
+            ConfigError::UnknownAction { name, entry } => write!(f, "entry {}: unknown action: {:?}", entry, name),
+            ConfigError::DuplicateBinding { binding, entry } => write!(f, "entry {}: {:?} is bound more than once", entry, binding),
+            ConfigError::Toml(e) => write!(f, "invalid keybinding file: {}", e),
+            ConfigError::BadField { field, entry } => write!(f, "entry {}: missing or invalid {:?}", entry, field),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Parse(e) => Some(e),
+            ConfigError::Toml(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for ConfigError {
+    fn from(e: serde_json::Error) -> Self {
+        ConfigError::Parse(e)
+    }
+}
+
+impl From<TomlError> for ConfigError {
+    fn from(e: TomlError) -> Self {
+        ConfigError::Toml(e)
+    }
+}
+
+/// Serialize keybindings, each paired with the name of the action it triggers, to JSON.
+pub fn save_keybinds(keybinds: &[(KeyBinding, String)]) -> serde_json::Result<String> {
+    let entries: Vec<KeybindEntry> = keybinds
+        .iter()
+        .map(|(kb, action)| KeybindEntry::new(kb, action))
+        .collect();
+    serde_json::to_string_pretty(&entries)
+}
+
+/// The handlers actions in a keybinding file can refer to, by name.
+///
+/// ```
+/// # use odilia_input::persist::ActionRegistry;
+/// let mut actions = ActionRegistry::new();
+/// actions.register_action("read_line", || async { /* ... */ });
+/// assert!(actions.contains("read_line"));
+/// ```
+#[derive(Default)]
+pub struct ActionRegistry {
+    actions: HashMap<String, Arc<AsyncFn>>,
+}
+
+impl ActionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make `name` run `func`, replacing whatever it ran before.
+    pub fn register_action<T, F>(&mut self, name: &str, func: T)
+    where
+        T: Fn() -> F + Send + Sync + 'static,
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.actions.insert(name.to_string(), Arc::new(async_fn(func)));
+    }
+
+    /// Forget `name`, returning whether it was registered.
+    pub fn unregister_action(&mut self, name: &str) -> bool {
+        self.actions.remove(name).is_some()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.actions.contains_key(name)
+    }
+
+    /// A handler running the action registered as `name`, if there is one.
+    pub fn resolve(&self, name: &str) -> Option<AsyncFn> {
+        let func = Arc::clone(self.actions.get(name)?);
+        Some(Box::new(move || func()))
+    }
+
+    /// The registered action names, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.actions.keys().map(String::as_str)
+    }
+}
+
+impl From<HashMap<String, Arc<AsyncFn>>> for ActionRegistry {
+    fn from(actions: HashMap<String, Arc<AsyncFn>>) -> Self {
+        ActionRegistry { actions }
+    }
+}
+
+/// Load keybindings saved with [`save_keybinds`], resolving each action name through `actions`.
+///
+/// The result can be handed to [`load_mode_keymap`][crate::keybinds::load_mode_keymap], or its
+/// bindings registered one by one.
+pub fn load_keybinds(json: &str, actions: &ActionRegistry) -> Result<HashMap<KeyBinding, AsyncFn>, ConfigError> {
+    let entries: Vec<KeybindEntry> = serde_json::from_str(json)?;
+    load_entries(entries, actions)
+}
+
+fn load_entries(entries: Vec<KeybindEntry>, actions: &ActionRegistry) -> Result<HashMap<KeyBinding, AsyncFn>, ConfigError> {
+    let mut keybinds = HashMap::with_capacity(entries.len());
+    for (index, entry) in entries.into_iter().enumerate() {
+        let handler = actions
+            .resolve(&entry.action)
+            .ok_or_else(|| ConfigError::UnknownAction {
+                name: entry.action.clone(),
+                entry: index,
+            })?;
+        let kb = entry.to_keybinding().map_err(|e| e.at_entry(index))?;
+        if keybinds.contains_key(&kb) {
+            return Err(ConfigError::DuplicateBinding { binding: kb, entry: index });
+        }
+        keybinds.insert(kb, handler);
+    }
+    Ok(keybinds)
+}
+
+/// A keybinding file in TOML, which keeps the user's comments, ordering and formatting when a
+/// binding is changed and the file saved again.
+///
+/// Each binding is a `[[keybind]]` table with the fields of a [`KeybindEntry`]:
+///
+/// ```toml
+/// # Say the current line
+/// [[keybind]]
+/// action = "read_line"
+/// key = "l"
+/// mods = ["ODILIA"]
+/// repeat = 1
+/// consume = true
+/// notify = true
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct KeybindDocument {
+    doc: DocumentMut,
+}
+
+impl KeybindDocument {
+    pub fn parse(toml: &str) -> Result<Self, ConfigError> {
+        Ok(KeybindDocument { doc: toml.parse()? })
+    }
+
+    fn tables(&self) -> Option<&ArrayOfTables> {
+        self.doc.get("keybind").and_then(Item::as_array_of_tables)
+    }
+
+    /// The entries of the file, in order.
+    pub fn entries(&self) -> Result<Vec<KeybindEntry>, ConfigError> {
+        self.tables()
+            .into_iter()
+            .flat_map(ArrayOfTables::iter)
+            .enumerate()
+            .map(|(index, table)| table_to_entry(table).map_err(|field| ConfigError::BadField { field, entry: index }))
+            .collect()
+    }
+
+    /// Load the bindings, resolving each action name through `actions` like [`load_keybinds`].
+    pub fn load(&self, actions: &ActionRegistry) -> Result<HashMap<KeyBinding, AsyncFn>, ConfigError> {
+        load_entries(self.entries()?, actions)
+    }
+
+    /// Load the bindings like [`load`][Self::load] and add them to the global keymap, remembering
+    /// each one's action name for [`export_keybinds`]. Nothing is added if any entry is invalid.
+    ///
+    /// Returns the bindings which were not added because they were bound already, like
+    /// [`add_keybinds`][crate::keybinds::add_keybinds].
+    pub async fn install(&self, actions: &ActionRegistry) -> Result<Vec<KeyBinding>, ConfigError> {
+        let entries = self.entries()?;
+        // Any entry skipped here makes load_entries fail below
+        let names: Vec<(KeyBinding, String)> = entries
+            .iter()
+            .filter_map(|entry| Some((entry.to_keybinding().ok()?, entry.action.clone())))
+            .collect();
+        let keybinds = load_entries(entries, actions)?;
+        let conflicts = add_keybinds(keybinds).await;
+        for (kb, action) in names {
+            if !conflicts.contains(&kb) {
+                set_action_name(&kb, Some(&action)).await;
+            }
+        }
+        Ok(conflicts)
+    }
+
+    /* unlike set_binding, keeps other entries for the same action */
+    fn push_binding(&mut self, action: &str, kb: &KeyBinding) {
+        let tables = self.doc
+            .entry("keybind")
+            .or_insert(Item::ArrayOfTables(ArrayOfTables::new()));
+        if let Some(tables) = tables.as_array_of_tables_mut() {
+            let mut table = Table::new();
+            fill_table(&mut table, &KeybindEntry::new(kb, action));
+            tables.push(table);
+        }
+    }
+
+    /// Bind `action` to `kb`, changing the first entry for `action` in place, or adding a new entry
+    /// at the end if there is none. Everything else in the file stays as it was.
+    pub fn set_binding(&mut self, action: &str, kb: &KeyBinding) {
+        let entry = KeybindEntry::new(kb, action);
+        let tables = self.doc
+            .entry("keybind")
+            .or_insert(Item::ArrayOfTables(ArrayOfTables::new()));
+        let Some(tables) = tables.as_array_of_tables_mut() else {
+            tracing::warn!("\"keybind\" is not an array of tables; not changing it");
+            return;
+        };
+        if let Some(table) = tables.iter_mut().find(|table| table.get("action").and_then(Item::as_str) == Some(action)) {
+            fill_table(table, &entry);
+            return;
+        }
+        let mut table = Table::new();
+        fill_table(&mut table, &entry);
+        tables.push(table);
+    }
+
+    /// Remove every entry for `action`, returning whether there was any. The comments above an
+    /// entry go with it, except for those heading the file, which a blank line separates from the
+    /// first entry.
+    pub fn remove_binding(&mut self, action: &str) -> bool {
+        let Some(tables) = self.doc.get_mut("keybind").and_then(Item::as_array_of_tables_mut) else {
+            return false;
+        };
+        let before = tables.len();
+        // A comment heading the file is part of the first entry's decor, so it must not go with it
+        let header = tables
+            .get(0)
+            .filter(|first| first.get("action").and_then(Item::as_str) == Some(action))
+            .and_then(file_header);
+        tables.retain(|table| table.get("action").and_then(Item::as_str) != Some(action));
+        let removed = tables.len() != before;
+        if let Some(header) = header {
+            match tables.get_mut(0) {
+                Some(first) => {
+                    let prefix = first.decor().prefix().and_then(|p| p.as_str()).unwrap_or("");
+                    let prefix = format!("{}{}", header, prefix.trim_start_matches(['\r', '\n']));
+                    first.decor_mut().set_prefix(prefix);
+                },
+                None => {
+                    let trailing = format!("{}{}", header, self.doc.trailing().as_str().unwrap_or(""));
+                    self.doc.set_trailing(trailing);
+                },
+            }
+        }
+        removed
+    }
+}
+
+/// The global keymap written out by [`export_keybinds`].
+#[derive(Clone, Debug)]
+pub struct KeybindExport {
+    /// The bindings which have an action name, as a file [`KeybindDocument::parse`] reads.
+    pub toml: String,
+    /// The bindings which could not be written, since they were added in code without an action
+    /// name (see [`set_action_name`][crate::keybinds::set_action_name]).
+    pub unexportable: Vec<KeyBinding>,
+}
+
+/// Write the global keymap in the TOML format of [`KeybindDocument`], e.g. so the user can edit
+/// it. Only bindings with an action name can be written; the others are listed in
+/// [`unexportable`][KeybindExport::unexportable]. Bindings are ordered by action name, so the
+/// output is the same from one run to the next. [Mode keymaps][crate::keybinds::load_mode_keymap]
+/// are not included.
+pub async fn export_keybinds() -> KeybindExport {
+    let mut named = Vec::new();
+    let mut unexportable = Vec::new();
+    for (kb, name) in named_keybinds().await {
+        match name {
+            Some(name) => named.push((name, kb)),
+            None => unexportable.push(kb),
+        }
+    }
+    // KeyBinding has no order of its own, so bindings of the same action are ordered by their
+    // debug form
+    named.sort_by_cached_key(|(name, kb)| (name.clone(), format!("{:?}", kb)));
+    let mut doc = KeybindDocument::default();
+    for (name, kb) in &named {
+        doc.push_binding(name, kb);
+    }
+    KeybindExport { toml: doc.to_string(), unexportable }
+}
+
+/// Write the global keymap to `path` with [`export_keybinds`], returning the bindings which could
+/// not be written.
+pub async fn save_keymap(path: impl AsRef<Path>) -> io::Result<Vec<KeyBinding>> {
+    let export = export_keybinds().await;
+    std::fs::write(path, export.toml)?;
+    Ok(export.unexportable)
+}
+
+impl std::fmt::Display for KeybindDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.doc.fmt(f)
+    }
+}
+
+/* the comments before a table up to the last blank line, which head the file rather than the table */
+fn file_header(table: &Table) -> Option<String> {
+    let prefix = table.decor().prefix()?.as_str()?;
+    let end = prefix.rfind("\n\n")? + 2;
+    prefix[..end].contains('#').then(|| prefix[..end].to_owned())
+}
+
+/* on error, returns the name of the offending field */
+fn table_to_entry(table: &Table) -> Result<KeybindEntry, &'static str> {
+    let string = |field: &'static str| table.get(field).and_then(Item::as_str).map(String::from);
+    let boolean = |field: &'static str| table.get(field).and_then(Item::as_bool).ok_or(field);
+    let mods = match table.get("mods") {
+        None => Vec::new(),
+        Some(item) => item
+            .as_array()
+            .and_then(|mods| mods.iter().map(|m| m.as_str().map(String::from)).collect::<Option<Vec<_>>>())
+            .ok_or("mods")?,
+    };
+    let repeat = table
+        .get("repeat")
+        .and_then(Item::as_integer)
+        .and_then(|repeat| u8::try_from(repeat).ok())
+        .ok_or("repeat")?;
+    if table.contains_key("key") && string("key").is_none() {
+        return Err("key");
+    }
+    if table.contains_key("mode") && string("mode").is_none() {
+        return Err("mode");
+    }
+    Ok(KeybindEntry {
+        action: string("action").ok_or("action")?,
+        key: string("key"),
+        mods,
+        repeat,
+        consume: boolean("consume")?,
+        notify: boolean("notify")?,
+        mode: string("mode"),
+    })
+}
+
+/* only touches the fields' values, so the keys keep their comments and formatting */
+fn fill_table(table: &mut Table, entry: &KeybindEntry) {
+    table["action"] = value(entry.action.as_str());
+    match &entry.key {
+        Some(key) => table["key"] = value(key.as_str()),
+        None => {
+            table.remove("key");
+        },
+    }
+    table["mods"] = value(entry.mods.iter().map(String::as_str).collect::<Array>());
+    table["repeat"] = value(i64::from(entry.repeat));
+    table["consume"] = value(entry.consume);
+    table["notify"] = value(entry.notify);
+    match &entry.mode {
+        Some(mode) => table["mode"] = value(mode.as_str()),
+        None => {
+            table.remove("mode");
+        },
+    }
+}
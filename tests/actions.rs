@@ -0,0 +1,34 @@
+mod common;
+
+use common::{block_on, chord, notification};
+use odilia_input::{
+    keybinds::{add_keybinds, run_keybind_func},
+    persist::{load_keybinds, ActionRegistry},
+};
+use rdev::Key as RDevKey;
+
+use std::sync::mpsc;
+
+const CONFIG: &str = r#"[
+    { "action": "read_line", "key": "l", "mods": ["ODILIA"], "repeat": 1, "consume": true, "notify": true }
+]"#;
+
+#[test]
+fn a_configured_action_name_fires_the_registered_handler() {
+    let _input = common::setup();
+    let (tx, ran) = mpsc::channel();
+    let mut actions = ActionRegistry::new();
+    actions.register_action("read_line", move || {
+        let _ = tx.send("read_line");
+        async {}
+    });
+    actions.register_action("read_word", || async {});
+
+    let keybinds = load_keybinds(CONFIG, &actions).unwrap();
+    assert!(block_on(add_keybinds(keybinds)).is_empty());
+
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyL]), [false, false]);
+    let notified = notification().expect("the binding was not notified");
+    block_on(run_keybind_func(&notified));
+    assert_eq!(ran.try_recv(), Ok("read_line"));
+}
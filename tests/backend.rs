@@ -0,0 +1,90 @@
+//! A backend of the test's own, driving the keybinding pipeline through the [`InputBackend`]
+//! trait like an OS backend would.
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::{
+    backend::{BackendError, EventHandler, InputBackend, ShutdownSignal},
+    events::{backend_ready, backend_stopped, create_keybind_channel_with_backend, shutdown_input, InputConfig},
+    keybinds::add_keybind,
+};
+use rdev::{
+    Event,
+    EventType::{self, KeyPress, KeyRelease},
+    Key as RDevKey,
+};
+
+use std::{
+    sync::mpsc,
+    time::{Duration, SystemTime},
+};
+
+/// Feeds the events sent to it through the pipeline, reporting what became of each, until the
+/// sender is dropped.
+struct ChannelBackend {
+    events: mpsc::Receiver<EventType>,
+    results: mpsc::Sender<bool>,
+}
+
+impl InputBackend for ChannelBackend {
+    fn run(self: Box<Self>, handler: EventHandler, _shutdown: ShutdownSignal) -> Result<(), BackendError> {
+        for event_type in self.events {
+            let passed = handler(Event {
+                time: SystemTime::now(),
+                name: None,
+                event_type,
+            });
+            let _ = self.results.send(passed.is_some());
+        }
+        Err("out of events".into())
+    }
+}
+
+#[test]
+fn a_backend_drives_the_pipeline_until_it_stops() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_time()
+        .build()
+        .unwrap();
+    let (events, backend_events) = mpsc::channel();
+    let (backend_results, results) = mpsc::channel();
+    let backend = ChannelBackend {
+        events: backend_events,
+        results: backend_results,
+    };
+    let mut rx = {
+        let _entered = runtime.enter();
+        create_keybind_channel_with_backend(InputConfig::default(), Box::new(backend)).unwrap()
+    };
+    runtime
+        .block_on(async { tokio::time::timeout(Duration::from_secs(5), backend_ready()).await })
+        .expect("the backend never became ready");
+    let kb = KeyBinding {
+        key: Some(Key::Other('b')),
+        mods: Modifiers::ODILIA,
+        repeat: 1,
+        consume: true,
+        notify: true,
+        mode: None,
+    };
+    runtime.block_on(add_keybind(kb.clone(), || async {}));
+    let feed = |event_type| {
+        events.send(event_type).unwrap();
+        results.recv_timeout(Duration::from_secs(5)).unwrap()
+    };
+
+    assert!(!feed(KeyPress(RDevKey::CapsLock)));
+    assert!(!feed(KeyPress(RDevKey::KeyB)));
+    feed(KeyRelease(RDevKey::KeyB));
+    assert!(feed(KeyPress(RDevKey::KeyC)));
+    feed(KeyRelease(RDevKey::KeyC));
+    assert_eq!(rx.try_recv().ok(), Some(kb));
+
+    // once shut down, the pipeline passes everything through
+    shutdown_input();
+    assert!(feed(KeyPress(RDevKey::KeyB)));
+    assert!(rx.try_recv().is_err());
+
+    drop(events);
+    let stopped = runtime.block_on(backend_stopped()).expect("the backend was started");
+    assert_eq!(stopped.unwrap_err().to_string(), "out of events");
+}
@@ -0,0 +1,36 @@
+//! A backend failing to start, the way grabbing input does without the permissions for it.
+use odilia_input::{
+    backend::{BackendError, EventHandler, InputBackend, ShutdownSignal},
+    events::{backend_stopped, create_keybind_channel_with_backend, InputConfig},
+};
+
+use std::time::Duration;
+
+struct FailingBackend;
+
+impl InputBackend for FailingBackend {
+    fn run(self: Box<Self>, _handler: EventHandler, _shutdown: ShutdownSignal) -> Result<(), BackendError> {
+        Err("permission denied".into())
+    }
+}
+
+#[test]
+fn a_backend_failing_to_start_is_reported_to_the_host() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_time()
+        .build()
+        .unwrap();
+    let _rx = {
+        let _entered = runtime.enter();
+        create_keybind_channel_with_backend(InputConfig::default(), Box::new(FailingBackend)).unwrap()
+    };
+
+    let stopped = runtime
+        .block_on(async { tokio::time::timeout(Duration::from_secs(5), backend_stopped()).await })
+        .expect("the backend never stopped")
+        .expect("the backend was started");
+    assert_eq!(stopped.unwrap_err().to_string(), "permission denied");
+    // the result is handed out once
+    assert!(runtime.block_on(backend_stopped()).is_none());
+}
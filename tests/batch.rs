@@ -0,0 +1,64 @@
+mod common;
+
+use common::{binding, block_on};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::keybinds::{async_fn, AsyncFn, InputManager};
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+// a handler recording `name` in `ran`
+fn recording(ran: &Arc<Mutex<Vec<&'static str>>>, name: &'static str) -> AsyncFn {
+    let ran = Arc::clone(ran);
+    async_fn(move || {
+        ran.lock().unwrap().push(name);
+        async {}
+    })
+}
+
+#[test]
+fn a_batch_adds_everything_but_its_conflicts() {
+    let _input = common::setup();
+    let manager = InputManager::new();
+    let ran = Arc::new(Mutex::new(Vec::new()));
+    let taken = binding(Key::Other('a'), Modifiers::ODILIA);
+    let free = binding(Key::Other('b'), Modifiers::ODILIA);
+    block_on(manager.add_keybind(taken.clone(), {
+        let ran = Arc::clone(&ran);
+        move || {
+            ran.lock().unwrap().push("original");
+            async {}
+        }
+    }));
+
+    let batch = HashMap::from([
+        (taken.clone(), recording(&ran, "replacement")),
+        (free.clone(), recording(&ran, "free")),
+    ]);
+    assert_eq!(block_on(manager.add_keybinds(batch)), std::slice::from_ref(&taken));
+
+    // the conflicting binding kept its handler, the other one was added
+    block_on(manager.trigger(&taken)).unwrap();
+    block_on(manager.trigger(&free)).unwrap();
+    assert_eq!(*ran.lock().unwrap(), ["original", "free"]);
+}
+
+#[test]
+fn a_batch_without_conflicts_reports_none() {
+    let _input = common::setup();
+    let manager = InputManager::new();
+    let ran = Arc::new(Mutex::new(Vec::new()));
+    let kbs = [
+        binding(Key::Other('c'), Modifiers::ODILIA),
+        binding(Key::Other('d'), Modifiers::ODILIA),
+    ];
+
+    let batch = kbs.iter().map(|kb| (kb.clone(), recording(&ran, "added"))).collect();
+    assert!(block_on(manager.add_keybinds(batch)).is_empty());
+    for kb in &kbs {
+        block_on(manager.trigger(kb)).unwrap();
+    }
+    assert_eq!(ran.lock().unwrap().len(), kbs.len());
+}
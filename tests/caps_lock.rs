@@ -0,0 +1,53 @@
+mod common;
+
+use common::push_at;
+use odilia_common::input::Modifiers;
+use odilia_input::events::{currently_pressed_modifiers, set_caps_lock_policy, CapsLockPolicy};
+use rdev::{
+    EventType::{KeyPress, KeyRelease},
+    Key as RDevKey,
+};
+
+use std::time::{Duration, SystemTime};
+
+/// Tap CapsLock `after` `start`, returning whether the press and the release were passed through.
+fn tap_at(start: SystemTime, after: Duration) -> (bool, bool) {
+    let time = start + after;
+    let pressed = push_at(KeyPress(RDevKey::CapsLock), time).is_some();
+    let released = push_at(KeyRelease(RDevKey::CapsLock), time + Duration::from_millis(50)).is_some();
+    (pressed, released)
+}
+
+#[test]
+fn by_default_the_odilia_key_never_passes_through() {
+    let _input = common::setup();
+    let start = SystemTime::now();
+
+    assert_eq!(tap_at(start, Duration::ZERO), (false, false));
+    assert_eq!(tap_at(start, Duration::from_millis(100)), (false, false));
+}
+
+#[test]
+fn a_quick_double_tap_passes_the_second_tap_through() {
+    let _input = common::setup();
+    set_caps_lock_policy(CapsLockPolicy::DoubleTapToggles);
+    let start = SystemTime::now();
+
+    assert_eq!(tap_at(start, Duration::ZERO), (false, false));
+    assert_eq!(tap_at(start, Duration::from_millis(100)), (true, true));
+    // the passed through tap doesn't start another double tap
+    assert_eq!(tap_at(start, Duration::from_millis(200)), (false, false));
+    // too slow for a double tap
+    assert_eq!(tap_at(start, Duration::from_secs(2)), (false, false));
+}
+
+#[test]
+fn with_pass_through_every_tap_passes_but_still_holds_the_modifier() {
+    let _input = common::setup();
+    set_caps_lock_policy(CapsLockPolicy::PassThrough);
+    let start = SystemTime::now();
+
+    assert!(push_at(KeyPress(RDevKey::CapsLock), start).is_some());
+    assert_eq!(currently_pressed_modifiers(), Modifiers::ODILIA);
+    assert!(push_at(KeyRelease(RDevKey::CapsLock), start).is_some());
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{binding, block_on, notification, poll, press, release, start};
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::keybinds::{add_keybind, capture_next_keybinding, remove_keybind};
+use rdev::Key as RDevKey;
+
+fn captured(key: char, mods: Modifiers) -> KeyBinding {
+    KeyBinding {
+        key: Some(Key::Other(key)),
+        mods,
+        repeat: 1,
+        consume: true,
+        notify: false,
+        mode: None,
+    }
+}
+
+#[test]
+fn pending_capture_swallows_the_next_key_combination() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('k'), Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    let capture = start(capture_next_keybinding());
+    press(RDevKey::CapsLock);
+    assert!(!press(RDevKey::KeyK), "the captured press must be consumed");
+    release(RDevKey::KeyK);
+    release(RDevKey::CapsLock);
+    assert_eq!(block_on(capture), captured('k', Modifiers::ODILIA));
+    assert_eq!(notification(), None, "the captured press must not fire the binding");
+
+    // Once resolved, the binding fires again as usual
+    press(RDevKey::CapsLock);
+    assert!(!press(RDevKey::KeyK));
+    release(RDevKey::KeyK);
+    release(RDevKey::CapsLock);
+    assert_eq!(notification(), Some(kb.clone()));
+    block_on(remove_keybind(kb));
+}
+
+#[test]
+fn capture_waits_for_a_key_besides_modifiers() {
+    let _input = common::setup();
+    let capture = start(capture_next_keybinding());
+    press(RDevKey::ControlLeft);
+    press(RDevKey::ShiftLeft);
+    assert!(!press(RDevKey::KeyA));
+    release(RDevKey::KeyA);
+    release(RDevKey::ShiftLeft);
+    release(RDevKey::ControlLeft);
+    assert_eq!(block_on(capture), captured('a', Modifiers::CONTROL_L | Modifiers::SHIFT_L));
+}
+
+#[test]
+fn only_one_capture_is_pending_at_a_time() {
+    let _input = common::setup();
+    let first = start(capture_next_keybinding());
+    let mut second = start(capture_next_keybinding());
+
+    assert!(!press(RDevKey::KeyA));
+    release(RDevKey::KeyA);
+    assert!(poll(&mut second).is_pending(), "the second capture must wait for the first");
+    assert_eq!(block_on(first), captured('a', Modifiers::empty()));
+
+    // The second capture only takes over now, so it gets the next key
+    assert!(poll(&mut second).is_pending());
+    assert!(!press(RDevKey::KeyB));
+    release(RDevKey::KeyB);
+    assert_eq!(block_on(second), captured('b', Modifiers::empty()));
+}
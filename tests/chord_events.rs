@@ -0,0 +1,32 @@
+mod common;
+
+use common::{press, release};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::events::{enable_chord_events, ChordEvent};
+use rdev::Key as RDevKey;
+use tokio::sync::mpsc;
+
+/// Every chord event sent so far.
+fn received(rx: &mut mpsc::Receiver<ChordEvent>) -> Vec<ChordEvent> {
+    std::iter::from_fn(|| rx.try_recv().ok()).collect()
+}
+
+#[test]
+fn an_a_n_chord_delivers_both_letters() {
+    let _input = common::setup();
+    let (tx, mut rx) = mpsc::channel(8);
+    enable_chord_events(tx);
+
+    press(RDevKey::CapsLock);
+    press(RDevKey::KeyA);
+    press(RDevKey::KeyN);
+    release(RDevKey::KeyN);
+    release(RDevKey::KeyA);
+    release(RDevKey::CapsLock);
+
+    let events = received(&mut rx);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].held, [Key::Other('a')]);
+    assert_eq!(events[1].held, [Key::Other('a'), Key::Other('n')]);
+    assert_eq!(events[1].event.mods, Modifiers::ODILIA);
+}
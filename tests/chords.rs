@@ -0,0 +1,71 @@
+mod common;
+
+use common::{binding, block_on, key_event, notification, press, push_at, release, unhandled_events};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::{
+    events::{currently_pressed, set_chord_timeout},
+    keybinds::add_keybind,
+};
+use rdev::{
+    EventType::{KeyPress, KeyRelease},
+    Key as RDevKey,
+};
+
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn auto_repeat_of_an_unbound_key_is_not_a_new_press() {
+    let _input = common::setup();
+    let unhandled = unhandled_events();
+
+    assert!(press(RDevKey::KeyQ));
+    // the OS repeats a held key as further presses
+    assert!(press(RDevKey::KeyQ));
+    assert!(press(RDevKey::KeyQ));
+    assert_eq!(currently_pressed(), [Key::Other('q')]);
+    assert!(release(RDevKey::KeyQ));
+    assert_eq!(currently_pressed(), []);
+
+    let kbm = unhandled.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(kbm, key_event(Key::Other('q'), Modifiers::NONE));
+    assert!(unhandled.recv_timeout(Duration::from_millis(100)).is_err());
+}
+
+#[test]
+fn an_unmatched_key_does_not_join_the_next_chord() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('j'), Modifiers::NONE);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    assert!(press(RDevKey::KeyW));
+    // still held, but its chord ended when nothing matched it
+    assert!(!press(RDevKey::KeyJ));
+    assert_eq!(notification(), Some(kb));
+    assert_eq!(currently_pressed(), [Key::Other('w'), Key::Other('j')]);
+    release(RDevKey::KeyJ);
+    release(RDevKey::KeyW);
+}
+
+#[test]
+fn a_chord_times_out_when_the_user_hesitates() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('x'), Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+    set_chord_timeout(Some(Duration::from_millis(500)));
+
+    let start = SystemTime::now();
+    push_at(KeyPress(RDevKey::CapsLock), start);
+    let late = start + Duration::from_secs(1);
+    assert!(push_at(KeyPress(RDevKey::KeyX), late).is_some());
+    assert_eq!(notification(), None);
+    push_at(KeyRelease(RDevKey::KeyX), late);
+    push_at(KeyRelease(RDevKey::CapsLock), late);
+
+    // in time, the chord still comes together
+    let start = SystemTime::now();
+    push_at(KeyPress(RDevKey::CapsLock), start);
+    assert!(push_at(KeyPress(RDevKey::KeyX), start + Duration::from_millis(100)).is_none());
+    assert_eq!(notification(), Some(kb));
+    push_at(KeyRelease(RDevKey::KeyX), start);
+    push_at(KeyRelease(RDevKey::CapsLock), start);
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use common::{binding, block_on, chord, key_event, notifications};
+use odilia_common::input::{Key, KeyBinding, KeyEvent, Modifiers};
+use odilia_input::{
+    events::{always_consume_keys, classify, set_learn_mode, set_observe_only, EventAction},
+    keybinds::{add_keybind, run_keybind_func, set_keybind_throttle, would_consume, would_notify},
+};
+use rdev::Key as RDevKey;
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Press and release `keys`, returning what actually happened to the last press.
+fn dispatched(keys: &[RDevKey]) -> EventAction {
+    let consumed = !*chord(keys).last().unwrap();
+    let notified = !notifications().is_empty();
+    match (notified, consumed) {
+        (false, false) => EventAction::PassThrough,
+        (true, false) => EventAction::Notify,
+        (false, true) => EventAction::Consume,
+        (true, true) => EventAction::NotifyAndConsume,
+    }
+}
+
+/// Check that [`classify`] and the `would_*` queries agree with each other on `kbm`, and return
+/// their answer.
+fn predicted(kbm: &KeyEvent) -> EventAction {
+    let (action, _) = classify(kbm);
+    assert_eq!(would_consume(kbm), action.consumes());
+    assert_eq!(would_notify(kbm), action.notifies());
+    action
+}
+
+fn ctrl(key: char) -> KeyEvent {
+    key_event(Key::Other(key), Modifiers::CONTROL_L)
+}
+
+fn bind(key: char, consume: bool, notify: bool) -> KeyBinding {
+    let kb = KeyBinding {
+        consume,
+        notify,
+        ..binding(Key::Other(key), Modifiers::CONTROL_L)
+    };
+    block_on(add_keybind(kb.clone(), || async {}));
+    kb
+}
+
+#[test]
+fn matched_and_unmatched_events_are_classified_as_they_are_dispatched() {
+    let _input = common::setup();
+    let both = bind('a', true, true);
+    bind('b', true, false);
+    bind('c', false, true);
+    bind('d', false, false);
+
+    assert_eq!(classify(&ctrl('a')), (EventAction::NotifyAndConsume, Some(both)));
+    assert_eq!(classify(&ctrl('e')), (EventAction::PassThrough, None));
+    let cases = [
+        ('a', RDevKey::KeyA, EventAction::NotifyAndConsume),
+        ('b', RDevKey::KeyB, EventAction::Consume),
+        ('c', RDevKey::KeyC, EventAction::Notify),
+        ('d', RDevKey::KeyD, EventAction::PassThrough),
+        ('e', RDevKey::KeyE, EventAction::PassThrough),
+    ];
+    for (key, rdev_key, expected) in cases {
+        assert_eq!(predicted(&ctrl(key)), expected, "Control+{key}");
+        assert_eq!(dispatched(&[RDevKey::ControlLeft, rdev_key]), expected, "Control+{key}");
+    }
+}
+
+#[test]
+fn observe_only_notifies_without_consuming_or_running_handlers() {
+    let _input = common::setup();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let kb = binding(Key::Other('f'), Modifiers::CONTROL_L);
+    let counter = Arc::clone(&runs);
+    block_on(add_keybind(kb.clone(), move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async {}
+    }));
+    always_consume_keys(vec![Key::Other('f')]);
+
+    set_observe_only(true);
+    assert_eq!(predicted(&ctrl('f')), EventAction::Notify);
+    assert_eq!(dispatched(&[RDevKey::ControlLeft, RDevKey::KeyF]), EventAction::Notify);
+    block_on(run_keybind_func(&kb));
+    assert_eq!(runs.load(Ordering::SeqCst), 0);
+
+    set_observe_only(false);
+    assert_eq!(predicted(&ctrl('f')), EventAction::NotifyAndConsume);
+    block_on(run_keybind_func(&kb));
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn always_consumed_keys_are_classified_as_consumed() {
+    let _input = common::setup();
+    always_consume_keys(vec![Key::Other('g')]);
+
+    let listed = key_event(Key::Other('g'), Modifiers::NONE);
+    assert_eq!(predicted(&listed), EventAction::Consume);
+    assert_eq!(dispatched(&[RDevKey::KeyG]), EventAction::Consume);
+
+    let unlisted = key_event(Key::Other('h'), Modifiers::NONE);
+    assert_eq!(predicted(&unlisted), EventAction::PassThrough);
+    assert_eq!(dispatched(&[RDevKey::KeyH]), EventAction::PassThrough);
+}
+
+#[test]
+fn a_throttled_binding_is_classified_as_not_notifying() {
+    let _input = common::setup();
+    let kb = bind('i', true, true);
+    block_on(set_keybind_throttle(&kb, Some(Duration::from_secs(3600))));
+
+    assert_eq!(predicted(&ctrl('i')), EventAction::NotifyAndConsume);
+    assert_eq!(dispatched(&[RDevKey::ControlLeft, RDevKey::KeyI]), EventAction::NotifyAndConsume);
+    assert_eq!(predicted(&ctrl('i')), EventAction::Consume);
+    assert_eq!(dispatched(&[RDevKey::ControlLeft, RDevKey::KeyI]), EventAction::Consume);
+}
+
+#[test]
+fn learn_mode_notifies_and_passes_everything_through() {
+    let _input = common::setup();
+    bind('k', true, false);
+
+    set_learn_mode(true);
+    assert_eq!(predicted(&ctrl('k')), EventAction::Notify);
+    assert_eq!(dispatched(&[RDevKey::ControlLeft, RDevKey::KeyK]), EventAction::Notify);
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::{block_on, push_at};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::keybinds::{add_combo, Combo};
+use rdev::{
+    EventType::{KeyPress, KeyRelease},
+    Key as RDevKey,
+};
+
+use std::{
+    sync::mpsc,
+    time::{Duration, SystemTime},
+};
+
+/// Add a combo of `keys` pressed within 100ms, returning a receiver told each time it fires.
+fn bind_combo(keys: [char; 2]) -> mpsc::Receiver<()> {
+    let (tx, rx) = mpsc::channel();
+    let combo = Combo {
+        keys: keys.map(Key::Other).to_vec(),
+        mods: Modifiers::NONE,
+        window: Duration::from_millis(100),
+        consume: true,
+    };
+    block_on(add_combo(combo, move || {
+        let _ = tx.send(());
+        async {}
+    }));
+    rx
+}
+
+/// Press `first`, then `second` after `gap`, returning whether the second press was passed through.
+fn press_apart(first: RDevKey, second: RDevKey, gap: Duration) -> bool {
+    let start = SystemTime::now();
+    push_at(KeyPress(first), start);
+    let passed = push_at(KeyPress(second), start + gap).is_some();
+    push_at(KeyRelease(second), start + gap);
+    push_at(KeyRelease(first), start + gap);
+    passed
+}
+
+#[test]
+fn keys_pressed_together_fire_the_combo() {
+    let _input = common::setup();
+    let fired = bind_combo(['j', 'k']);
+
+    assert!(!press_apart(RDevKey::KeyJ, RDevKey::KeyK, Duration::from_millis(20)));
+    assert!(fired.recv_timeout(Duration::from_secs(1)).is_ok());
+}
+
+#[test]
+fn keys_pressed_far_apart_are_not_a_combo() {
+    let _input = common::setup();
+    let fired = bind_combo(['d', 'f']);
+
+    assert!(press_apart(RDevKey::KeyD, RDevKey::KeyF, Duration::from_millis(500)));
+    assert!(fired.recv_timeout(Duration::from_millis(100)).is_err());
+}
@@ -0,0 +1,233 @@
+//! Drives the keybinding pipeline by hand through [`MockBackend`], the way the input monitoring
+//! thread would.
+//!
+//! The input monitoring system can only be initialised once per process, and every test binary is
+//! a process of its own. Tests within one binary share it, so [`setup`] runs them one at a time and
+//! puts the pipeline back into its initial state first. Bindings added to the global manager are
+//! not part of that state and stay around, so each test binds keys of its own.
+#![allow(dead_code)]
+
+use odilia_common::input::{Key, KeyBinding, KeyEvent, Modifiers};
+use odilia_input::{
+    backend::MockBackend,
+    events::{self, AltGrPolicy, CapsLockPolicy, EventAction, InputConfig, InputState, RepeatPolicy},
+    keybinds::{self, InputManager},
+};
+use once_cell::sync::{Lazy, OnceCell};
+use rdev::{
+    Event,
+    EventType::{self, KeyPress, KeyRelease},
+    Key as RDevKey,
+};
+use tokio::{runtime::Runtime, sync::mpsc};
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{mpsc as std_mpsc, Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll, Waker},
+    time::{Duration, SystemTime},
+};
+
+struct Input {
+    runtime: Runtime,
+    rx: Mutex<mpsc::Receiver<KeyBinding>>,
+    /// The pipeline's state right after initialisation.
+    initial: InputState,
+}
+
+static INPUT: OnceCell<Input> = OnceCell::new();
+static SERIAL: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// Keeps other tests of the same binary from feeding events until dropped.
+pub struct Fixture {
+    _serial: MutexGuard<'static, ()>,
+}
+
+/// Initialise the input monitoring system with the default [`InputConfig`] if that didn't happen
+/// yet, and reset it for the next test.
+pub fn setup() -> Fixture {
+    setup_with(InputConfig::default)
+}
+
+/// Like [`setup`], initialising with the given [`InputConfig`]. Only the first call in a test
+/// binary gets to pick it.
+pub fn setup_with(config: impl FnOnce() -> InputConfig) -> Fixture {
+    let serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+    let input = INPUT.get_or_init(|| {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_time()
+            .build()
+            .unwrap();
+        let rx = {
+            let _entered = runtime.enter();
+            events::create_keybind_channel_with_backend(config(), Box::new(MockBackend)).unwrap()
+        };
+        let initial = events::snapshot_state();
+        Input {
+            runtime,
+            rx: Mutex::new(rx),
+            initial,
+        }
+    });
+    reset(input);
+    Fixture { _serial: serial }
+}
+
+/// Undo whatever an earlier test may have left behind, even if it panicked halfway.
+fn reset(input: &Input) {
+    events::set_learn_mode(false);
+    events::set_observe_only(false);
+    events::end_raw_capture();
+    events::disable_char_echo();
+    events::disable_chord_events();
+    events::clear_event_filters();
+    events::always_consume_keys(Vec::new());
+    events::set_chord_timeout(None);
+    events::set_max_chord_keys(None);
+    events::set_modifier_release_grace(None);
+    events::set_modifier_tap_timing(Duration::from_millis(400), None);
+    events::reset_repeat();
+    events::set_count_prefix_modes(Vec::new());
+    events::set_num_lock(false);
+    events::set_latency_tracking(false);
+    events::reset_latency_stats();
+    events::set_odilia_modifier_key(RDevKey::CapsLock);
+    events::set_caps_lock_policy(CapsLockPolicy::AlwaysOdilia);
+    events::set_alt_gr_policy(AltGrPolicy::Native);
+    events::set_repeat_policy(RepeatPolicy::EveryTap);
+    InputManager::global().activate();
+    input.runtime.block_on(keybinds::remove_unhandled_handler());
+    input.runtime.block_on(keybinds::set_mode_provider(None));
+    input.runtime.block_on(keybinds::set_outcome_observer(None));
+    input.runtime.block_on(keybinds::set_app_key_predicate(None));
+    let _ = events::restore_state(input.initial.clone());
+    events::drain(&mut input.rx.lock().unwrap_or_else(PoisonError::into_inner));
+}
+
+/// Run `fut` to completion on the input monitoring system's runtime.
+pub fn block_on<F: Future>(fut: F) -> F::Output {
+    INPUT.get().expect("call setup() first").runtime.block_on(fut)
+}
+
+/// Poll `fut` once and hand it back, so that e.g. a capture it starts is in place before events
+/// are fed in. Finish it with [`block_on`].
+pub fn start<F: Future>(fut: F) -> Pin<Box<F>> {
+    let mut fut = Box::pin(fut);
+    assert!(poll(&mut fut).is_pending(), "the future finished before any event came in");
+    fut
+}
+
+/// Poll `fut` once, without waiting.
+pub fn poll<F: Future>(fut: &mut Pin<Box<F>>) -> Poll<F::Output> {
+    fut.as_mut().poll(&mut Context::from_waker(Waker::noop()))
+}
+
+/// Feed an event through the pipeline at `time`, returning it if it was passed through.
+pub fn push_at(event_type: EventType, time: SystemTime) -> Option<Event> {
+    MockBackend.push_event(Event {
+        time,
+        name: None,
+        event_type,
+    })
+}
+
+/// Press `key` as the active layout types `name` with it, returning whether the press was passed
+/// through.
+pub fn press_named(key: RDevKey, name: &str) -> bool {
+    MockBackend
+        .push_event(Event {
+            time: SystemTime::now(),
+            name: Some(name.to_owned()),
+            event_type: KeyPress(key),
+        })
+        .is_some()
+}
+
+/// Feed an event through the pipeline, returning whether it was passed through.
+pub fn push(event_type: EventType) -> bool {
+    push_at(event_type, SystemTime::now()).is_some()
+}
+
+/// Press `key`, returning whether the press was passed through.
+pub fn press(key: RDevKey) -> bool {
+    push(KeyPress(key))
+}
+
+/// Release `key`, returning whether the release was passed through.
+pub fn release(key: RDevKey) -> bool {
+    push(KeyRelease(key))
+}
+
+/// Press `keys` in order, then release them in reverse, returning whether each press was passed
+/// through.
+pub fn chord(keys: &[RDevKey]) -> Vec<bool> {
+    let passed = keys.iter().map(|&k| press(k)).collect();
+    for &k in keys.iter().rev() {
+        release(k);
+    }
+    passed
+}
+
+/// The next notification already sent down the channel, if any.
+pub fn notification() -> Option<KeyBinding> {
+    let input = INPUT.get().expect("call setup() first");
+    input.rx.lock().unwrap_or_else(PoisonError::into_inner).try_recv().ok()
+}
+
+/// Wait up to `timeout` for the next notification, for those sent from a timer.
+pub fn notification_within(timeout: Duration) -> Option<KeyBinding> {
+    let input = INPUT.get().expect("call setup() first");
+    let mut rx = input.rx.lock().unwrap_or_else(PoisonError::into_inner);
+    input.runtime.block_on(async { tokio::time::timeout(timeout, rx.recv()).await.ok().flatten() })
+}
+
+/// Discard every notification already sent down the channel with [`events::drain`], returning how
+/// many there were.
+pub fn drain() -> usize {
+    let input = INPUT.get().expect("call setup() first");
+    events::drain(&mut input.rx.lock().unwrap_or_else(PoisonError::into_inner))
+}
+
+/// Take up to `max` notifications already sent down the channel with [`events::try_recv_batch`].
+pub fn notification_batch(max: usize) -> Vec<(KeyBinding, EventAction)> {
+    let input = INPUT.get().expect("call setup() first");
+    events::try_recv_batch(&mut input.rx.lock().unwrap_or_else(PoisonError::into_inner), max)
+}
+
+/// Every notification already sent down the channel.
+pub fn notifications() -> Vec<KeyBinding> {
+    std::iter::from_fn(notification).collect()
+}
+
+/// Set an unhandled handler reporting every key event it runs for.
+pub fn unhandled_events() -> std_mpsc::Receiver<KeyEvent> {
+    let (tx, rx) = std_mpsc::channel();
+    block_on(keybinds::set_unhandled_handler(Box::new(move |kbm| {
+        let _ = tx.send(kbm);
+        Box::new(Box::pin(async {}))
+    })));
+    rx
+}
+
+/// A binding for a single press of `key` with `mods`, consuming and notifying, in any mode.
+pub fn binding(key: Key, mods: Modifiers) -> KeyBinding {
+    KeyBinding {
+        key: Some(key),
+        mods,
+        repeat: 1,
+        consume: true,
+        notify: true,
+        mode: None,
+    }
+}
+
+/// The key event a press of `key` with `mods` translates to.
+pub fn key_event(key: Key, mods: Modifiers) -> KeyEvent {
+    KeyEvent {
+        key: Some(key),
+        mods,
+        repeat: 1,
+    }
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::{binding, block_on, chord};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::{
+    events::InputConfig,
+    keybinds::{add_keybind, run_keybind_func, set_unhandled_handler},
+};
+use rdev::Key as RDevKey;
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+const MAX_RUNNING: usize = 2;
+
+fn setup() -> common::Fixture {
+    common::setup_with(|| InputConfig {
+        max_running_handlers: Some(MAX_RUNNING),
+        ..InputConfig::default()
+    })
+}
+
+/// Counts the handlers running at once, and the most there ever were.
+#[derive(Clone, Default)]
+struct Running {
+    now: Arc<AtomicUsize>,
+    most: Arc<AtomicUsize>,
+    done: Arc<AtomicUsize>,
+}
+
+impl Running {
+    async fn handler(self) {
+        let now = self.now.fetch_add(1, Ordering::SeqCst) + 1;
+        self.most.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        self.now.fetch_sub(1, Ordering::SeqCst);
+        self.done.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn wait_for(&self, done: usize) {
+        while self.done.load(Ordering::SeqCst) < done {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+#[test]
+fn bound_handlers_run_at_most_the_limit_at_once() {
+    let _input = setup();
+    let kb = binding(Key::Other('l'), Modifiers::ODILIA);
+    let running = Running::default();
+    let handler = running.clone();
+    block_on(add_keybind(kb.clone(), move || handler.clone().handler()));
+
+    block_on(async {
+        let runs: Vec<_> = (0..8)
+            .map(|_| {
+                let kb = kb.clone();
+                tokio::spawn(async move { run_keybind_func(&kb).await })
+            })
+            .collect();
+        for run in runs {
+            run.await.unwrap();
+        }
+    });
+    assert_eq!(running.done.load(Ordering::SeqCst), 8);
+    assert_eq!(running.most.load(Ordering::SeqCst), MAX_RUNNING);
+}
+
+#[test]
+fn rapid_unhandled_keys_run_at_most_the_limit_at_once() {
+    let _input = setup();
+    let running = Running::default();
+    let handler = running.clone();
+    block_on(set_unhandled_handler(Box::new(move |_| Box::new(Box::pin(handler.clone().handler())))));
+
+    let keys = [RDevKey::KeyQ, RDevKey::KeyW, RDevKey::KeyE, RDevKey::KeyR, RDevKey::KeyT, RDevKey::KeyY];
+    for key in keys {
+        chord(&[key]);
+    }
+    running.wait_for(keys.len());
+    assert_eq!(running.most.load(Ordering::SeqCst), MAX_RUNNING);
+}
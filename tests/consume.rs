@@ -0,0 +1,89 @@
+mod common;
+
+use common::{binding, block_on, chord, key_event, notification, notifications, press, release};
+use odilia_common::input::{Key, KeyEvent, Modifiers};
+use odilia_input::{
+    events::{always_consume_keys, set_observe_only},
+    keybinds::{add_keybind, keyevent_match, set_app_key_predicate, set_consume_policy, ConsumePolicy},
+};
+use rdev::Key as RDevKey;
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+
+#[test]
+fn a_listed_key_is_consumed_without_any_binding() {
+    let _input = common::setup();
+    always_consume_keys(vec![Key::Insert]);
+    assert_eq!(block_on(keyevent_match(&key_event(Key::Insert, Modifiers::NONE))), None);
+
+    assert!(!press(RDevKey::Insert));
+    assert!(!release(RDevKey::Insert));
+    // with a modifier held too
+    assert!(press(RDevKey::ShiftLeft));
+    assert!(!press(RDevKey::Insert));
+    assert!(!release(RDevKey::Insert));
+    assert!(release(RDevKey::ShiftLeft));
+    assert_eq!(notifications(), []);
+}
+
+#[test]
+fn an_unlisted_unbound_key_passes_through() {
+    let _input = common::setup();
+    always_consume_keys(vec![Key::Insert]);
+
+    assert!(press(RDevKey::Pause));
+    assert!(release(RDevKey::Pause));
+    assert_eq!(notifications(), []);
+}
+
+#[test]
+fn observe_only_mode_lets_listed_keys_through() {
+    let _input = common::setup();
+    always_consume_keys(vec![Key::Insert]);
+    set_observe_only(true);
+
+    assert!(press(RDevKey::Insert));
+    assert!(release(RDevKey::Insert));
+}
+
+#[test]
+fn an_empty_list_consumes_nothing_extra() {
+    let _input = common::setup();
+    always_consume_keys(vec![Key::Insert]);
+    always_consume_keys(Vec::new());
+
+    assert!(press(RDevKey::Insert));
+    assert!(release(RDevKey::Insert));
+}
+
+#[test]
+fn a_binding_consuming_if_unhandled_yields_to_the_focused_application() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('s'), Modifiers::NONE);
+    block_on(add_keybind(kb.clone(), || async {}));
+    block_on(set_consume_policy(&kb, ConsumePolicy::IfUnhandled));
+
+    // without a predicate, it always consumes
+    assert_eq!(chord(&[RDevKey::KeyS]), [false]);
+    assert_eq!(notification(), Some(kb.clone()));
+
+    let in_text_field = Arc::new(AtomicBool::new(true));
+    let asked = Arc::new(Mutex::new(Vec::<KeyEvent>::new()));
+    let (focus, log) = (Arc::clone(&in_text_field), Arc::clone(&asked));
+    block_on(set_app_key_predicate(Some(Box::new(move |kbm| {
+        log.lock().unwrap().push(kbm.clone());
+        focus.load(Ordering::Relaxed)
+    }))));
+
+    // the application uses the key, so it gets it, though the binding still fires
+    assert_eq!(chord(&[RDevKey::KeyS]), [true]);
+    assert_eq!(notification(), Some(kb.clone()));
+    assert!(asked.lock().unwrap().contains(&key_event(Key::Other('s'), Modifiers::NONE)));
+
+    in_text_field.store(false, Ordering::Relaxed);
+    assert_eq!(chord(&[RDevKey::KeyS]), [false]);
+    assert_eq!(notification(), Some(kb));
+}
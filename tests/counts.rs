@@ -0,0 +1,103 @@
+mod common;
+
+use common::{binding, block_on, chord, notification, push_at};
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::{
+    events::set_count_prefix_modes,
+    keybinds::{add_counted_keybind, get_sr_mode_sync, run_keybind_func},
+};
+use rdev::{
+    EventType::{KeyPress, KeyRelease},
+    Key as RDevKey,
+};
+
+use std::{
+    sync::mpsc,
+    time::{Duration, SystemTime},
+};
+
+/// Bind `key` without modifiers to a handler reporting its count, with counts on in the current
+/// mode.
+fn counted(key: char) -> (KeyBinding, mpsc::Receiver<u32>) {
+    set_count_prefix_modes(vec![get_sr_mode_sync()]);
+    let kb = binding(Key::Other(key), Modifiers::NONE);
+    let (tx, counts) = mpsc::channel();
+    block_on(add_counted_keybind(kb.clone(), move |count| {
+        let _ = tx.send(count);
+        async {}
+    }));
+    (kb, counts)
+}
+
+/// Run the handler of the binding which was just notified, returning the count it got.
+fn fired_count(counts: &mpsc::Receiver<u32>) -> u32 {
+    let kb = notification().expect("the binding did not fire");
+    block_on(run_keybind_func(&kb));
+    counts.try_recv().unwrap()
+}
+
+#[test]
+fn digits_typed_before_a_binding_are_its_count() {
+    let _input = common::setup();
+    let (_, counts) = counted('j');
+
+    assert_eq!(chord(&[RDevKey::Num3]), [false]);
+    assert_eq!(chord(&[RDevKey::KeyJ]), [false]);
+    assert_eq!(fired_count(&counts), 3);
+
+    // several digits, from the keypad too
+    chord(&[RDevKey::Num1]);
+    chord(&[RDevKey::Kp2]);
+    chord(&[RDevKey::KeyJ]);
+    assert_eq!(fired_count(&counts), 12);
+
+    // the count is used up
+    chord(&[RDevKey::KeyJ]);
+    assert_eq!(fired_count(&counts), 1);
+}
+
+#[test]
+fn another_key_drops_the_count() {
+    let _input = common::setup();
+    let (_, counts) = counted('k');
+
+    chord(&[RDevKey::Num4]);
+    assert_eq!(chord(&[RDevKey::KeyQ]), [true]);
+    chord(&[RDevKey::KeyK]);
+    assert_eq!(fired_count(&counts), 1);
+}
+
+#[test]
+fn waiting_too_long_drops_the_count() {
+    let _input = common::setup();
+    let (_, counts) = counted('n');
+
+    let then = SystemTime::now() - Duration::from_secs(5);
+    push_at(KeyPress(RDevKey::Num5), then);
+    push_at(KeyRelease(RDevKey::Num5), then);
+    chord(&[RDevKey::KeyN]);
+    assert_eq!(fired_count(&counts), 1);
+}
+
+#[test]
+fn a_count_stops_growing_at_the_maximum() {
+    let _input = common::setup();
+    let (_, counts) = counted('p');
+
+    for _ in 0..12 {
+        chord(&[RDevKey::Num9]);
+    }
+    chord(&[RDevKey::KeyP]);
+    assert_eq!(fired_count(&counts), u32::MAX);
+}
+
+#[test]
+fn outside_the_count_modes_digits_pass_through() {
+    let _input = common::setup();
+    let (_, counts) = counted('y');
+    set_count_prefix_modes(Vec::new());
+
+    assert_eq!(chord(&[RDevKey::Num6]), [true]);
+    chord(&[RDevKey::KeyY]);
+    assert_eq!(fired_count(&counts), 1);
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use odilia_common::input::Modifiers;
+use odilia_input::events::{modifiers_to_display_string, modifiers_to_rdev_keys, set_odilia_modifier_key};
+use rdev::Key as RDevKey;
+
+#[test]
+fn combined_modifiers_list_their_keys_odilia_first() {
+    let _input = common::setup();
+    let mods = Modifiers::SHIFT_L | Modifiers::CONTROL_L | Modifiers::ODILIA;
+    assert_eq!(modifiers_to_rdev_keys(mods), [RDevKey::CapsLock, RDevKey::ControlLeft, RDevKey::ShiftLeft]);
+    assert_eq!(modifiers_to_display_string(mods), "Caps Lock+Ctrl+Shift");
+}
+
+#[test]
+fn right_hand_modifiers_are_called_out() {
+    let _input = common::setup();
+    let mods = Modifiers::CONTROL_R | Modifiers::ALT_R | Modifiers::SHIFT_R | Modifiers::META_R;
+    assert_eq!(
+        modifiers_to_rdev_keys(mods),
+        [RDevKey::ControlRight, RDevKey::AltGr, RDevKey::ShiftRight, RDevKey::MetaRight]
+    );
+    assert_eq!(modifiers_to_display_string(mods), "Right Ctrl+AltGr+Right Shift+Right Super");
+    assert_eq!(modifiers_to_display_string(Modifiers::ALT_L | Modifiers::META_L), "Alt+Super");
+}
+
+#[test]
+fn the_odilia_modifier_shows_the_configured_key() {
+    let _input = common::setup();
+    set_odilia_modifier_key(RDevKey::Insert);
+    assert_eq!(modifiers_to_rdev_keys(Modifiers::ODILIA | Modifiers::ALT_L), [RDevKey::Insert, RDevKey::Alt]);
+    assert_eq!(modifiers_to_display_string(Modifiers::ODILIA | Modifiers::ALT_L), "Insert+Alt");
+}
+
+#[test]
+fn no_modifiers_show_as_nothing() {
+    let _input = common::setup();
+    assert_eq!(modifiers_to_rdev_keys(Modifiers::NONE), []);
+    assert_eq!(modifiers_to_display_string(Modifiers::NONE), "");
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::{press, release};
+use odilia_input::events::enable_char_echo;
+use rdev::Key as RDevKey;
+use tokio::sync::mpsc;
+
+fn echoed(rx: &mut mpsc::Receiver<char>) -> String {
+    std::iter::from_fn(|| rx.try_recv().ok()).collect()
+}
+
+#[test]
+fn typed_characters_are_echoed_with_shift_applied() {
+    let _input = common::setup();
+    let (tx, mut rx) = mpsc::channel(8);
+    enable_char_echo(tx);
+
+    // nothing is consumed
+    assert!(press(RDevKey::ShiftLeft));
+    assert!(press(RDevKey::KeyH));
+    release(RDevKey::KeyH);
+    release(RDevKey::ShiftLeft);
+    assert!(press(RDevKey::KeyI));
+    release(RDevKey::KeyI);
+    assert_eq!(echoed(&mut rx), "Hi");
+}
+
+#[test]
+fn keys_which_type_nothing_are_skipped() {
+    let _input = common::setup();
+    let (tx, mut rx) = mpsc::channel(8);
+    enable_char_echo(tx);
+
+    for key in [RDevKey::LeftArrow, RDevKey::F5, RDevKey::KeyA] {
+        assert!(press(key));
+        release(key);
+    }
+    // nor is anything typed with Control held
+    press(RDevKey::ControlLeft);
+    press(RDevKey::KeyC);
+    release(RDevKey::KeyC);
+    release(RDevKey::ControlLeft);
+    assert_eq!(echoed(&mut rx), "a");
+}
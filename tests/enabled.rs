@@ -0,0 +1,43 @@
+mod common;
+
+use common::{binding, block_on, chord, key_event, notification, press, release, unhandled_events};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::{
+    events::{classify, EventAction},
+    keybinds::{add_keybind, is_keybind_enabled, set_keybind_enabled},
+};
+use rdev::Key as RDevKey;
+
+use std::time::Duration;
+
+#[test]
+fn a_disabled_binding_does_not_fire_until_enabled_again() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('d'), Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    block_on(set_keybind_enabled(&kb, false));
+    assert!(!block_on(is_keybind_enabled(&kb)));
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyD]), [false, true]);
+    assert_eq!(notification(), None);
+
+    block_on(set_keybind_enabled(&kb, true));
+    assert!(block_on(is_keybind_enabled(&kb)));
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyD]), [false, false]);
+    assert_eq!(notification(), Some(kb));
+}
+
+#[test]
+fn a_disabled_consuming_binding_passes_its_key_on_as_if_unbound() {
+    let _input = common::setup();
+    let kb = binding(Key::F7, Modifiers::NONE);
+    block_on(add_keybind(kb.clone(), || async {}));
+    block_on(set_keybind_enabled(&kb, false));
+    let unhandled = unhandled_events();
+
+    assert_eq!(classify(&key_event(Key::F7, Modifiers::NONE)), (EventAction::PassThrough, None));
+    assert!(press(RDevKey::F7));
+    assert!(release(RDevKey::F7));
+    assert_eq!(notification(), None);
+    assert_eq!(unhandled.recv_timeout(Duration::from_secs(5)), Ok(key_event(Key::F7, Modifiers::NONE)));
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::{binding, block_on, key_event};
+use odilia_common::{
+    input::{Key, KeyBinding, Modifiers},
+    modes::ScreenReaderMode,
+};
+use odilia_input::keybinds::{add_keybind, explain_match, set_keybind_enabled, MatchFailure};
+
+fn failures(kb: &KeyBinding, key: char, mods: Modifiers) -> Vec<MatchFailure> {
+    let explanations = block_on(explain_match(&key_event(Key::Other(key), mods)));
+    explanations.into_iter().find(|(explained, _)| explained == kb).expect("the binding is explained").1
+}
+
+#[test]
+fn a_near_miss_is_explained() {
+    let _input = common::setup();
+    let kb = KeyBinding {
+        repeat: 2,
+        mode: Some(ScreenReaderMode::new("Explained")),
+        ..binding(Key::Other('g'), Modifiers::ODILIA | Modifiers::CONTROL_L)
+    };
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    assert_eq!(
+        failures(&kb, 'g', Modifiers::ODILIA),
+        [MatchFailure::Modifiers, MatchFailure::Mode, MatchFailure::Repeat]
+    );
+    assert_eq!(
+        failures(&kb, 'h', Modifiers::ODILIA | Modifiers::CONTROL_L),
+        [MatchFailure::Key, MatchFailure::Mode, MatchFailure::Repeat]
+    );
+}
+
+#[test]
+fn a_match_has_no_failures_unless_disabled() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('m'), Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    assert_eq!(failures(&kb, 'm', Modifiers::ODILIA), []);
+    block_on(set_keybind_enabled(&kb, false));
+    assert_eq!(failures(&kb, 'm', Modifiers::ODILIA), [MatchFailure::Disabled]);
+    block_on(set_keybind_enabled(&kb, true));
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::{binding, block_on};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::{
+    keybinds::add_keybind,
+    persist::{export_keybinds, ActionRegistry, KeybindDocument, KeybindEntry},
+};
+
+const CONFIG: &str = r#"# My bindings
+
+[[keybind]]
+action = "read_line"
+key = "l"
+mods = ["ODILIA"]
+repeat = 1
+consume = true
+notify = true
+
+[[keybind]]
+action = "next_heading"
+key = "h"
+mods = []
+repeat = 1
+consume = true
+notify = false
+mode = "Browse"
+
+[[keybind]]
+action = "read_line"
+key = "Up"
+mods = ["ODILIA", "SHIFT_L"]
+repeat = 2
+consume = false
+notify = true
+"#;
+
+/// The entries of `toml`, in a fixed order.
+fn sorted_entries(toml: &str) -> Vec<KeybindEntry> {
+    let mut entries = KeybindDocument::parse(toml).unwrap().entries().unwrap();
+    entries.sort_by_cached_key(|entry| format!("{:?}", entry));
+    entries
+}
+
+#[test]
+fn a_loaded_config_round_trips_through_export() {
+    let _input = common::setup();
+    let mut actions = ActionRegistry::new();
+    actions.register_action("read_line", || async {});
+    actions.register_action("next_heading", || async {});
+    let doc = KeybindDocument::parse(CONFIG).unwrap();
+    assert_eq!(block_on(doc.install(&actions)).unwrap(), []);
+
+    // bound in code, without an action name
+    let unnamed = binding(Key::F9, Modifiers::ODILIA);
+    block_on(add_keybind(unnamed.clone(), || async {}));
+
+    let export = block_on(export_keybinds());
+    assert_eq!(sorted_entries(&export.toml), sorted_entries(CONFIG));
+    assert_eq!(export.unexportable, [unnamed]);
+    // exporting again gives the same file
+    assert_eq!(block_on(export_keybinds()).toml, export.toml);
+}
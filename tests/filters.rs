@@ -0,0 +1,44 @@
+mod common;
+
+use common::{binding, block_on, chord, notification, unhandled_events};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::{
+    events::{add_event_filter, FilterDecision},
+    keybinds::add_keybind,
+};
+use rdev::{
+    EventType::{KeyPress, KeyRelease},
+    Key as RDevKey,
+};
+
+use std::time::Duration;
+
+#[test]
+fn a_filter_can_drop_a_key() {
+    let _input = common::setup();
+    let unhandled = unhandled_events();
+    add_event_filter(Box::new(|ev| match ev.event_type {
+        KeyPress(RDevKey::KeyD) | KeyRelease(RDevKey::KeyD) => FilterDecision::Drop,
+        _ => FilterDecision::Keep,
+    }));
+
+    assert_eq!(chord(&[RDevKey::KeyD]), [false]);
+    assert!(unhandled.recv_timeout(Duration::from_millis(100)).is_err());
+}
+
+#[test]
+fn a_filter_can_remap_a_key() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('b'), Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+    add_event_filter(Box::new(|ev| {
+        match &mut ev.event_type {
+            KeyPress(key) | KeyRelease(key) if *key == RDevKey::KeyA => *key = RDevKey::KeyB,
+            _ => {},
+        }
+        FilterDecision::Keep
+    }));
+
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyA]), [false, false]);
+    assert_eq!(notification(), Some(kb));
+}
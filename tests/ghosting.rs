@@ -0,0 +1,67 @@
+mod common;
+
+use common::{binding, block_on, notification, press, release};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::{
+    events::{set_max_chord_keys, translate_event},
+    keybinds::add_keybind,
+};
+use rdev::{Event, EventType::KeyPress, Key as RDevKey};
+
+use std::time::SystemTime;
+
+fn translated_key(key: RDevKey, held: &[RDevKey]) -> Option<Key> {
+    let ev = Event {
+        time: SystemTime::now(),
+        name: None,
+        event_type: KeyPress(key),
+    };
+    translate_event(&ev, held)?.key
+}
+
+/// Press `held` and then `key` with the Odilia key down, returning whether `key` was passed
+/// through, and let go of everything again.
+fn odilia_chord(held: &[RDevKey], key: RDevKey) -> bool {
+    press(RDevKey::CapsLock);
+    for &k in held {
+        press(k);
+    }
+    let passed = press(key);
+    for &k in held.iter().chain([&key]).rev() {
+        release(k);
+    }
+    release(RDevKey::CapsLock);
+    passed
+}
+
+#[test]
+fn chords_over_the_limit_translate_to_no_key() {
+    let _input = common::setup();
+    set_max_chord_keys(Some(3));
+
+    let plausible = [RDevKey::CapsLock, RDevKey::KeyA, RDevKey::KeyS, RDevKey::KeyF];
+    assert_eq!(translated_key(RDevKey::KeyF, &plausible), Some(Key::Other('a')));
+    let ghost = [RDevKey::KeyA, RDevKey::KeyS, RDevKey::KeyD, RDevKey::KeyG, RDevKey::KeyH, RDevKey::KeyF];
+    assert_eq!(translated_key(RDevKey::KeyF, &ghost), None);
+}
+
+#[test]
+fn a_ghost_chord_fires_no_binding() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('f'), Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+    set_max_chord_keys(Some(3));
+
+    // three keys at once are fine
+    assert!(!odilia_chord(&[RDevKey::KeyA, RDevKey::KeyS], RDevKey::KeyF));
+    assert_eq!(notification(), Some(kb.clone()));
+
+    // six are a keyboard reporting phantom keys
+    let ghosts = [RDevKey::KeyA, RDevKey::KeyS, RDevKey::KeyD, RDevKey::KeyG, RDevKey::KeyH];
+    assert!(odilia_chord(&ghosts, RDevKey::KeyF));
+    assert_eq!(notification(), None);
+
+    set_max_chord_keys(None);
+    assert!(!odilia_chord(&ghosts, RDevKey::KeyF));
+    assert_eq!(notification(), Some(kb));
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::{binding, block_on, notification, push_at};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::{events::set_modifier_release_grace, keybinds::add_keybind};
+use rdev::{
+    EventType::{KeyPress, KeyRelease},
+    Key as RDevKey,
+};
+
+use std::time::{Duration, SystemTime};
+
+const GRACE: Duration = Duration::from_millis(100);
+
+fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+}
+
+#[test]
+fn a_modifier_re_pressed_within_the_grace_period_continues_the_chord() {
+    let _input = common::setup();
+    set_modifier_release_grace(Some(GRACE));
+    let kb = binding(Key::Other('k'), Modifiers::CONTROL_L);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    let t0 = SystemTime::now();
+    push_at(KeyPress(RDevKey::ControlLeft), t0);
+    push_at(KeyRelease(RDevKey::ControlLeft), t0 + ms(10));
+    push_at(KeyPress(RDevKey::ControlLeft), t0 + ms(40));
+    // the release was never let go of, so K sees Control even once the grace period is over
+    assert!(push_at(KeyPress(RDevKey::KeyK), t0 + ms(300)).is_none());
+    assert_eq!(notification(), Some(kb));
+    push_at(KeyRelease(RDevKey::KeyK), t0 + ms(310));
+    push_at(KeyRelease(RDevKey::ControlLeft), t0 + ms(320));
+}
+
+#[test]
+fn a_key_pressed_within_the_grace_period_still_gets_the_modifier() {
+    let _input = common::setup();
+    set_modifier_release_grace(Some(GRACE));
+    let kb = binding(Key::Other('j'), Modifiers::CONTROL_L | Modifiers::SHIFT_L);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    // Shift stays down, so lifting Control is no modifier tap
+    let t0 = SystemTime::now();
+    push_at(KeyPress(RDevKey::ControlLeft), t0);
+    push_at(KeyPress(RDevKey::ShiftLeft), t0 + ms(5));
+    push_at(KeyRelease(RDevKey::ControlLeft), t0 + ms(10));
+    assert!(push_at(KeyPress(RDevKey::KeyJ), t0 + ms(50)).is_none());
+    assert_eq!(notification(), Some(kb));
+    push_at(KeyRelease(RDevKey::KeyJ), t0 + ms(60));
+    push_at(KeyRelease(RDevKey::ShiftLeft), t0 + ms(70));
+}
+
+#[test]
+fn a_modifier_released_beyond_the_grace_period_is_dropped() {
+    let _input = common::setup();
+    set_modifier_release_grace(Some(GRACE));
+    let kb = binding(Key::Other('l'), Modifiers::CONTROL_L | Modifiers::SHIFT_L);
+    block_on(add_keybind(kb, || async {}));
+
+    let t0 = SystemTime::now();
+    push_at(KeyPress(RDevKey::ControlLeft), t0);
+    push_at(KeyPress(RDevKey::ShiftLeft), t0 + ms(5));
+    push_at(KeyRelease(RDevKey::ControlLeft), t0 + ms(10));
+    assert!(push_at(KeyPress(RDevKey::KeyL), t0 + ms(300)).is_some());
+    assert_eq!(notification(), None);
+    push_at(KeyRelease(RDevKey::KeyL), t0 + ms(310));
+    push_at(KeyRelease(RDevKey::ShiftLeft), t0 + ms(320));
+}
+
+#[test]
+fn without_a_grace_period_a_released_modifier_is_dropped_right_away() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('h'), Modifiers::CONTROL_L | Modifiers::SHIFT_L);
+    block_on(add_keybind(kb, || async {}));
+
+    let t0 = SystemTime::now();
+    push_at(KeyPress(RDevKey::ControlLeft), t0);
+    push_at(KeyPress(RDevKey::ShiftLeft), t0 + ms(5));
+    push_at(KeyRelease(RDevKey::ControlLeft), t0 + ms(10));
+    assert!(push_at(KeyPress(RDevKey::KeyH), t0 + ms(20)).is_some());
+    assert_eq!(notification(), None);
+    push_at(KeyRelease(RDevKey::KeyH), t0 + ms(30));
+    push_at(KeyRelease(RDevKey::ShiftLeft), t0 + ms(40));
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::{binding, block_on, chord, notification};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::keybinds::{add_keybind, set_keybind_guard};
+use rdev::Key as RDevKey;
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+#[test]
+fn a_guard_decides_whether_the_binding_fires_and_consumes() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('g'), Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+    let in_text_field = Arc::new(AtomicBool::new(false));
+    let guard = Arc::clone(&in_text_field);
+    block_on(set_keybind_guard(&kb, Some(Box::new(move || guard.load(Ordering::Relaxed)))));
+
+    // the Odilia key itself is always consumed
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyG]), [false, true]);
+    assert_eq!(notification(), None);
+
+    in_text_field.store(true, Ordering::Relaxed);
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyG]), [false, false]);
+    assert_eq!(notification(), Some(kb.clone()));
+
+    // without a guard, the binding always fires
+    in_text_field.store(false, Ordering::Relaxed);
+    block_on(set_keybind_guard(&kb, None));
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyG]), [false, false]);
+    assert_eq!(notification(), Some(kb));
+}
+
+#[test]
+fn a_failing_guard_lets_another_binding_match() {
+    let _input = common::setup();
+    let guarded = binding(Key::Other('u'), Modifiers::ODILIA | Modifiers::SHIFT_L);
+    let fallback = binding(Key::Other('u'), Modifiers::ODILIA);
+    block_on(add_keybind(guarded.clone(), || async {}));
+    block_on(add_keybind(fallback.clone(), || async {}));
+    block_on(set_keybind_guard(&guarded, Some(Box::new(|| false))));
+
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::ShiftLeft, RDevKey::KeyU]), [false, true, false]);
+    assert_eq!(notification(), Some(fallback));
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use common::{binding, block_on, key_event};
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::{
+    events::set_observe_only,
+    keybinds::{
+        add_fallible_keybind, add_handler, add_keybind, keyevent_match_sync, remove_keybind, run_keybind_func,
+        set_handler_dispatch, set_keybind_timeout, set_outcome_observer, trigger, HandlerDispatch, NotBound,
+    },
+};
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+#[test]
+fn running_a_binding_removed_after_it_matched_does_nothing() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('r'), Modifiers::ODILIA);
+    let ran = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&ran);
+    block_on(add_keybind(kb.clone(), move || {
+        flag.store(true, Ordering::SeqCst);
+        async {}
+    }));
+
+    let matched = keyevent_match_sync(&key_event(Key::Other('r'), Modifiers::ODILIA)).unwrap();
+    assert!(block_on(remove_keybind(kb)));
+    block_on(run_keybind_func(&matched));
+    assert!(!ran.load(Ordering::SeqCst));
+}
+
+#[test]
+fn the_outcome_of_a_fallible_handler_is_observed() {
+    let _input = common::setup();
+    let (failing, succeeding) = (binding(Key::Other('f'), Modifiers::ODILIA), binding(Key::Other('s'), Modifiers::ODILIA));
+    block_on(add_fallible_keybind(failing.clone(), || async { Err("no line to read".into()) }));
+    block_on(add_fallible_keybind(succeeding.clone(), || async { Ok(()) }));
+    let (tx, outcomes) = mpsc::channel();
+    block_on(set_outcome_observer(Some(Box::new(move |kb, result| {
+        let _ = tx.send((kb.clone(), result.as_ref().map(|_| ()).map_err(ToString::to_string)));
+    }))));
+
+    block_on(run_keybind_func(&failing));
+    block_on(run_keybind_func(&succeeding));
+    assert_eq!(outcomes.try_recv(), Ok((failing, Err("no line to read".to_string()))));
+    assert_eq!(outcomes.try_recv(), Ok((succeeding, Ok(()))));
+}
+
+#[test]
+fn a_handler_running_past_its_timeout_is_aborted() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('t'), Modifiers::ODILIA);
+    let finished = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&finished);
+    block_on(add_keybind(kb.clone(), move || {
+        let flag = Arc::clone(&flag);
+        async move {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            flag.store(true, Ordering::SeqCst);
+        }
+    }));
+    block_on(set_keybind_timeout(&kb, Some(Duration::from_millis(50))));
+
+    let start = Instant::now();
+    block_on(run_keybind_func(&kb));
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(!finished.load(Ordering::SeqCst));
+}
+
+#[test]
+fn triggering_a_binding_runs_its_handler() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('g'), Modifiers::ODILIA);
+    let (tx, ran) = mpsc::channel();
+    block_on(add_keybind(kb.clone(), move || {
+        let _ = tx.send(());
+        async {}
+    }));
+
+    assert_eq!(block_on(trigger(&kb)), Ok(()));
+    assert_eq!(ran.try_recv(), Ok(()));
+    // it doesn't come from a key press, so it runs in observe only mode too
+    set_observe_only(true);
+    assert_eq!(block_on(trigger(&kb)), Ok(()));
+    assert_eq!(ran.try_recv(), Ok(()));
+}
+
+#[test]
+fn triggering_an_unbound_binding_fails() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('n'), Modifiers::ODILIA);
+    assert_eq!(block_on(trigger(&kb)), Err(NotBound(kb)));
+}
+
+/// Bind `key` to a handler recording "action", with a slow handler recording "log" before it and
+/// another recording "after" behind it, returning what they record.
+fn bind_with_handlers(key: char, dispatch: HandlerDispatch) -> (KeyBinding, Arc<Mutex<Vec<&'static str>>>) {
+    let kb = binding(Key::Other(key), Modifiers::ODILIA);
+    let ran = Arc::new(Mutex::new(Vec::new()));
+    let record = |name| {
+        let ran = Arc::clone(&ran);
+        move || {
+            let ran = Arc::clone(&ran);
+            async move {
+                if name == "log" {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+                ran.lock().unwrap().push(name);
+            }
+        }
+    };
+    block_on(add_keybind(kb.clone(), record("action")));
+    assert!(block_on(add_handler(&kb, -1, record("after"))));
+    assert!(block_on(add_handler(&kb, 5, record("log"))));
+    block_on(set_handler_dispatch(&kb, dispatch));
+    (kb, ran)
+}
+
+#[test]
+fn sequential_handlers_run_in_order_of_priority() {
+    let _input = common::setup();
+    let (kb, ran) = bind_with_handlers('q', HandlerDispatch::Sequential);
+
+    block_on(run_keybind_func(&kb));
+    assert_eq!(*ran.lock().unwrap(), ["log", "action", "after"]);
+}
+
+#[test]
+fn concurrent_handlers_do_not_wait_for_each_other() {
+    let _input = common::setup();
+    let (kb, ran) = bind_with_handlers('w', HandlerDispatch::Concurrent);
+
+    block_on(run_keybind_func(&kb));
+    // the others run on tasks of their own, in no particular order, but don't wait for the slow one
+    let ran = ran.lock().unwrap();
+    assert_eq!(ran.len(), 3);
+    assert_eq!(ran.last(), Some(&"log"));
+}
@@ -0,0 +1,56 @@
+//! The input monitoring system without the `grab` feature, where nothing touches the OS input
+//! devices.
+#![cfg(not(feature = "grab"))]
+
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::{
+    backend::MockBackend,
+    events::{backend_ready, create_keybind_channel_with_config, InputConfig},
+    keybinds::add_keybind,
+};
+use rdev::{
+    Event,
+    EventType::{self, KeyPress},
+    Key as RDevKey,
+};
+
+use std::time::{Duration, SystemTime};
+
+fn push(event_type: EventType) -> bool {
+    MockBackend
+        .push_event(Event {
+            time: SystemTime::now(),
+            name: None,
+            event_type,
+        })
+        .is_some()
+}
+
+#[test]
+fn bindings_fire_through_the_mock_backend_started_by_default() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_time()
+        .build()
+        .unwrap();
+    let mut rx = {
+        let _entered = runtime.enter();
+        create_keybind_channel_with_config(InputConfig::default()).unwrap()
+    };
+    runtime
+        .block_on(async { tokio::time::timeout(Duration::from_secs(5), backend_ready()).await })
+        .expect("the mock backend never became ready");
+
+    let kb = KeyBinding {
+        key: Some(Key::Other('j')),
+        mods: Modifiers::CONTROL_L,
+        repeat: 1,
+        consume: true,
+        notify: true,
+        mode: None,
+    };
+    assert!(runtime.block_on(add_keybind(kb.clone(), || async {})));
+    assert!(push(KeyPress(RDevKey::ControlLeft)));
+    assert!(!push(KeyPress(RDevKey::KeyJ)));
+    assert_eq!(rx.try_recv().ok(), Some(kb));
+}
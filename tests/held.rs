@@ -0,0 +1,76 @@
+mod common;
+
+use common::{binding, block_on, notification, notifications, press, release};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::{
+    events::{currently_pressed, currently_pressed_modifiers},
+    keybinds::add_keybind,
+};
+use rdev::Key as RDevKey;
+
+fn held() -> (Vec<Key>, Modifiers) {
+    (currently_pressed(), currently_pressed_modifiers())
+}
+
+#[test]
+fn overlapping_presses_and_releases_keep_the_held_keys_accurate() {
+    let _input = common::setup();
+    press(RDevKey::ShiftLeft);
+    press(RDevKey::KeyA);
+    // Shift goes up before the letter does, as it does when typing fast
+    release(RDevKey::ShiftLeft);
+    press(RDevKey::KeyB);
+    assert_eq!(held(), (vec![Key::Other('a'), Key::Other('b')], Modifiers::empty()));
+
+    press(RDevKey::ControlRight);
+    release(RDevKey::KeyA);
+    assert_eq!(held(), (vec![Key::Other('b')], Modifiers::CONTROL_R));
+    release(RDevKey::KeyB);
+    release(RDevKey::ControlRight);
+    assert_eq!(held(), (Vec::new(), Modifiers::empty()));
+}
+
+#[test]
+fn a_modifier_released_mid_chord_no_longer_applies() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('n'), Modifiers::ALT_L);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    press(RDevKey::Alt);
+    press(RDevKey::KeyM);
+    release(RDevKey::Alt);
+    release(RDevKey::KeyM);
+    // Alt is up again, so this is a plain 'n'
+    assert!(press(RDevKey::KeyN));
+    release(RDevKey::KeyN);
+    assert_eq!(notification(), None);
+    assert_eq!(held(), (Vec::new(), Modifiers::empty()));
+}
+
+// A press is new unless it repeats the most recently pressed key, which is what the OS sends while
+// a key is held down
+#[test]
+fn only_a_repeat_of_the_most_recent_key_is_not_a_new_press() {
+    let _input = common::setup();
+    let j = binding(Key::Other('j'), Modifiers::ODILIA);
+    block_on(add_keybind(j.clone(), || async {}));
+
+    press(RDevKey::CapsLock);
+    press(RDevKey::KeyJ);
+    press(RDevKey::KeyJ);
+    assert_eq!(notifications(), [j]);
+    assert_eq!(held().0, [Key::Other('j')]);
+
+    // once another key went down, a press of the first one counts as new again
+    press(RDevKey::KeyK);
+    press(RDevKey::KeyK);
+    press(RDevKey::KeyJ);
+    assert_eq!(held().0, [Key::Other('j'), Key::Other('k'), Key::Other('j')]);
+
+    // a single release lets go of the key however often it was pressed
+    release(RDevKey::KeyJ);
+    assert_eq!(held(), (vec![Key::Other('k')], Modifiers::ODILIA));
+    release(RDevKey::KeyK);
+    release(RDevKey::CapsLock);
+    assert_eq!(held(), (Vec::new(), Modifiers::empty()));
+}
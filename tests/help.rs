@@ -0,0 +1,41 @@
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::keybinds::{InputManager, KeybindHelp};
+
+fn binding(key: char) -> KeyBinding {
+    KeyBinding {
+        key: Some(Key::Other(key)),
+        mods: Modifiers::ODILIA,
+        repeat: 1,
+        consume: true,
+        notify: true,
+        mode: None,
+    }
+}
+
+#[test]
+fn descriptions_are_listed_by_category() {
+    let manager = InputManager::new();
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    runtime.block_on(async {
+        manager.add_keybind_described(binding('w'), "Read current word", "Reading", || async {}).await;
+        manager.add_keybind_described(binding('h'), "Next heading", "Navigation", || async {}).await;
+        manager.add_keybind_described(binding('l'), "Read current line", "Reading", || async {}).await;
+        manager.add_keybind(binding('x'), || async {}).await;
+    });
+
+    let help = runtime.block_on(manager.keybind_help());
+    let entry = |key, description: &str, category: &str| KeybindHelp {
+        keybind: binding(key),
+        description: description.to_string(),
+        category: category.to_string(),
+        then_mode: None,
+    };
+    assert_eq!(
+        help,
+        [
+            entry('h', "Next heading", "Navigation"),
+            entry('l', "Read current line", "Reading"),
+            entry('w', "Read current word", "Reading"),
+        ]
+    );
+}
@@ -0,0 +1,86 @@
+//! Idle detection against a paused clock, which only moves when the test advances it.
+use odilia_input::{
+    backend::MockBackend,
+    events::{create_keybind_channel_with_backend, on_idle, InputConfig},
+    keybinds::async_fn,
+};
+use rdev::{
+    Event,
+    EventType::{self, KeyPress, KeyRelease},
+    Key as RDevKey,
+};
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+
+fn push(event_type: EventType) {
+    MockBackend.push_event(Event {
+        time: SystemTime::now(),
+        name: None,
+        event_type,
+    });
+}
+
+async fn wait(secs: u64) {
+    tokio::time::sleep(Duration::from_secs(secs)).await;
+}
+
+#[test]
+fn idle_fires_once_per_quiet_period_until_cancelled() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .start_paused(true)
+        .build()
+        .unwrap();
+    let _rx = {
+        let _entered = runtime.enter();
+        create_keybind_channel_with_backend(InputConfig::default(), Box::new(MockBackend)).unwrap()
+    };
+    let fired = Arc::new(AtomicUsize::new(0));
+    let count = Arc::clone(&fired);
+    let idle = || fired.load(Ordering::SeqCst);
+
+    runtime.block_on(async {
+        let handle = on_idle(
+            Duration::from_secs(10),
+            async_fn(move || {
+                count.fetch_add(1, Ordering::SeqCst);
+                async {}
+            }),
+        );
+        wait(9).await;
+        assert_eq!(idle(), 0);
+        wait(2).await;
+        assert_eq!(idle(), 1);
+        // it stays quiet, but the callback already ran for this period
+        wait(60).await;
+        assert_eq!(idle(), 1);
+
+        // input starts a new quiet period
+        push(KeyPress(RDevKey::KeyA));
+        wait(5).await;
+        push(KeyRelease(RDevKey::KeyA));
+        wait(9).await;
+        assert_eq!(idle(), 1);
+        wait(2).await;
+        assert_eq!(idle(), 2);
+
+        // so does the mouse, though only keys are monitored
+        push(EventType::MouseMove { x: 1.0, y: 2.0 });
+        wait(9).await;
+        assert_eq!(idle(), 2);
+        wait(2).await;
+        assert_eq!(idle(), 3);
+
+        handle.cancel();
+        push(KeyPress(RDevKey::KeyA));
+        push(KeyRelease(RDevKey::KeyA));
+        wait(60).await;
+        assert_eq!(idle(), 3);
+    });
+}
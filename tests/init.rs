@@ -0,0 +1,20 @@
+use odilia_input::{
+    backend::MockBackend,
+    events::{create_keybind_channel_with_backend, is_initialized, try_create_keybind_channel, InitError, InputConfig},
+};
+
+// Initialisation can only succeed once per process, so the steps share a single test
+#[test]
+fn initialising_needs_a_runtime_and_only_works_once() {
+    assert_eq!(try_create_keybind_channel().err(), Some(InitError::NoRuntime));
+    assert!(!is_initialized());
+
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let _entered = runtime.enter();
+    assert!(create_keybind_channel_with_backend(InputConfig::default(), Box::new(MockBackend)).is_ok());
+    assert!(is_initialized());
+    assert_eq!(
+        create_keybind_channel_with_backend(InputConfig::default(), Box::new(MockBackend)).err(),
+        Some(InitError::AlreadyInitialized)
+    );
+}
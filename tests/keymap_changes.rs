@@ -0,0 +1,87 @@
+mod common;
+
+use common::{binding, block_on, key_event};
+use odilia_common::{
+    input::{Key, Modifiers},
+    modes::ScreenReaderMode,
+};
+use odilia_input::keybinds::{async_fn, InputManager, KeymapChange};
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// A manager with an observer recording the changes it is told about.
+fn observed() -> (InputManager, Arc<Mutex<Vec<KeymapChange>>>) {
+    let manager = InputManager::new();
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&changes);
+    block_on(manager.set_keymap_observer(Some(Box::new(move |change| seen.lock().unwrap().push(change.clone())))));
+    (manager, changes)
+}
+
+fn take(changes: &Mutex<Vec<KeymapChange>>) -> Vec<KeymapChange> {
+    std::mem::take(&mut changes.lock().unwrap())
+}
+
+#[test]
+fn adding_and_removing_a_binding_is_observed() {
+    let _input = common::setup();
+    let (manager, changes) = observed();
+    let kb = binding(Key::Other('a'), Modifiers::ODILIA);
+
+    block_on(manager.add_keybind(kb.clone(), || async {}));
+    assert_eq!(take(&changes), [KeymapChange::Added(kb.clone())]);
+    block_on(manager.remove_keybind(kb.clone()));
+    assert_eq!(take(&changes), [KeymapChange::Removed(kb.clone())]);
+    // nothing changed, so nothing is observed
+    block_on(manager.remove_keybind(kb));
+    assert_eq!(take(&changes), []);
+}
+
+#[test]
+fn a_batch_is_observed_without_its_conflicts() {
+    let _input = common::setup();
+    let (manager, changes) = observed();
+    let (taken, free) = (binding(Key::Other('b'), Modifiers::ODILIA), binding(Key::Other('c'), Modifiers::ODILIA));
+    block_on(manager.add_keybind(taken.clone(), || async {}));
+    take(&changes);
+
+    let batch = HashMap::from([(taken, async_fn(|| async {})), (free.clone(), async_fn(|| async {}))]);
+    block_on(manager.add_keybinds(batch));
+    assert_eq!(take(&changes), [KeymapChange::Added(free)]);
+}
+
+#[test]
+fn replacing_a_modes_keymap_is_observed() {
+    let _input = common::setup();
+    let (manager, changes) = observed();
+    let browse = ScreenReaderMode::new("Browse");
+    let keymap = || HashMap::from([(binding(Key::Other('h'), Modifiers::NONE), async_fn(|| async {}))]);
+
+    block_on(manager.load_mode_keymap(browse.clone(), keymap()));
+    block_on(manager.load_mode_keymap(browse.clone(), keymap()));
+    assert_eq!(
+        take(&changes),
+        [KeymapChange::ModeKeymapLoaded(browse.clone()), KeymapChange::ModeKeymapLoaded(browse)]
+    );
+}
+
+#[test]
+fn the_observer_can_read_the_keymap() {
+    let _input = common::setup();
+    let manager = InputManager::new();
+    let consumed = Arc::new(Mutex::new(Vec::new()));
+    let (reader, seen) = (manager.clone(), Arc::clone(&consumed));
+    block_on(manager.set_keymap_observer(Some(Box::new(move |_| {
+        let kbm = key_event(Key::Other('d'), Modifiers::ODILIA);
+        seen.lock().unwrap().push(reader.would_consume(&kbm));
+    }))));
+
+    let kb = binding(Key::Other('d'), Modifiers::ODILIA);
+    block_on(manager.add_keybind(kb.clone(), || async {}));
+    block_on(manager.remove_keybind(kb));
+    assert_eq!(*consumed.lock().unwrap(), [true, false]);
+    block_on(manager.set_keymap_observer(None));
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use common::{binding, block_on, chord, key_event, notification};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::{
+    events::{is_num_lock_on, numpad_key, set_num_lock, supported_keys, translate_event},
+    keybinds::add_keybind,
+};
+use rdev::{Event, EventType::KeyPress, Key as RDevKey};
+
+use std::time::SystemTime;
+
+fn press_event(key: RDevKey) -> Event {
+    Event {
+        time: SystemTime::now(),
+        name: None,
+        event_type: KeyPress(key),
+    }
+}
+
+fn translate_press(key: RDevKey) -> Option<Key> {
+    translate_event(&press_event(key), &[])?.key
+}
+
+// rdev has no keys past F12, so it is the highest function key a binding can use
+#[test]
+fn f12_can_be_bound() {
+    let _input = common::setup();
+    assert!(supported_keys().contains(&Key::F12));
+    let kb = binding(Key::F12, Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::F12]), [false, false]);
+    assert_eq!(notification(), Some(kb));
+}
+
+#[test]
+fn supported_keys_are_those_a_press_translates_to() {
+    let keys = supported_keys();
+    assert_eq!(translate_press(RDevKey::KeyM), Some(Key::Other('m')));
+    assert!(keys.contains(&Key::Other('m')));
+    // an unknown key translates to nothing, so nothing is listed for it
+    assert_eq!(translate_press(RDevKey::Unknown(0xfff0)), None);
+}
+
+#[test]
+fn punctuation_is_bound_by_physical_key_plus_shift() {
+    let _input = common::setup();
+    let shifted = translate_event(&press_event(RDevKey::SemiColon), &[RDevKey::ShiftLeft]);
+    assert_eq!(shifted, Some(key_event(Key::Other(';'), Modifiers::SHIFT_L)));
+
+    let plain = binding(Key::Other(';'), Modifiers::ODILIA);
+    let colon = binding(Key::Other(';'), Modifiers::ODILIA | Modifiers::SHIFT_L);
+    block_on(add_keybind(plain.clone(), || async {}));
+    block_on(add_keybind(colon.clone(), || async {}));
+
+    chord(&[RDevKey::CapsLock, RDevKey::SemiColon]);
+    assert_eq!(notification(), Some(plain));
+    chord(&[RDevKey::CapsLock, RDevKey::ShiftLeft, RDevKey::SemiColon]);
+    assert_eq!(notification(), Some(colon));
+}
+
+#[test]
+fn translating_a_modifier_and_letter() {
+    assert_eq!(
+        translate_event(&press_event(RDevKey::KeyA), &[RDevKey::CapsLock, RDevKey::ControlLeft]),
+        Some(key_event(Key::Other('a'), Modifiers::ODILIA | Modifiers::CONTROL_L))
+    );
+    // a modifier on its own completes nothing
+    assert_eq!(translate_event(&press_event(RDevKey::ControlLeft), &[]), None);
+    assert_eq!(translate_event(&press_event(RDevKey::Unknown(0xfff0)), &[RDevKey::ControlLeft]), None);
+}
+
+#[test]
+fn keypad_digits_translate_to_keypad_keys() {
+    let keypad = [
+        (RDevKey::Kp0, Key::Kp0),
+        (RDevKey::Kp1, Key::Kp1),
+        (RDevKey::Kp2, Key::Kp2),
+        (RDevKey::Kp3, Key::Kp3),
+        (RDevKey::Kp4, Key::Kp4),
+        (RDevKey::Kp5, Key::Kp5),
+        (RDevKey::Kp6, Key::Kp6),
+        (RDevKey::Kp7, Key::Kp7),
+        (RDevKey::Kp8, Key::Kp8),
+        (RDevKey::Kp9, Key::Kp9),
+    ];
+    for (rdev_key, key) in keypad {
+        assert_eq!(translate_press(rdev_key), Some(key));
+    }
+}
+
+#[test]
+fn number_row_digits_translate_like_the_keypad_digits() {
+    assert_eq!(translate_press(RDevKey::Num0), Some(Key::Kp0));
+    assert_eq!(translate_press(RDevKey::Num5), Some(Key::Kp5));
+    assert_eq!(translate_press(RDevKey::Num9), Some(Key::Kp9));
+
+    let _input = common::setup();
+    let kp5 = binding(Key::Kp5, Modifiers::ODILIA);
+    block_on(add_keybind(kp5.clone(), || async {}));
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::Num5]), [false, false]);
+    assert_eq!(notification(), Some(kp5.clone()));
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::Kp5]), [false, false]);
+    assert_eq!(notification(), Some(kp5));
+}
+
+#[test]
+fn the_numpad_navigates_with_num_lock_off_and_types_digits_with_it_on() {
+    let _input = common::setup();
+    set_num_lock(false);
+    assert_eq!(numpad_key(&Key::Kp7), Key::Home);
+    assert_eq!(numpad_key(&Key::Kp1), Key::End);
+    assert_eq!(numpad_key(&Key::Kp8), Key::Up);
+    assert_eq!(numpad_key(&Key::Kp3), Key::PageDown);
+    assert_eq!(numpad_key(&Key::Kp5), Key::Kp5);
+
+    set_num_lock(true);
+    assert_eq!(numpad_key(&Key::Kp7), Key::Other('7'));
+    assert_eq!(numpad_key(&Key::KpDelete), Key::Other('.'));
+    // other keys are left alone either way
+    assert_eq!(numpad_key(&Key::Home), Key::Home);
+}
+
+#[test]
+fn pressing_num_lock_toggles_the_numpad_layer() {
+    let _input = common::setup();
+    assert!(!is_num_lock_on());
+    chord(&[RDevKey::NumLock]);
+    assert!(is_num_lock_on());
+    assert_eq!(numpad_key(&Key::Kp7), Key::Other('7'));
+    chord(&[RDevKey::NumLock]);
+    assert_eq!(numpad_key(&Key::Kp7), Key::Home);
+}
+
+#[test]
+fn the_fn_key_can_be_bound() {
+    let _input = common::setup();
+    assert_eq!(translate_press(RDevKey::Function), Some(Key::Function));
+    let kb = binding(Key::Function, Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::Function]), [false, false]);
+    assert_eq!(notification(), Some(kb));
+}
+
+// rdev 0.5 reports media keys like VolumeUp only by their platform's code, and Key has nothing
+// to translate such a code to, so they pass through untouched
+#[test]
+fn media_keys_pass_through_untranslated() {
+    let _input = common::setup();
+    const VOLUME_UP: u32 = 123;
+    assert_eq!(translate_press(RDevKey::Unknown(VOLUME_UP)), None);
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::Unknown(VOLUME_UP)]), [false, true]);
+    assert_eq!(notification(), None);
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use common::chord;
+use odilia_input::events::{grab_latency_stats, reset_latency_stats, set_latency_tracking, LatencyStats};
+use rdev::Key as RDevKey;
+
+#[test]
+fn latency_is_recorded_while_tracking_is_on() {
+    let _input = common::setup();
+    set_latency_tracking(true);
+
+    chord(&[RDevKey::KeyA, RDevKey::KeyB]);
+    let stats = grab_latency_stats();
+    // two presses and two releases
+    assert_eq!(stats.count, 4);
+    assert!(stats.max >= stats.last);
+    assert!(stats.total >= stats.max);
+    assert!(stats.mean().is_some_and(|mean| mean <= stats.max));
+
+    reset_latency_stats();
+    assert_eq!(grab_latency_stats(), LatencyStats::default());
+    assert_eq!(grab_latency_stats().mean(), None);
+}
+
+#[test]
+fn nothing_is_recorded_while_tracking_is_off() {
+    let _input = common::setup();
+
+    chord(&[RDevKey::KeyC]);
+    assert_eq!(grab_latency_stats().count, 0);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{binding, block_on, notification, press, press_named, release};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::keybinds::{add_keybind, set_match_strategy, MatchStrategy};
+use rdev::Key as RDevKey;
+
+// on an AZERTY layout the key in the QWERTY Q position types an 'a', and the one in the A
+// position types a 'q'
+
+#[test]
+fn a_physical_binding_ignores_the_layout() {
+    let _input = common::setup();
+    let up = binding(Key::Up, Modifiers::ODILIA);
+    block_on(add_keybind(up.clone(), || async {}));
+
+    press(RDevKey::CapsLock);
+    assert!(!press_named(RDevKey::UpArrow, ""));
+    release(RDevKey::UpArrow);
+    release(RDevKey::CapsLock);
+    assert_eq!(notification(), Some(up));
+}
+
+#[test]
+fn a_physical_letter_binding_matches_the_key_position() {
+    let _input = common::setup();
+    let w = binding(Key::Other('w'), Modifiers::ODILIA);
+    block_on(add_keybind(w.clone(), || async {}));
+
+    // AZERTY types a 'z' with the key in the QWERTY W position
+    press(RDevKey::CapsLock);
+    assert!(!press_named(RDevKey::KeyW, "z"));
+    release(RDevKey::KeyW);
+    release(RDevKey::CapsLock);
+    assert_eq!(notification(), Some(w));
+}
+
+#[test]
+fn a_logical_binding_matches_the_typed_character() {
+    let _input = common::setup();
+    let a = binding(Key::Other('a'), Modifiers::ODILIA);
+    block_on(add_keybind(a.clone(), || async {}));
+    block_on(set_match_strategy(&a, MatchStrategy::Logical));
+
+    press(RDevKey::CapsLock);
+    assert!(!press_named(RDevKey::KeyQ, "a"));
+    release(RDevKey::KeyQ);
+    assert_eq!(notification(), Some(a));
+
+    // the key in the QWERTY A position types a 'q' now
+    assert!(press_named(RDevKey::KeyA, "q"));
+    release(RDevKey::KeyA);
+    release(RDevKey::CapsLock);
+    assert_eq!(notification(), None);
+}
+
+#[test]
+fn a_logical_binding_takes_shift_from_the_character() {
+    let _input = common::setup();
+    let e = binding(Key::Other('E'), Modifiers::ODILIA);
+    block_on(add_keybind(e.clone(), || async {}));
+    block_on(set_match_strategy(&e, MatchStrategy::Logical));
+
+    press(RDevKey::CapsLock);
+    press(RDevKey::ShiftLeft);
+    assert!(!press_named(RDevKey::KeyE, "E"));
+    release(RDevKey::KeyE);
+    release(RDevKey::ShiftLeft);
+    release(RDevKey::CapsLock);
+    assert_eq!(notification(), Some(e));
+}
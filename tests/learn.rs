@@ -0,0 +1,45 @@
+mod common;
+
+use common::{binding, block_on, chord, notifications, press, release, unhandled_events};
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::{events::set_learn_mode, keybinds::add_keybind};
+use rdev::Key as RDevKey;
+
+use std::time::Duration;
+
+/// What learn mode sends for a press of `key` with `mods`.
+fn learned(key: Key, mods: Modifiers) -> KeyBinding {
+    KeyBinding {
+        consume: false,
+        ..binding(key, mods)
+    }
+}
+
+#[test]
+fn in_learn_mode_every_key_notifies_and_passes_through() {
+    let _input = common::setup();
+    block_on(add_keybind(binding(Key::Other('n'), Modifiers::ODILIA), || async {}));
+    let unhandled = unhandled_events();
+    set_learn_mode(true);
+
+    // only the Odilia key itself is still kept from the system
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyN]), [false, true]);
+    assert_eq!(chord(&[RDevKey::F3]), [true]);
+    assert_eq!(
+        notifications(),
+        [learned(Key::Other('n'), Modifiers::ODILIA), learned(Key::F3, Modifiers::NONE)]
+    );
+    assert!(unhandled.recv_timeout(Duration::from_millis(100)).is_err());
+}
+
+#[test]
+fn in_learn_mode_a_held_key_notifies_once() {
+    let _input = common::setup();
+    set_learn_mode(true);
+
+    for _ in 0..3 {
+        assert!(press(RDevKey::F4));
+    }
+    release(RDevKey::F4);
+    assert_eq!(notifications(), [learned(Key::F4, Modifiers::NONE)]);
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::{binding, block_on, chord, notification};
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::keybinds::InputManager;
+use rdev::Key as RDevKey;
+
+#[test]
+fn events_are_matched_against_the_active_manager() {
+    let _input = common::setup();
+    let (a, b) = (InputManager::new(), InputManager::new());
+    let consumed = binding(Key::Other('m'), Modifiers::CONTROL_L);
+    let passed = KeyBinding {
+        consume: false,
+        ..consumed.clone()
+    };
+    block_on(a.add_keybind(consumed.clone(), || async {}));
+    block_on(b.add_keybind(passed.clone(), || async {}));
+
+    a.activate();
+    assert!(a.is_active() && !b.is_active());
+    assert_eq!(chord(&[RDevKey::ControlLeft, RDevKey::KeyM]), [true, false]);
+    assert_eq!(notification(), Some(consumed));
+
+    b.activate();
+    assert_eq!(chord(&[RDevKey::ControlLeft, RDevKey::KeyM]), [true, true]);
+    assert_eq!(notification(), Some(passed));
+}
+
+#[test]
+fn bindings_of_an_inactive_manager_never_fire() {
+    let _input = common::setup();
+    let inactive = InputManager::new();
+    block_on(inactive.add_keybind(binding(Key::Other('m'), Modifiers::CONTROL_L), || async {}));
+
+    assert_eq!(chord(&[RDevKey::ControlLeft, RDevKey::KeyM]), [true, true]);
+    assert_eq!(notification(), None);
+}
+
+#[test]
+fn removing_a_binding_from_one_manager_leaves_the_other_alone() {
+    let _input = common::setup();
+    let (a, b) = (InputManager::new(), InputManager::new());
+    let kb = binding(Key::Other('m'), Modifiers::CONTROL_L);
+    block_on(a.add_keybind_described(kb.clone(), "Next heading", "Navigation", || async {}));
+    block_on(b.add_keybind_described(kb.clone(), "Next heading", "Navigation", || async {}));
+
+    assert!(block_on(a.remove_keybind(kb.clone())));
+    assert_eq!(block_on(a.keybind_help()), []);
+    assert_eq!(block_on(b.keybind_help()).len(), 1);
+
+    b.activate();
+    assert_eq!(chord(&[RDevKey::ControlLeft, RDevKey::KeyM]), [true, false]);
+    assert_eq!(notification(), Some(kb));
+}
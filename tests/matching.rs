@@ -0,0 +1,55 @@
+mod common;
+
+use common::{binding, block_on};
+use odilia_common::input::{Key, KeyBinding, KeyEvent, Modifiers};
+use odilia_input::{
+    events::classify,
+    keybinds::{add_keybind, get_sr_mode_sync, keyevent_match, keyevent_match_sync, matches},
+};
+
+#[test]
+fn every_entry_point_agrees_on_the_match() {
+    let _input = common::setup();
+    let bindings = [
+        binding(Key::Other('a'), Modifiers::ODILIA),
+        binding(Key::Other('a'), Modifiers::ODILIA | Modifiers::SHIFT_L),
+        KeyBinding {
+            repeat: 2,
+            ..binding(Key::Other('b'), Modifiers::ODILIA)
+        },
+        binding(Key::Other('c'), Modifiers::NONE),
+        binding(Key::Other('c'), Modifiers::SHIFT_L | Modifiers::SHIFT_R),
+    ];
+    for kb in &bindings {
+        block_on(add_keybind(kb.clone(), || async {}));
+    }
+    let mode = get_sr_mode_sync();
+
+    let keys = ['a', 'b', 'c', 'd'].map(|c| Some(Key::Other(c)));
+    let mods = [
+        Modifiers::NONE,
+        Modifiers::ODILIA,
+        Modifiers::ODILIA | Modifiers::SHIFT_L,
+        Modifiers::SHIFT_L,
+        Modifiers::SHIFT_L | Modifiers::SHIFT_R,
+    ];
+    for key in &keys {
+        for &mods in &mods {
+            for repeat in 1..=2 {
+                let kbm = KeyEvent {
+                    key: key.clone(),
+                    mods,
+                    repeat,
+                };
+                let matched = keyevent_match_sync(&kbm);
+                assert_eq!(block_on(keyevent_match(&kbm)), matched, "{:?}", kbm);
+                assert_eq!(classify(&kbm).1, matched, "{:?}", kbm);
+                let matching: Vec<_> = bindings.iter().filter(|kb| matches(kb, &kbm, &mode)).collect();
+                match &matched {
+                    Some(kb) => assert!(matching.contains(&kb), "{:?} matched {:?}", kbm, kb),
+                    None => assert!(matching.is_empty(), "{:?} matched nothing, but {:?} do", kbm, matching),
+                }
+            }
+        }
+    }
+}
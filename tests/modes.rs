@@ -0,0 +1,237 @@
+mod common;
+
+use common::{binding, block_on, chord, notification};
+use odilia_common::{
+    input::{Key, KeyBinding, Modifiers},
+    modes::ScreenReaderMode,
+};
+use odilia_input::keybinds::{
+    add_keybind, add_keybind_for_modes, add_keybind_with_priority, async_fn, get_sr_mode_sync, load_mode_keymap, run_keybind_func,
+    set_mode_provider, set_sr_mode, set_then_mode, then_mode, AsyncFn, InputManager, UnknownMode,
+};
+use rdev::Key as RDevKey;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+fn keymap(kbs: &[&KeyBinding]) -> HashMap<KeyBinding, AsyncFn> {
+    kbs.iter().map(|&kb| (kb.clone(), async_fn(|| async {}))).collect()
+}
+
+fn switch_to(mode: &ScreenReaderMode) {
+    block_on(set_sr_mode(mode.clone())).unwrap();
+}
+
+#[test]
+fn only_the_active_modes_keymap_fires() {
+    let _input = common::setup();
+    let (browse, focus) = (ScreenReaderMode::new("Browse"), ScreenReaderMode::new("Focus"));
+    let heading = binding(Key::Other('h'), Modifiers::NONE);
+    let form = binding(Key::Other('f'), Modifiers::NONE);
+    block_on(load_mode_keymap(browse.clone(), keymap(&[&heading])));
+    block_on(load_mode_keymap(focus.clone(), keymap(&[&form])));
+
+    switch_to(&browse);
+    assert_eq!(chord(&[RDevKey::KeyH]), [false]);
+    assert_eq!(notification(), Some(heading.clone()));
+    assert_eq!(chord(&[RDevKey::KeyF]), [true]);
+    assert_eq!(notification(), None);
+
+    switch_to(&focus);
+    assert_eq!(chord(&[RDevKey::KeyH]), [true]);
+    assert_eq!(notification(), None);
+    assert_eq!(chord(&[RDevKey::KeyF]), [false]);
+    assert_eq!(notification(), Some(form));
+}
+
+#[test]
+fn loading_a_modes_keymap_again_swaps_it_as_a_whole() {
+    let _input = common::setup();
+    let review = ScreenReaderMode::new("Review");
+    let (old, new) = (binding(Key::Other('o'), Modifiers::NONE), binding(Key::Other('n'), Modifiers::NONE));
+    block_on(load_mode_keymap(review.clone(), keymap(&[&old])));
+    block_on(load_mode_keymap(review.clone(), keymap(&[&new])));
+
+    switch_to(&review);
+    assert_eq!(chord(&[RDevKey::KeyO]), [true]);
+    assert_eq!(chord(&[RDevKey::KeyN]), [false]);
+    assert_eq!(notification(), Some(new));
+}
+
+#[test]
+fn a_mode_scoped_binding_only_fires_in_its_mode() {
+    let _input = common::setup();
+    let (browse, other) = (ScreenReaderMode::new("Scoped browse"), ScreenReaderMode::new("Scoped other"));
+    let kb = KeyBinding {
+        mode: Some(browse.clone()),
+        ..binding(Key::Other('s'), Modifiers::NONE)
+    };
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    switch_to(&other);
+    assert_eq!(chord(&[RDevKey::KeyS]), [true]);
+    assert_eq!(notification(), None);
+
+    switch_to(&browse);
+    assert_eq!(chord(&[RDevKey::KeyS]), [false]);
+    assert_eq!(notification(), Some(kb));
+}
+
+#[test]
+fn a_binding_for_several_modes_fires_in_each_of_them_only() {
+    let _input = common::setup();
+    let (browse, focus, review) = (
+        ScreenReaderMode::new("Multi browse"),
+        ScreenReaderMode::new("Multi focus"),
+        ScreenReaderMode::new("Multi review"),
+    );
+    let kb = binding(Key::Other('m'), Modifiers::NONE);
+    block_on(add_keybind_for_modes(kb.clone(), vec![browse.clone(), focus.clone()], || async {}));
+
+    for mode in [&browse, &focus] {
+        switch_to(mode);
+        assert_eq!(chord(&[RDevKey::KeyM]), [false]);
+        assert_eq!(notification(), Some(kb.clone()));
+    }
+    switch_to(&review);
+    assert_eq!(chord(&[RDevKey::KeyM]), [true]);
+    assert_eq!(notification(), None);
+}
+
+#[test]
+fn a_mode_provider_decides_the_mode() {
+    let _input = common::setup();
+    let (reading, writing) = (ScreenReaderMode::new("Provided reading"), ScreenReaderMode::new("Provided writing"));
+    let kb = KeyBinding {
+        mode: Some(reading.clone()),
+        ..binding(Key::Other('p'), Modifiers::NONE)
+    };
+    block_on(add_keybind(kb.clone(), || async {}));
+    let host_mode = Arc::new(Mutex::new(writing.clone()));
+    let provided = Arc::clone(&host_mode);
+    block_on(set_mode_provider(Some(Box::new(move || provided.lock().unwrap().clone()))));
+
+    // the crate's own mode is ignored while a provider is set
+    switch_to(&reading);
+    assert_eq!(chord(&[RDevKey::KeyP]), [true]);
+    assert_eq!(notification(), None);
+
+    switch_to(&writing);
+    *host_mode.lock().unwrap() = reading;
+    assert_eq!(chord(&[RDevKey::KeyP]), [false]);
+    assert_eq!(notification(), Some(kb));
+}
+
+#[test]
+fn the_active_bindings_follow_the_mode() {
+    let _input = common::setup();
+    let manager = InputManager::new();
+    let (browse, focus) = (ScreenReaderMode::new("Browse"), ScreenReaderMode::new("Focus"));
+    let everywhere = binding(Key::Other('s'), Modifiers::ODILIA);
+    let browse_only = KeyBinding {
+        mode: Some(browse.clone()),
+        ..binding(Key::Other('b'), Modifiers::ODILIA)
+    };
+    let heading = binding(Key::Other('h'), Modifiers::NONE);
+    let form = binding(Key::Other('f'), Modifiers::NONE);
+    let disabled = binding(Key::Other('d'), Modifiers::ODILIA);
+    block_on(async {
+        manager.add_keybind(everywhere.clone(), || async {}).await;
+        manager.add_keybind(browse_only.clone(), || async {}).await;
+        manager.add_keybind(disabled.clone(), || async {}).await;
+        manager.set_keybind_enabled(&disabled, false).await;
+        manager.load_mode_keymap(browse.clone(), keymap(&[&heading])).await;
+        manager.load_mode_keymap(focus.clone(), keymap(&[&form])).await;
+    });
+    let active = || {
+        let mut kbs = block_on(manager.active_keybinds());
+        kbs.sort_by_key(|kb| format!("{:?}", kb.key));
+        kbs
+    };
+
+    block_on(manager.set_sr_mode(browse)).unwrap();
+    assert_eq!(active(), [browse_only, heading, everywhere.clone()]);
+    block_on(manager.set_sr_mode(focus)).unwrap();
+    assert_eq!(active(), [form, everywhere]);
+}
+
+#[test]
+fn a_global_binding_fires_in_every_mode() {
+    let _input = common::setup();
+    let (browse, focus) = (ScreenReaderMode::new("Browse"), ScreenReaderMode::new("Focus"));
+    let plain = ScreenReaderMode::new("Plain");
+    let stop = binding(Key::Other('x'), Modifiers::ODILIA);
+    block_on(add_keybind(stop.clone(), || async {}));
+    // modes with keymaps of their own, and one without
+    block_on(load_mode_keymap(browse.clone(), keymap(&[&binding(Key::Other('y'), Modifiers::NONE)])));
+    block_on(load_mode_keymap(focus.clone(), keymap(&[&binding(Key::Other('z'), Modifiers::NONE)])));
+
+    for mode in [&browse, &focus, &plain] {
+        switch_to(mode);
+        assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyX]), [false, false], "in {:?}", mode);
+        assert_eq!(notification(), Some(stop.clone()), "in {:?}", mode);
+    }
+}
+
+#[test]
+fn a_global_binding_with_priority_beats_a_modes_own_binding() {
+    let _input = common::setup();
+    let review = ScreenReaderMode::new("Review");
+    let quit = binding(Key::Other('q'), Modifiers::ODILIA);
+    let review_q = KeyBinding {
+        consume: false,
+        ..quit.clone()
+    };
+    block_on(add_keybind_with_priority(quit.clone(), 1, || async {}));
+    block_on(load_mode_keymap(review.clone(), keymap(&[&review_q])));
+
+    switch_to(&review);
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyQ]), [false, false]);
+    assert_eq!(notification(), Some(quit));
+}
+
+// on a manager of its own, since registering a mode makes every other mode unknown
+#[test]
+fn only_registered_modes_can_be_switched_to() {
+    let _input = common::setup();
+    let manager = InputManager::new();
+    let browse = ScreenReaderMode::new("BrowseMode");
+    let typo = ScreenReaderMode::new("CommandMoode");
+
+    // a host which never registers a mode may switch to any
+    assert_eq!(block_on(manager.set_sr_mode(typo.clone())), Ok(()));
+
+    block_on(manager.register_mode(browse.clone()));
+    block_on(manager.register_mode(ScreenReaderMode::new("CommandMode")));
+    assert_eq!(block_on(manager.set_sr_mode(browse.clone())), Ok(()));
+    assert_eq!(block_on(manager.set_sr_mode(typo.clone())), Err(UnknownMode(typo.clone())));
+    assert_eq!(manager.set_sr_mode_sync(typo.clone()), Err(UnknownMode(typo)));
+    // a failed switch keeps the mode
+    assert_eq!(manager.get_sr_mode_sync(), browse);
+}
+
+#[test]
+fn a_binding_with_a_follow_up_mode_runs_its_handler_then_switches() {
+    let _input = common::setup();
+    let browse = ScreenReaderMode::new("Browse");
+    let kb = binding(Key::Other('e'), Modifiers::ODILIA);
+    let ran = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&ran);
+    block_on(add_keybind(kb.clone(), move || {
+        // the switch only comes once the handler has run
+        seen.lock().unwrap().push(get_sr_mode_sync());
+        async {}
+    }));
+    block_on(set_then_mode(&kb, Some(browse.clone())));
+    assert_eq!(block_on(then_mode(&kb)), Some(browse.clone()));
+    let before = get_sr_mode_sync();
+    assert_ne!(before, browse);
+
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyE]), [false, false]);
+    let notified = notification().expect("the binding did not fire");
+    block_on(run_keybind_func(&notified));
+    assert_eq!(*ran.lock().unwrap(), [before]);
+    assert_eq!(get_sr_mode_sync(), browse);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::{binding, block_on, chord, notification, press, release};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::{
+    events::{currently_pressed_modifiers, set_alt_gr_policy, set_odilia_modifier_key, AltGrPolicy},
+    keybinds::add_keybind,
+};
+use rdev::Key as RDevKey;
+
+#[test]
+fn both_sides_of_a_modifier_accumulate() {
+    let _input = common::setup();
+
+    press(RDevKey::ShiftLeft);
+    press(RDevKey::ShiftRight);
+    assert_eq!(currently_pressed_modifiers(), Modifiers::SHIFT_L | Modifiers::SHIFT_R);
+    release(RDevKey::ShiftRight);
+    assert_eq!(currently_pressed_modifiers(), Modifiers::SHIFT_L);
+    release(RDevKey::ShiftLeft);
+}
+
+#[test]
+fn a_binding_for_both_sides_needs_both_held() {
+    let _input = common::setup();
+    let kb = binding(Key::F5, Modifiers::SHIFT_L | Modifiers::SHIFT_R);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    assert_eq!(chord(&[RDevKey::ShiftLeft, RDevKey::F5]), [true, true]);
+    assert_eq!(notification(), None);
+    assert_eq!(chord(&[RDevKey::ShiftRight, RDevKey::F5]), [true, true]);
+    assert_eq!(notification(), None);
+    assert_eq!(chord(&[RDevKey::ShiftLeft, RDevKey::ShiftRight, RDevKey::F5]), [true, true, false]);
+    assert_eq!(notification(), Some(kb));
+}
+
+#[test]
+fn the_live_modifiers_include_the_odilia_key_and_each_side() {
+    let _input = common::setup();
+    press(RDevKey::CapsLock);
+    press(RDevKey::ShiftLeft);
+    assert_eq!(currently_pressed_modifiers(), Modifiers::ODILIA | Modifiers::SHIFT_L);
+    release(RDevKey::ShiftLeft);
+    release(RDevKey::CapsLock);
+
+    // once another key is the Odilia key, CapsLock is no modifier any more
+    set_odilia_modifier_key(RDevKey::Insert);
+    press(RDevKey::Insert);
+    press(RDevKey::ControlRight);
+    press(RDevKey::CapsLock);
+    assert_eq!(currently_pressed_modifiers(), Modifiers::ODILIA | Modifiers::CONTROL_R);
+    release(RDevKey::CapsLock);
+    release(RDevKey::ControlRight);
+    release(RDevKey::Insert);
+    assert_eq!(currently_pressed_modifiers(), Modifiers::NONE);
+}
+
+#[test]
+fn the_alt_gr_key_is_always_alt_gr() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('e'), Modifiers::ALT_R);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    assert_eq!(chord(&[RDevKey::AltGr, RDevKey::KeyE]), [true, false]);
+    assert_eq!(notification(), Some(kb.clone()));
+    set_alt_gr_policy(AltGrPolicy::CtrlAltIsAltGr);
+    assert_eq!(chord(&[RDevKey::AltGr, RDevKey::KeyE]), [true, false]);
+    assert_eq!(notification(), Some(kb));
+}
+
+#[test]
+fn left_control_and_alt_are_alt_gr_only_under_that_policy() {
+    let _input = common::setup();
+    let alt_gr = binding(Key::Other('q'), Modifiers::ALT_R);
+    block_on(add_keybind(alt_gr.clone(), || async {}));
+
+    assert_eq!(chord(&[RDevKey::ControlLeft, RDevKey::Alt, RDevKey::KeyQ]), [true, true, true]);
+    assert_eq!(notification(), None);
+    assert_eq!(currently_pressed_modifiers(), Modifiers::NONE);
+
+    set_alt_gr_policy(AltGrPolicy::CtrlAltIsAltGr);
+    press(RDevKey::ControlLeft);
+    press(RDevKey::Alt);
+    assert_eq!(currently_pressed_modifiers(), Modifiers::ALT_R);
+    assert!(!press(RDevKey::KeyQ));
+    release(RDevKey::KeyQ);
+    release(RDevKey::Alt);
+    release(RDevKey::ControlLeft);
+    assert_eq!(notification(), Some(alt_gr));
+
+    // the right Control key still makes a genuine Ctrl+Alt
+    press(RDevKey::ControlRight);
+    press(RDevKey::Alt);
+    assert_eq!(currently_pressed_modifiers(), Modifiers::CONTROL_R | Modifiers::ALT_L);
+    release(RDevKey::Alt);
+    release(RDevKey::ControlRight);
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::{notifications, push, unhandled_events};
+use odilia_input::events::{add_event_filter, enable_chord_events, recent_events, FilterDecision};
+use rdev::{Button, EventType};
+use tokio::sync::mpsc;
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[test]
+fn with_only_keys_monitored_mouse_events_are_neither_dispatched_nor_delivered() {
+    let _input = common::setup();
+    let filtered = Arc::new(AtomicUsize::new(0));
+    let count = Arc::clone(&filtered);
+    add_event_filter(Box::new(move |_| {
+        count.fetch_add(1, Ordering::SeqCst);
+        FilterDecision::Keep
+    }));
+    let (tx, mut chord_events) = mpsc::channel(8);
+    enable_chord_events(tx);
+    let unhandled = unhandled_events();
+    let recent = recent_events();
+
+    // they reach the rest of the system untouched
+    assert!(push(EventType::MouseMove { x: 10.0, y: 20.0 }));
+    assert!(push(EventType::ButtonPress(Button::Left)));
+    assert!(push(EventType::ButtonRelease(Button::Left)));
+    assert!(push(EventType::Wheel { delta_x: 0, delta_y: -1 }));
+
+    assert_eq!(filtered.load(Ordering::SeqCst), 0);
+    assert!(chord_events.try_recv().is_err());
+    assert!(unhandled.recv_timeout(Duration::from_millis(100)).is_err());
+    assert_eq!(notifications(), []);
+    assert_eq!(recent_events(), recent);
+}
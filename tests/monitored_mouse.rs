@@ -0,0 +1,27 @@
+mod common;
+
+use common::push;
+use odilia_input::events::{add_event_filter, EventKind, FilterDecision, InputConfig};
+use rdev::{Button, EventType};
+
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn monitored_mouse_moves_go_through_the_pipeline() {
+    let _input = common::setup_with(|| InputConfig {
+        monitored_events: vec![EventKind::KeyPress, EventKind::KeyRelease, EventKind::MouseMove],
+        ..InputConfig::default()
+    });
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let record = Arc::clone(&seen);
+    add_event_filter(Box::new(move |ev| {
+        record.lock().unwrap().push(ev.event_type);
+        FilterDecision::Keep
+    }));
+
+    let moved = EventType::MouseMove { x: 3.0, y: 4.0 };
+    assert!(push(moved));
+    // clicks are still left alone
+    assert!(push(EventType::ButtonPress(Button::Left)));
+    assert_eq!(*seen.lock().unwrap(), [moved]);
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::{block_on, chord, key_event, notification, notifications, press, release};
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::{
+    events::{classify, EventAction},
+    keybinds::add_keybind,
+};
+use rdev::Key as RDevKey;
+
+fn notifying(key: char, consume: bool) -> KeyBinding {
+    KeyBinding {
+        key: Some(Key::Other(key)),
+        mods: Modifiers::ODILIA,
+        repeat: 1,
+        consume,
+        notify: true,
+        mode: None,
+    }
+}
+
+#[test]
+fn a_notify_binding_is_sent_once_and_passed_through() {
+    let _input = common::setup();
+    let kb = notifying('o', false);
+    block_on(add_keybind(kb.clone(), || async {}));
+    assert_eq!(classify(&key_event(Key::Other('o'), Modifiers::ODILIA)), (EventAction::Notify, Some(kb.clone())));
+
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyO]), [false, true]);
+    assert_eq!(notifications(), [kb]);
+}
+
+#[test]
+fn a_consumed_notify_binding_is_sent_once() {
+    let _input = common::setup();
+    let kb = notifying('p', true);
+    block_on(add_keybind(kb.clone(), || async {}));
+    assert_eq!(classify(&key_event(Key::Other('p'), Modifiers::ODILIA)), (EventAction::NotifyAndConsume, Some(kb.clone())));
+
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyP]), [false, false]);
+    assert_eq!(notifications(), [kb]);
+}
+
+#[test]
+fn auto_repeat_is_not_notified_but_a_re_press_is() {
+    let _input = common::setup();
+    let kb = notifying('j', true);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    press(RDevKey::CapsLock);
+    // the OS repeats a held key as further presses
+    for _ in 0..3 {
+        press(RDevKey::KeyJ);
+    }
+    release(RDevKey::KeyJ);
+    assert_eq!(notifications(), std::slice::from_ref(&kb));
+
+    assert!(!press(RDevKey::KeyJ));
+    release(RDevKey::KeyJ);
+    assert!(!press(RDevKey::KeyJ));
+    release(RDevKey::KeyJ);
+    release(RDevKey::CapsLock);
+    assert_eq!(notifications(), [kb.clone(), kb]);
+}
+
+#[test]
+fn draining_discards_only_what_was_already_sent() {
+    let _input = common::setup();
+    let kb = notifying('q', true);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    for _ in 0..3 {
+        chord(&[RDevKey::CapsLock, RDevKey::KeyQ]);
+    }
+    assert_eq!(common::drain(), 3);
+    assert_eq!(notification(), None);
+
+    chord(&[RDevKey::CapsLock, RDevKey::KeyQ]);
+    assert_eq!(notification(), Some(kb));
+}
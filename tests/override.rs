@@ -0,0 +1,111 @@
+mod common;
+
+use common::{binding, block_on};
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::keybinds::{add_keybind, override_keybind, remove_keybind, trigger, NotBound};
+
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// A handler counting how often it ran in `runs`.
+fn counter(runs: &Arc<AtomicUsize>) -> impl Fn() -> std::future::Ready<()> + Send + Sync + 'static {
+    let runs = Arc::clone(runs);
+    move || {
+        runs.fetch_add(1, Ordering::SeqCst);
+        std::future::ready(())
+    }
+}
+
+fn runs(counter: &Arc<AtomicUsize>) -> usize {
+    counter.load(Ordering::SeqCst)
+}
+
+fn bound(key: char) -> KeyBinding {
+    binding(Key::Other(key), Modifiers::ODILIA)
+}
+
+#[test]
+fn override_fires_while_held_and_the_original_is_restored_on_drop() {
+    let _input = common::setup();
+    let kb = bound('o');
+    let (original, temporary) = (Arc::default(), Arc::default());
+    block_on(add_keybind(kb.clone(), counter(&original)));
+
+    let guard = block_on(override_keybind(kb.clone(), counter(&temporary)));
+    block_on(trigger(&kb)).unwrap();
+    assert_eq!((runs(&original), runs(&temporary)), (0, 1));
+
+    drop(guard);
+    block_on(trigger(&kb)).unwrap();
+    assert_eq!((runs(&original), runs(&temporary)), (1, 1));
+    block_on(remove_keybind(kb));
+}
+
+#[test]
+fn overriding_an_unbound_key_unbinds_it_again() {
+    let _input = common::setup();
+    let kb = bound('u');
+    let guard = block_on(override_keybind(kb.clone(), || async {}));
+    assert_eq!(block_on(trigger(&kb)), Ok(()));
+    drop(guard);
+    assert_eq!(block_on(trigger(&kb)), Err(NotBound(kb)));
+}
+
+#[test]
+fn dropping_inside_async_code_restores_before_the_next_await() {
+    let _input = common::setup();
+    let kb = bound('a');
+    let (original, first, second) = (Arc::default(), Arc::default(), Arc::default());
+    block_on(async {
+        add_keybind(kb.clone(), counter(&original)).await;
+        let outer = override_keybind(kb.clone(), counter(&first)).await;
+        let inner = override_keybind(kb.clone(), counter(&second)).await;
+        drop(inner);
+        trigger(&kb).await.unwrap();
+        drop(outer);
+        trigger(&kb).await.unwrap();
+        remove_keybind(kb.clone()).await;
+    });
+    assert_eq!((runs(&original), runs(&first), runs(&second)), (1, 1, 0));
+}
+
+#[test]
+fn original_is_restored_when_a_panic_unwinds_past_the_guard() {
+    let _input = common::setup();
+    let kb = bound('p');
+    let original = Arc::default();
+    block_on(add_keybind(kb.clone(), counter(&original)));
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let _guard = block_on(override_keybind(kb.clone(), || async {}));
+        panic!("the modal dialog crashed");
+    }));
+    assert!(result.is_err());
+    block_on(trigger(&kb)).unwrap();
+    assert_eq!(runs(&original), 1);
+    block_on(remove_keybind(kb));
+}
+
+#[test]
+fn original_is_restored_when_a_task_holding_the_guard_panics() {
+    let _input = common::setup();
+    let kb = bound('t');
+    let original = Arc::default();
+    block_on(async {
+        add_keybind(kb.clone(), counter(&original)).await;
+        let binding = kb.clone();
+        let task = tokio::spawn(async move {
+            let _guard = override_keybind(binding, || async {}).await;
+            panic!("the modal dialog crashed");
+        });
+        assert!(task.await.unwrap_err().is_panic());
+        trigger(&kb).await.unwrap();
+        remove_keybind(kb.clone()).await;
+    });
+    assert_eq!(runs(&original), 1);
+}
@@ -0,0 +1,228 @@
+use odilia_common::{
+    input::{Key, KeyBinding, Modifiers},
+    modes::ScreenReaderMode,
+};
+use odilia_input::persist::{load_keybinds, save_keybinds, ActionRegistry, ConfigError, KeybindDocument};
+
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+fn keybinds() -> Vec<(KeyBinding, String)> {
+    vec![
+        (
+            KeyBinding {
+                key: Some(Key::Other('l')),
+                mods: Modifiers::ODILIA,
+                repeat: 1,
+                consume: true,
+                notify: true,
+                mode: None,
+            },
+            "read_line".to_string(),
+        ),
+        (
+            KeyBinding {
+                key: Some(Key::PageDown),
+                mods: Modifiers::ODILIA | Modifiers::SHIFT_L | Modifiers::CONTROL_R,
+                repeat: 2,
+                consume: false,
+                notify: true,
+                mode: Some(ScreenReaderMode::new("Browse")),
+            },
+            "next_page".to_string(),
+        ),
+        (
+            KeyBinding {
+                key: None,
+                mods: Modifiers::SHIFT_L,
+                repeat: 2,
+                consume: false,
+                notify: false,
+                mode: None,
+            },
+            "stop_speech".to_string(),
+        ),
+    ]
+}
+
+#[test]
+fn saved_keybinds_load_back_with_their_actions() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut actions = ActionRegistry::new();
+    for name in ["read_line", "next_page", "stop_speech"] {
+        let calls = Arc::clone(&calls);
+        actions.register_action(name, move || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async {}
+        });
+    }
+
+    let json = save_keybinds(&keybinds()).unwrap();
+    let loaded = load_keybinds(&json, &actions).unwrap();
+    let expected: HashSet<KeyBinding> = keybinds().into_iter().map(|(kb, _)| kb).collect();
+    assert_eq!(loaded.keys().cloned().collect::<HashSet<_>>(), expected);
+
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    for handler in loaded.values() {
+        runtime.block_on(handler());
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn modifiers_are_saved_by_name() {
+    let json = save_keybinds(&keybinds()[1..2]).unwrap();
+    let entries: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(entries[0]["mods"], serde_json::json!(["ODILIA", "CONTROL_R", "SHIFT_L"]));
+    assert_eq!(entries[0]["key"], "PageDown");
+    assert_eq!(entries[0]["mode"], "Browse");
+}
+
+fn read_line() -> ActionRegistry {
+    let mut actions = ActionRegistry::new();
+    actions.register_action("read_line", || async {});
+    actions
+}
+
+/// The error loading `json` fails with.
+fn load_error(json: &str) -> ConfigError {
+    match load_keybinds(json, &read_line()) {
+        Err(e) => e,
+        Ok(keybinds) => panic!("loaded {} bindings from a broken file", keybinds.len()),
+    }
+}
+
+const ENTRY: &str = r#"{ "action": "read_line", "key": "l", "mods": ["ODILIA"], "repeat": 1, "consume": true, "notify": true }"#;
+
+#[test]
+fn malformed_json_is_a_parse_error_with_its_location() {
+    let e = load_error("[\n  { \"action\": \"read_line\",\n    \"key\": }\n]");
+    assert!(matches!(e, ConfigError::Parse(_)), "{:?}", e);
+    assert_eq!(e.location(), Some((3, 12)));
+    assert_eq!(e.entry(), None);
+}
+
+#[test]
+fn an_unknown_action_is_an_error() {
+    let json = save_keybinds(&keybinds()).unwrap();
+    match load_error(&json) {
+        ConfigError::UnknownAction { name, entry } => {
+            assert_eq!(name, "next_page");
+            assert_eq!(entry, 1);
+        },
+        e => panic!("expected an unknown action, got {:?}", e),
+    }
+}
+
+#[test]
+fn an_unknown_key_is_an_error() {
+    let json = format!("[{}, {}]", ENTRY, ENTRY.replace(r#""l""#, r#""NoSuchKey""#));
+    match load_error(&json) {
+        ConfigError::UnknownKey { name, entry } => {
+            assert_eq!(name, "NoSuchKey");
+            assert_eq!(entry, 1);
+        },
+        e => panic!("expected an unknown key, got {:?}", e),
+    }
+}
+
+#[test]
+fn an_unknown_modifier_is_an_error() {
+    let json = format!("[{}]", ENTRY.replace("ODILIA", "HYPER"));
+    match load_error(&json) {
+        ConfigError::BadModifier { token, entry } => {
+            assert_eq!(token, "HYPER");
+            assert_eq!(entry, 0);
+        },
+        e => panic!("expected an unknown modifier, got {:?}", e),
+    }
+}
+
+#[test]
+fn binding_the_same_keys_twice_is_an_error() {
+    let json = format!("[{}, {}]", ENTRY, ENTRY);
+    match load_error(&json) {
+        ConfigError::DuplicateBinding { binding, entry } => {
+            assert_eq!(binding.key, Some(Key::Other('l')));
+            assert_eq!(entry, 1);
+        },
+        e => panic!("expected a duplicate binding, got {:?}", e),
+    }
+}
+
+#[test]
+fn a_broken_toml_file_is_an_error() {
+    assert!(matches!(KeybindDocument::parse("[[keybind]\naction = "), Err(ConfigError::Toml(_))));
+
+    let doc = KeybindDocument::parse("[[keybind]]\naction = \"read_line\"\nkey = \"l\"\nconsume = true\nnotify = true\n").unwrap();
+    match doc.load(&read_line()) {
+        Err(ConfigError::BadField { field, entry }) => {
+            assert_eq!(field, "repeat");
+            assert_eq!(entry, 0);
+        },
+        other => panic!("expected a missing field, got {:?}", other.map(|kbs| kbs.len())),
+    }
+}
+
+const USER_FILE: &str = r#"# My keybindings
+# (edited by hand)
+
+# Say the current line
+[[keybind]]
+action = "read_line"
+key = "l"
+mods = ["ODILIA"]   # CapsLock
+repeat = 1
+consume = true
+notify = true
+
+# Say the current word
+[[keybind]]
+action = "read_word"
+key = "w"
+mods = ["ODILIA"]
+repeat = 1
+consume = true
+notify = true
+"#;
+
+#[test]
+fn changing_one_binding_keeps_the_rest_of_the_file() {
+    let mut doc = KeybindDocument::parse(USER_FILE).unwrap();
+    let read_word = KeyBinding {
+        key: Some(Key::Other('k')),
+        mods: Modifiers::ODILIA,
+        repeat: 1,
+        consume: true,
+        notify: true,
+        mode: None,
+    };
+    doc.set_binding("read_word", &read_word);
+    let saved = doc.to_string();
+
+    // everything before the changed binding is untouched, comments included
+    let unchanged = USER_FILE.find("[[keybind]]\naction = \"read_word\"").unwrap();
+    assert_eq!(saved[..unchanged], USER_FILE[..unchanged]);
+    assert_eq!(saved.replace("key = \"k\"", "key = \"w\""), USER_FILE);
+
+    let entries = KeybindDocument::parse(&saved).unwrap().entries().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].to_keybinding().unwrap(), read_word);
+}
+
+#[test]
+fn removing_one_binding_keeps_the_rest_of_the_file() {
+    let mut doc = KeybindDocument::parse(USER_FILE).unwrap();
+    assert!(doc.remove_binding("read_line"));
+    assert!(!doc.remove_binding("read_line"));
+
+    // the comments heading the file stay, those of the removed binding go with it
+    let (header, rest) = USER_FILE.split_at(USER_FILE.find("# Say the current line").unwrap());
+    let read_word = &rest[rest.find("# Say the current word").unwrap()..];
+    assert_eq!(doc.to_string(), format!("{}{}", header, read_word));
+}
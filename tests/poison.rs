@@ -0,0 +1,55 @@
+//! A panic on the input monitoring thread poisons the locks it held; the events after it must still
+//! be handled.
+mod common;
+
+use common::{binding, block_on, chord, notification, press, release};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::{
+    events::{add_event_filter, clear_event_filters, FilterDecision},
+    keybinds::{add_keybind, set_mode_provider},
+};
+use rdev::{EventType::KeyPress, Key as RDevKey};
+
+use std::panic::{self, AssertUnwindSafe};
+
+fn panics(f: impl FnOnce() -> bool) -> bool {
+    panic::catch_unwind(AssertUnwindSafe(f)).is_err()
+}
+
+#[test]
+fn input_recovers_from_a_panic_while_matching() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('p'), Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    // the mode is looked up while the key state and the keymap are locked
+    block_on(set_mode_provider(Some(Box::new(|| panic!("no mode")))));
+    press(RDevKey::CapsLock);
+    assert!(panics(|| press(RDevKey::KeyP)));
+    release(RDevKey::KeyP);
+    release(RDevKey::CapsLock);
+    block_on(set_mode_provider(None));
+
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyP]), [false, false]);
+    assert_eq!(notification(), Some(kb));
+}
+
+#[test]
+fn input_recovers_from_a_panicking_event_filter() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('q'), Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    add_event_filter(Box::new(|ev| match ev.event_type {
+        KeyPress(RDevKey::KeyQ) => panic!("broken filter"),
+        _ => FilterDecision::Keep,
+    }));
+    press(RDevKey::CapsLock);
+    assert!(panics(|| press(RDevKey::KeyQ)));
+    release(RDevKey::KeyQ);
+    release(RDevKey::CapsLock);
+    clear_event_filters();
+
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyQ]), [false, false]);
+    assert_eq!(notification(), Some(kb));
+}
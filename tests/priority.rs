@@ -0,0 +1,76 @@
+mod common;
+
+use common::{binding, block_on, chord, key_event, notification};
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::keybinds::{add_keybind, add_keybind_with_priority, keyevent_match_sync};
+use rdev::Key as RDevKey;
+
+/// `kb`, passing the key through instead of consuming it.
+fn passing(kb: &KeyBinding) -> KeyBinding {
+    KeyBinding {
+        consume: false,
+        ..kb.clone()
+    }
+}
+
+#[test]
+fn the_highest_priority_binding_wins_every_time() {
+    let _input = common::setup();
+    let high = binding(Key::Other('h'), Modifiers::ODILIA);
+    let low = passing(&high);
+    block_on(add_keybind_with_priority(high.clone(), 5, || async {}));
+    block_on(add_keybind_with_priority(low, -5, || async {}));
+
+    for _ in 0..20 {
+        assert_eq!(keyevent_match_sync(&key_event(Key::Other('h'), Modifiers::ODILIA)), Some(high.clone()));
+    }
+}
+
+#[test]
+fn an_exact_modifier_match_wins_over_a_partial_one() {
+    let _input = common::setup();
+    let exact = binding(Key::Other('e'), Modifiers::ODILIA | Modifiers::SHIFT_L);
+    let partial = binding(Key::Other('e'), Modifiers::ODILIA);
+    block_on(add_keybind(partial.clone(), || async {}));
+    block_on(add_keybind(exact.clone(), || async {}));
+
+    for _ in 0..20 {
+        assert_eq!(keyevent_match_sync(&key_event(Key::Other('e'), Modifiers::ODILIA | Modifiers::SHIFT_L)), Some(exact.clone()));
+        assert_eq!(keyevent_match_sync(&key_event(Key::Other('e'), Modifiers::ODILIA)), Some(partial.clone()));
+    }
+}
+
+#[test]
+fn each_event_fires_its_most_specific_binding() {
+    let _input = common::setup();
+    let plain = binding(Key::Other('k'), Modifiers::NONE);
+    let odilia = binding(Key::Other('k'), Modifiers::ODILIA);
+    let odilia_shift = binding(Key::Other('k'), Modifiers::ODILIA | Modifiers::SHIFT_L);
+    for kb in [&odilia_shift, &plain, &odilia] {
+        block_on(add_keybind(kb.clone(), || async {}));
+    }
+
+    assert_eq!(chord(&[RDevKey::KeyK]), [false]);
+    assert_eq!(notification(), Some(plain));
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyK]), [false, false]);
+    assert_eq!(notification(), Some(odilia.clone()));
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::ShiftLeft, RDevKey::KeyK]), [false, true, false]);
+    assert_eq!(notification(), Some(odilia_shift));
+    // without a binding for these exact modifiers, the one needing the most of them wins
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::ControlLeft, RDevKey::KeyK]), [false, true, false]);
+    assert_eq!(notification(), Some(odilia));
+}
+
+#[test]
+fn among_partial_matches_the_one_needing_most_modifiers_wins() {
+    let _input = common::setup();
+    let odilia = binding(Key::Other('m'), Modifiers::ODILIA);
+    let odilia_shift = binding(Key::Other('m'), Modifiers::ODILIA | Modifiers::SHIFT_L);
+    block_on(add_keybind(odilia.clone(), || async {}));
+    block_on(add_keybind(odilia_shift.clone(), || async {}));
+
+    let held = Modifiers::ODILIA | Modifiers::SHIFT_L | Modifiers::ALT_L;
+    for _ in 0..20 {
+        assert_eq!(keyevent_match_sync(&key_event(Key::Other('m'), held)), Some(odilia_shift.clone()));
+    }
+}
@@ -0,0 +1,16 @@
+mod common;
+
+use odilia_common::input::KeyBinding;
+use odilia_input::events::{estimated_queue_bytes, queue_capacity, InputConfig};
+
+const CAPACITY: usize = 32;
+
+#[test]
+fn the_reported_footprint_tracks_the_configured_capacity() {
+    let _input = common::setup_with(|| InputConfig {
+        queue_capacity: CAPACITY,
+        ..InputConfig::default()
+    });
+    assert_eq!(queue_capacity(), CAPACITY);
+    assert_eq!(estimated_queue_bytes(), CAPACITY * std::mem::size_of::<KeyBinding>());
+}
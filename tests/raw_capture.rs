@@ -0,0 +1,51 @@
+mod common;
+
+use common::{binding, block_on, chord, key_event, notification};
+use odilia_common::input::{Key, KeyEvent, Modifiers};
+use odilia_input::{
+    events::{begin_raw_capture, end_raw_capture, is_raw_capturing},
+    keybinds::add_keybind,
+};
+use rdev::Key as RDevKey;
+use tokio::sync::mpsc::{self, error::TryRecvError};
+
+fn captured(rx: &mut mpsc::Receiver<KeyEvent>) -> Vec<KeyEvent> {
+    std::iter::from_fn(|| rx.try_recv().ok()).collect()
+}
+
+#[test]
+fn while_capturing_every_key_goes_to_the_sender_and_none_fires() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('r'), Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+    let (tx, mut rx) = mpsc::channel(8);
+    begin_raw_capture(tx);
+
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyR]), [false, false]);
+    assert_eq!(chord(&[RDevKey::KeyX]), [false]);
+    assert_eq!(notification(), None);
+    assert_eq!(
+        captured(&mut rx),
+        [key_event(Key::Other('r'), Modifiers::ODILIA), key_event(Key::Other('x'), Modifiers::NONE)]
+    );
+
+    end_raw_capture();
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyR]), [false, false]);
+    assert_eq!(notification(), Some(kb));
+    assert_eq!(chord(&[RDevKey::KeyX]), [true]);
+    assert!(captured(&mut rx).is_empty());
+}
+
+#[test]
+fn escape_ends_the_capture() {
+    let _input = common::setup();
+    let (tx, mut rx) = mpsc::channel(8);
+    begin_raw_capture(tx);
+
+    assert_eq!(chord(&[RDevKey::KeyQ]), [false]);
+    assert_eq!(chord(&[RDevKey::Escape]), [false]);
+    assert!(!is_raw_capturing());
+    assert_eq!(rx.try_recv(), Ok(key_event(Key::Other('q'), Modifiers::NONE)));
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(chord(&[RDevKey::KeyQ]), [true]);
+}
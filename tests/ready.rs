@@ -0,0 +1,27 @@
+mod common;
+
+use common::{binding, block_on, chord, notification};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::{events::backend_ready, keybinds::add_keybind};
+use rdev::Key as RDevKey;
+
+use std::time::Duration;
+
+/// Wait for [`backend_ready`], giving up after a while.
+fn wait_until_ready() -> bool {
+    block_on(async { tokio::time::timeout(Duration::from_secs(5), backend_ready()).await.is_ok() })
+}
+
+#[test]
+fn events_injected_once_the_backend_is_ready_are_not_missed() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('w'), Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    assert!(wait_until_ready(), "the backend never became ready");
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyW]), [false, false]);
+    assert_eq!(notification(), Some(kb));
+
+    // once ready, it stays ready
+    assert!(wait_until_ready(), "the backend is no longer ready");
+}
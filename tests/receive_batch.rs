@@ -0,0 +1,52 @@
+mod common;
+
+use common::{binding, block_on, chord, notification, notification_batch};
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::{
+    events::{set_observe_only, EventAction},
+    keybinds::add_keybind,
+};
+use rdev::Key as RDevKey;
+
+#[test]
+fn a_batch_drains_up_to_max_notifications_in_order() {
+    let _input = common::setup();
+    let keys = [(RDevKey::KeyA, 'a'), (RDevKey::KeyB, 'b'), (RDevKey::KeyC, 'c'), (RDevKey::KeyD, 'd')];
+    let bindings: Vec<KeyBinding> = keys
+        .iter()
+        .map(|&(_, c)| KeyBinding {
+            consume: c != 'b',
+            ..binding(Key::Other(c), Modifiers::ODILIA)
+        })
+        .collect();
+    for kb in &bindings {
+        block_on(add_keybind(kb.clone(), || async {}));
+    }
+    for &(key, _) in &keys {
+        chord(&[RDevKey::CapsLock, key]);
+    }
+
+    assert_eq!(
+        notification_batch(3),
+        [
+            (bindings[0].clone(), EventAction::NotifyAndConsume),
+            (bindings[1].clone(), EventAction::Notify),
+            (bindings[2].clone(), EventAction::NotifyAndConsume),
+        ]
+    );
+    // the rest stays for the next batch
+    assert_eq!(notification_batch(3), [(bindings[3].clone(), EventAction::NotifyAndConsume)]);
+    assert_eq!(notification_batch(3), []);
+}
+
+#[test]
+fn a_batch_reports_what_the_binding_asks_for_even_in_observe_only_mode() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('e'), Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+    set_observe_only(true);
+
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyE]), [false, true]);
+    assert_eq!(notification_batch(8), [(kb, EventAction::NotifyAndConsume)]);
+    assert_eq!(notification(), None);
+}
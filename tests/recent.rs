@@ -0,0 +1,23 @@
+mod common;
+
+use common::{chord, key_event};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::events::{recent_events, InputConfig};
+use rdev::Key as RDevKey;
+
+#[test]
+fn the_most_recent_events_are_kept_oldest_first() {
+    let _input = common::setup_with(|| InputConfig {
+        recent_events: 3,
+        redact_recent_events: false,
+        ..InputConfig::default()
+    });
+
+    for key in [RDevKey::KeyA, RDevKey::KeyB, RDevKey::KeyC, RDevKey::KeyD] {
+        chord(&[key]);
+    }
+    assert_eq!(
+        recent_events(),
+        ['b', 'c', 'd'].map(|c| key_event(Key::Other(c), Modifiers::NONE))
+    );
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::{binding, key_event, notification, press, push, release};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::{
+    backend::MockBackend,
+    events::{remap_key, unmap_key, RemapError},
+};
+use rdev::{
+    EventType::{self, KeyPress, KeyRelease},
+    Key as RDevKey,
+};
+
+use std::time::{Duration, Instant};
+
+/// Wait up to a second for `count` simulated events; they are simulated on a thread of their own.
+fn simulated(count: usize) -> Vec<EventType> {
+    let deadline = Instant::now() + Duration::from_secs(1);
+    let mut events = Vec::new();
+    while events.len() < count && Instant::now() < deadline {
+        events.extend(MockBackend.take_simulated());
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    events
+}
+
+#[test]
+fn a_remapped_key_is_consumed_and_its_target_simulated() {
+    let _input = common::setup();
+    let from = binding(Key::Other('a'), Modifiers::NONE);
+    remap_key(from.clone(), key_event(Key::Other('b'), Modifiers::NONE)).unwrap();
+
+    assert!(!press(RDevKey::KeyA));
+    // auto-repeat is consumed too, without simulating anything
+    assert!(!press(RDevKey::KeyA));
+    assert!(!release(RDevKey::KeyA));
+    let target = [KeyPress(RDevKey::KeyB), KeyRelease(RDevKey::KeyB)];
+    assert_eq!(simulated(3), target);
+
+    // the OS feeds the simulated events back in; they go through untouched
+    for event_type in target {
+        assert!(push(event_type));
+    }
+    assert_eq!(notification(), None);
+
+    assert!(unmap_key(&from));
+    assert!(press(RDevKey::KeyA));
+    release(RDevKey::KeyA);
+    assert_eq!(simulated(1), []);
+}
+
+#[test]
+fn modifiers_of_the_target_are_simulated_around_it() {
+    let _input = common::setup();
+    let from = binding(Key::F9, Modifiers::NONE);
+    remap_key(from.clone(), key_event(Key::Other('z'), Modifiers::CONTROL_L)).unwrap();
+
+    assert!(!press(RDevKey::F9));
+    assert!(!release(RDevKey::F9));
+    let target = [
+        KeyPress(RDevKey::ControlLeft),
+        KeyPress(RDevKey::KeyZ),
+        KeyRelease(RDevKey::KeyZ),
+        KeyRelease(RDevKey::ControlLeft),
+    ];
+    assert_eq!(simulated(target.len()), target);
+    for event_type in target {
+        push(event_type);
+    }
+    unmap_key(&from);
+}
+
+#[test]
+fn remapping_into_a_loop_or_an_untypable_key_is_refused() {
+    let _input = common::setup();
+    let (c, d) = (Key::Other('c'), Key::Other('d'));
+    remap_key(binding(c.clone(), Modifiers::NONE), key_event(d.clone(), Modifiers::NONE)).unwrap();
+
+    assert_eq!(
+        remap_key(binding(d.clone(), Modifiers::NONE), key_event(c.clone(), Modifiers::NONE)),
+        Err(RemapError::Loop)
+    );
+    assert_eq!(
+        remap_key(binding(d, Modifiers::NONE), key_event(Key::Other('e'), Modifiers::ODILIA)),
+        Err(RemapError::Unsimulatable)
+    );
+    assert!(unmap_key(&binding(c, Modifiers::NONE)));
+}
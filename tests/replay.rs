@@ -0,0 +1,44 @@
+mod common;
+
+use common::{binding, block_on, chord, notification, notifications};
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::{events::replay_last_event, keybinds::add_keybind};
+use rdev::Key as RDevKey;
+
+#[test]
+fn a_replay_fires_the_same_binding_again() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('r'), Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    chord(&[RDevKey::CapsLock, RDevKey::KeyR]);
+    assert_eq!(notification(), Some(kb.clone()));
+    assert_eq!(replay_last_event(), Some(kb.clone()));
+    assert_eq!(notification(), Some(kb.clone()));
+    assert_eq!(replay_last_event(), Some(kb.clone()));
+    assert_eq!(notification(), Some(kb));
+}
+
+#[test]
+fn a_replay_is_sent_even_for_a_binding_which_does_not_notify() {
+    let _input = common::setup();
+    let kb = KeyBinding {
+        notify: false,
+        ..binding(Key::Other('t'), Modifiers::ODILIA)
+    };
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    chord(&[RDevKey::CapsLock, RDevKey::KeyT]);
+    assert_eq!(notification(), None);
+    assert_eq!(replay_last_event(), Some(kb.clone()));
+    assert_eq!(notification(), Some(kb));
+}
+
+#[test]
+fn replaying_an_unbound_key_does_nothing() {
+    let _input = common::setup();
+
+    chord(&[RDevKey::KeyZ]);
+    assert_eq!(replay_last_event(), None);
+    assert_eq!(notifications(), []);
+}
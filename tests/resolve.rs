@@ -0,0 +1,45 @@
+mod common;
+
+use common::{binding, block_on, key_event};
+use odilia_common::{
+    input::{Key, KeyBinding, Modifiers},
+    modes::ScreenReaderMode,
+};
+use odilia_input::keybinds::{add_keybind, add_keybind_described, resolve_binding, set_sr_mode};
+
+#[test]
+fn a_matching_binding_resolves_with_its_description() {
+    let _input = common::setup();
+    let described = binding(Key::Other('l'), Modifiers::ODILIA);
+    block_on(add_keybind_described(described.clone(), "Read the current line", "Reading", || async {}));
+    let plain = binding(Key::Other('t'), Modifiers::ODILIA);
+    block_on(add_keybind(plain.clone(), || async {}));
+
+    assert_eq!(
+        block_on(resolve_binding(&key_event(Key::Other('l'), Modifiers::ODILIA))),
+        Some((described, Some("Read the current line".to_string())))
+    );
+    assert_eq!(block_on(resolve_binding(&key_event(Key::Other('t'), Modifiers::ODILIA))), Some((plain, None)));
+}
+
+#[test]
+fn an_unbound_key_resolves_to_nothing() {
+    let _input = common::setup();
+    assert_eq!(block_on(resolve_binding(&key_event(Key::Other('z'), Modifiers::ODILIA | Modifiers::ALT_R))), None);
+}
+
+#[test]
+fn a_mode_scoped_binding_only_resolves_in_its_mode() {
+    let _input = common::setup();
+    let browse = ScreenReaderMode::new("Browse");
+    let kb = KeyBinding {
+        mode: Some(browse.clone()),
+        ..binding(Key::Other('h'), Modifiers::NONE)
+    };
+    block_on(add_keybind_described(kb.clone(), "Next heading", "Navigation", || async {}));
+    let kbm = key_event(Key::Other('h'), Modifiers::NONE);
+
+    assert_eq!(block_on(resolve_binding(&kbm)), None);
+    block_on(set_sr_mode(browse)).unwrap();
+    assert_eq!(block_on(resolve_binding(&kbm)), Some((kb, Some("Next heading".to_string()))));
+}
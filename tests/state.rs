@@ -0,0 +1,103 @@
+mod common;
+
+use common::{binding, block_on, chord, key_event, notification, press, release};
+use odilia_common::{
+    input::{Key, Modifiers},
+    modes::ScreenReaderMode,
+};
+use odilia_input::{
+    events::{
+        currently_pressed, currently_pressed_modifiers, get_odilia_modifier_key, recent_events, restore_state, set_odilia_modifier_key,
+        snapshot_state,
+    },
+    keybinds::{add_keybind, get_sr_mode_sync, set_sr_mode_sync},
+};
+use rdev::Key as RDevKey;
+
+#[test]
+fn pressed_keys_snapshot_reflects_a_held_chord() {
+    let _input = common::setup();
+    block_on(add_keybind(binding(Key::Other('k'), Modifiers::ODILIA), || async {}));
+
+    press(RDevKey::CapsLock);
+    press(RDevKey::KeyK);
+    assert_eq!(currently_pressed(), [Key::Other('k')]);
+    assert_eq!(currently_pressed_modifiers(), Modifiers::ODILIA);
+
+    release(RDevKey::KeyK);
+    assert_eq!(currently_pressed(), []);
+    assert_eq!(currently_pressed_modifiers(), Modifiers::ODILIA);
+    release(RDevKey::CapsLock);
+    assert_eq!(currently_pressed_modifiers(), Modifiers::NONE);
+}
+
+#[test]
+fn a_bare_odilia_key_is_consumed_but_held() {
+    let _input = common::setup();
+
+    assert!(!press(RDevKey::CapsLock));
+    assert_eq!(currently_pressed_modifiers(), Modifiers::ODILIA);
+    assert!(!release(RDevKey::CapsLock));
+    assert_eq!(currently_pressed_modifiers(), Modifiers::NONE);
+}
+
+#[test]
+fn characters_are_redacted_from_recent_events_by_default() {
+    let _input = common::setup();
+
+    chord(&[RDevKey::KeyS]);
+    chord(&[RDevKey::Home]);
+    let recent = recent_events();
+    assert_eq!(
+        recent[recent.len() - 2..],
+        [key_event(Key::Other('*'), Modifiers::NONE), key_event(Key::Home, Modifiers::NONE)]
+    );
+}
+
+#[test]
+fn the_odilia_key_can_be_switched_while_held() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('w'), Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    press(RDevKey::CapsLock);
+    assert_eq!(currently_pressed_modifiers(), Modifiers::ODILIA);
+    set_odilia_modifier_key(RDevKey::ScrollLock);
+    assert_eq!(get_odilia_modifier_key(), RDevKey::ScrollLock);
+    // the old key no longer holds the modifier down
+    assert_eq!(currently_pressed_modifiers(), Modifiers::NONE);
+    release(RDevKey::CapsLock);
+
+    assert_eq!(chord(&[RDevKey::ScrollLock, RDevKey::KeyW]), [false, false]);
+    assert_eq!(notification(), Some(kb));
+    chord(&[RDevKey::CapsLock, RDevKey::KeyW]);
+    assert_eq!(notification(), None);
+}
+
+#[test]
+fn matching_resumes_from_a_restored_mid_chord_state() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('m'), Modifiers::ODILIA | Modifiers::SHIFT_L);
+    block_on(add_keybind(kb.clone(), || async {}));
+    let mode = get_sr_mode_sync();
+
+    press(RDevKey::CapsLock);
+    press(RDevKey::ShiftLeft);
+    let saved = snapshot_state();
+
+    // let go of everything, type something else and switch modes
+    release(RDevKey::ShiftLeft);
+    release(RDevKey::CapsLock);
+    chord(&[RDevKey::KeyX]);
+    set_sr_mode_sync(ScreenReaderMode::new("Elsewhere")).unwrap();
+    assert_eq!(currently_pressed_modifiers(), Modifiers::NONE);
+
+    assert_eq!(restore_state(saved), Ok(()));
+    assert_eq!(get_sr_mode_sync(), mode);
+    assert_eq!(currently_pressed_modifiers(), Modifiers::ODILIA | Modifiers::SHIFT_L);
+    assert!(!press(RDevKey::KeyM));
+    assert_eq!(notification(), Some(kb));
+    release(RDevKey::KeyM);
+    release(RDevKey::ShiftLeft);
+    release(RDevKey::CapsLock);
+}
@@ -0,0 +1,56 @@
+//! A host without a tokio runtime of its own.
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::{
+    backend::MockBackend,
+    events::{init_sync, init_sync_with_backend, InitError, InputConfig},
+    keybinds::{add_sync_keybind, run_keybind_func_sync},
+};
+use rdev::{
+    Event,
+    EventType::{self, KeyPress, KeyRelease},
+    Key as RDevKey,
+};
+
+use std::{
+    sync::mpsc,
+    time::{Duration, SystemTime},
+};
+
+fn push(event_type: EventType) -> bool {
+    MockBackend
+        .push_event(Event {
+            time: SystemTime::now(),
+            name: None,
+            event_type,
+        })
+        .is_some()
+}
+
+#[test]
+fn bindings_are_notified_and_dispatched_without_a_runtime() {
+    let mut rx = init_sync_with_backend(InputConfig::default(), Box::new(MockBackend)).unwrap();
+    assert!(matches!(init_sync(InputConfig::default()), Err(InitError::AlreadyInitialized)));
+
+    let kb = KeyBinding {
+        key: Some(Key::Other('s')),
+        mods: Modifiers::ODILIA,
+        repeat: 1,
+        consume: true,
+        notify: true,
+        mode: None,
+    };
+    let (ran, handled) = mpsc::channel();
+    assert!(add_sync_keybind(kb.clone(), move || ran.send(std::thread::current().id()).unwrap()));
+
+    assert!(!push(KeyPress(RDevKey::CapsLock)));
+    assert!(!push(KeyPress(RDevKey::KeyS)));
+    push(KeyRelease(RDevKey::KeyS));
+    push(KeyRelease(RDevKey::CapsLock));
+    let notified = rx.blocking_recv().unwrap();
+    assert_eq!(notified, kb);
+
+    run_keybind_func_sync(&notified);
+    // the handler runs on a thread of the input monitoring system, not this one
+    let handler_thread = handled.recv_timeout(Duration::from_secs(5)).expect("the handler never ran");
+    assert_ne!(handler_thread, std::thread::current().id());
+}
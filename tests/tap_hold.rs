@@ -0,0 +1,70 @@
+mod common;
+
+use common::{binding, block_on, notification, press, push_at, release};
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::keybinds::add_tap_hold_keybind;
+use rdev::{
+    EventType::{KeyPress, KeyRelease},
+    Key as RDevKey,
+};
+
+use std::{
+    sync::mpsc,
+    time::{Duration, SystemTime},
+};
+
+/// Bind `kb` as a tap/hold binding, returning a receiver told each time the hold handler runs.
+fn bind_tap_hold(kb: &KeyBinding, threshold: Duration) -> mpsc::Receiver<()> {
+    let (tx, rx) = mpsc::channel();
+    block_on(add_tap_hold_keybind(kb.clone(), threshold, || async {}, move || {
+        let _ = tx.send(());
+        async {}
+    }));
+    rx
+}
+
+#[test]
+fn a_quick_tap_fires_the_tap_action_on_release() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('t'), Modifiers::ODILIA);
+    let held = bind_tap_hold(&kb, Duration::from_secs(10));
+
+    let now = SystemTime::now();
+    push_at(KeyPress(RDevKey::CapsLock), now);
+    assert!(push_at(KeyPress(RDevKey::KeyT), now).is_none());
+    assert_eq!(notification(), None);
+    assert!(push_at(KeyRelease(RDevKey::KeyT), now + Duration::from_millis(50)).is_none());
+    assert_eq!(notification(), Some(kb));
+    push_at(KeyRelease(RDevKey::CapsLock), now);
+    assert!(held.recv_timeout(Duration::from_millis(100)).is_err());
+}
+
+#[test]
+fn releasing_after_the_threshold_fires_the_hold_action() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('h'), Modifiers::ODILIA);
+    let held = bind_tap_hold(&kb, Duration::from_secs(10));
+
+    let now = SystemTime::now();
+    push_at(KeyPress(RDevKey::CapsLock), now);
+    assert!(push_at(KeyPress(RDevKey::KeyH), now).is_none());
+    assert!(push_at(KeyRelease(RDevKey::KeyH), now + Duration::from_secs(11)).is_none());
+    push_at(KeyRelease(RDevKey::CapsLock), now);
+    assert!(held.recv_timeout(Duration::from_secs(1)).is_ok());
+    assert_eq!(notification(), None);
+}
+
+#[test]
+fn a_key_still_held_past_the_threshold_fires_the_hold_action() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('g'), Modifiers::ODILIA);
+    let held = bind_tap_hold(&kb, Duration::from_millis(50));
+
+    press(RDevKey::CapsLock);
+    assert!(!press(RDevKey::KeyG));
+    assert!(held.recv_timeout(Duration::from_secs(1)).is_ok());
+    assert!(!release(RDevKey::KeyG));
+    release(RDevKey::CapsLock);
+    assert_eq!(notification(), None);
+    assert!(held.recv_timeout(Duration::from_millis(100)).is_err());
+}
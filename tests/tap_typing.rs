@@ -0,0 +1,84 @@
+mod common;
+
+use common::{block_on, chord, notification, notifications, push_at};
+use odilia_common::input::{KeyBinding, Modifiers};
+use odilia_input::{events::set_modifier_tap_timing, keybinds::add_keybind};
+use rdev::{
+    EventType::{KeyPress, KeyRelease},
+    Key as RDevKey,
+};
+
+use std::time::{Duration, SystemTime};
+
+/// Bind a double tap of the left Shift key.
+fn double_shift() -> KeyBinding {
+    let kb = KeyBinding {
+        key: None,
+        mods: Modifiers::SHIFT_L,
+        repeat: 2,
+        consume: false,
+        notify: true,
+        mode: None,
+    };
+    block_on(add_keybind(kb.clone(), || async {}));
+    kb
+}
+
+/// Tap the left Shift key on its own at `at`, holding it for `held`.
+fn tap_shift(at: SystemTime, held: Duration) {
+    push_at(KeyPress(RDevKey::ShiftLeft), at);
+    push_at(KeyRelease(RDevKey::ShiftLeft), at + held);
+}
+
+#[test]
+fn two_bare_shift_taps_fire_the_double_tap() {
+    let _input = common::setup();
+    let kb = double_shift();
+
+    chord(&[RDevKey::ShiftLeft]);
+    chord(&[RDevKey::ShiftLeft]);
+    assert_eq!(notification(), Some(kb));
+}
+
+#[test]
+fn typing_capitals_does_not_fire_the_double_tap() {
+    let _input = common::setup();
+    double_shift();
+
+    chord(&[RDevKey::ShiftLeft, RDevKey::KeyH]);
+    chord(&[RDevKey::ShiftLeft, RDevKey::KeyI]);
+    // a tap followed by a capital is no double tap either
+    chord(&[RDevKey::ShiftLeft]);
+    chord(&[RDevKey::ShiftLeft, RDevKey::KeyJ]);
+    assert_eq!(notifications(), []);
+}
+
+#[test]
+fn taps_further_apart_than_the_window_are_no_double_tap() {
+    let _input = common::setup();
+    double_shift();
+    set_modifier_tap_timing(Duration::from_millis(100), None);
+
+    let start = SystemTime::now();
+    tap_shift(start, Duration::from_millis(20));
+    tap_shift(start + Duration::from_millis(300), Duration::from_millis(20));
+    assert_eq!(notifications(), []);
+}
+
+#[test]
+fn a_shift_held_too_long_is_no_tap() {
+    let _input = common::setup();
+    let kb = double_shift();
+    set_modifier_tap_timing(Duration::from_millis(400), Some(Duration::from_millis(150)));
+
+    let start = SystemTime::now();
+    tap_shift(start, Duration::from_millis(50));
+    tap_shift(start + Duration::from_millis(100), Duration::from_millis(500));
+    assert_eq!(notifications(), []);
+
+    // quick taps still count
+    let later = start + Duration::from_secs(2);
+    tap_shift(later, Duration::from_millis(50));
+    tap_shift(later + Duration::from_millis(100), Duration::from_millis(50));
+    assert_eq!(notification(), Some(kb));
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use common::{block_on, chord, notification, notification_within, notifications, push_at};
+use odilia_common::input::{KeyBinding, Modifiers};
+use odilia_input::{
+    events::{repeat_count, reset_repeat, set_modifier_tap_timing, set_repeat_policy, RepeatPolicy},
+    keybinds::add_keybind,
+};
+use rdev::{
+    EventType::{KeyPress, KeyRelease},
+    Key as RDevKey,
+};
+
+use std::time::{Duration, SystemTime};
+
+/// A binding for tapping `mods` on their own `repeat` times in a row.
+fn tap_binding(mods: Modifiers, repeat: u8) -> KeyBinding {
+    KeyBinding {
+        key: None,
+        mods,
+        repeat,
+        consume: true,
+        notify: true,
+        mode: None,
+    }
+}
+
+#[test]
+fn a_modifier_tap_passes_through_even_when_bound() {
+    let _input = common::setup();
+    let kb = tap_binding(Modifiers::SHIFT_L, 1);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    let now = SystemTime::now();
+    assert!(push_at(KeyPress(RDevKey::ShiftLeft), now).is_some());
+    assert!(push_at(KeyRelease(RDevKey::ShiftLeft), now).is_some());
+    assert_eq!(notification(), Some(kb));
+}
+
+#[test]
+fn a_double_tap_fires_on_the_second_release() {
+    let _input = common::setup();
+    let kb = tap_binding(Modifiers::SHIFT_R, 2);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    chord(&[RDevKey::ShiftRight]);
+    assert_eq!(notification(), None);
+    chord(&[RDevKey::ShiftRight]);
+    assert_eq!(notification(), Some(kb));
+}
+
+#[test]
+fn the_odilia_key_held_alone_is_a_tap() {
+    let _input = common::setup();
+    let kb = tap_binding(Modifiers::ODILIA, 1);
+    block_on(add_keybind(kb.clone(), || async {}));
+
+    chord(&[RDevKey::CapsLock]);
+    assert_eq!(notification(), Some(kb));
+}
+
+#[test]
+fn a_modifier_used_in_a_chord_is_not_a_tap() {
+    let _input = common::setup();
+    block_on(add_keybind(tap_binding(Modifiers::ALT_L, 1), || async {}));
+
+    assert_eq!(chord(&[RDevKey::Alt, RDevKey::KeyT]), [true, true]);
+    assert_eq!(notifications(), []);
+}
+
+/// Bind a single and a double tap of `mods`.
+fn bind_single_and_double(mods: Modifiers) -> (KeyBinding, KeyBinding) {
+    let (single, double) = (tap_binding(mods, 1), tap_binding(mods, 2));
+    block_on(add_keybind(single.clone(), || async {}));
+    block_on(add_keybind(double.clone(), || async {}));
+    (single, double)
+}
+
+#[test]
+fn by_default_every_tap_fires() {
+    let _input = common::setup();
+    let (single, double) = bind_single_and_double(Modifiers::CONTROL_L);
+
+    chord(&[RDevKey::ControlLeft]);
+    chord(&[RDevKey::ControlLeft]);
+    assert_eq!(notifications(), [single, double]);
+}
+
+#[test]
+fn with_exact_repeats_a_double_tap_only_fires_the_double_tap_binding() {
+    let _input = common::setup();
+    set_repeat_policy(RepeatPolicy::Exact);
+    let (_, double) = bind_single_and_double(Modifiers::META_R);
+
+    chord(&[RDevKey::MetaRight]);
+    chord(&[RDevKey::MetaRight]);
+    assert_eq!(notification(), Some(double));
+    assert_eq!(notification_within(Duration::from_secs(1)), None);
+}
+
+#[test]
+fn with_exact_repeats_a_single_tap_fires_once_the_window_passed() {
+    let _input = common::setup();
+    set_repeat_policy(RepeatPolicy::Exact);
+    let (single, _) = bind_single_and_double(Modifiers::META_L);
+
+    chord(&[RDevKey::MetaLeft]);
+    assert_eq!(notification(), None);
+    assert_eq!(notification_within(Duration::from_secs(1)), Some(single));
+}
+
+#[test]
+fn the_repeat_count_follows_rapid_taps() {
+    let _input = common::setup();
+    assert_eq!(repeat_count(Modifiers::ALT_R), 0);
+
+    chord(&[RDevKey::AltGr]);
+    assert_eq!(repeat_count(Modifiers::ALT_R), 1);
+    chord(&[RDevKey::AltGr]);
+    assert_eq!(repeat_count(Modifiers::ALT_R), 2);
+    // only the modifiers tapped last have a count
+    assert_eq!(repeat_count(Modifiers::SHIFT_L), 0);
+
+    reset_repeat();
+    assert_eq!(repeat_count(Modifiers::ALT_R), 0);
+    chord(&[RDevKey::AltGr]);
+    assert_eq!(repeat_count(Modifiers::ALT_R), 1);
+}
+
+#[test]
+fn the_repeat_count_resets_once_the_window_passed() {
+    let _input = common::setup();
+    let window = Duration::from_millis(50);
+    set_modifier_tap_timing(window, None);
+
+    chord(&[RDevKey::ControlRight]);
+    chord(&[RDevKey::ControlRight]);
+    assert_eq!(repeat_count(Modifiers::CONTROL_R), 2);
+    std::thread::sleep(window * 2);
+    assert_eq!(repeat_count(Modifiers::CONTROL_R), 0);
+
+    // a tap coming too late starts over
+    chord(&[RDevKey::ControlRight]);
+    assert_eq!(repeat_count(Modifiers::CONTROL_R), 1);
+}
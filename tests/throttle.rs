@@ -0,0 +1,50 @@
+mod common;
+
+use common::{binding, block_on, chord, notifications};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::keybinds::{add_keybind, set_keybind_throttle, InputManager};
+use rdev::Key as RDevKey;
+
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn mashing_a_throttled_binding_fires_it_once_per_interval() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('t'), Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+    block_on(set_keybind_throttle(&kb, Some(Duration::from_millis(300))));
+
+    for _ in 0..3 {
+        // throttled presses are consumed all the same
+        assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyT]), [false, false]);
+    }
+    assert_eq!(notifications(), std::slice::from_ref(&kb));
+
+    std::thread::sleep(Duration::from_millis(350));
+    chord(&[RDevKey::CapsLock, RDevKey::KeyT]);
+    assert_eq!(notifications(), std::slice::from_ref(&kb));
+
+    block_on(set_keybind_throttle(&kb, None));
+    chord(&[RDevKey::CapsLock, RDevKey::KeyT]);
+    chord(&[RDevKey::CapsLock, RDevKey::KeyT]);
+    assert_eq!(notifications().len(), 2);
+}
+
+#[test]
+fn the_interval_counts_from_the_last_fire() {
+    let _input = common::setup();
+    let manager = InputManager::new();
+    let kb = binding(Key::Other('u'), Modifiers::ODILIA);
+    let second = Duration::from_secs(1);
+    block_on(manager.set_keybind_throttle(&kb, Some(second)));
+
+    let start = SystemTime::now();
+    let at = |millis| start + Duration::from_millis(millis);
+    assert!(manager.throttle_allows_sync(&kb, at(0)));
+    assert!(!manager.throttle_allows_sync(&kb, at(500)));
+    assert!(!manager.throttle_allows_sync(&kb, at(999)));
+    assert!(manager.throttle_allows_sync(&kb, at(1000)));
+    // a rejected press doesn't restart the interval
+    assert!(!manager.throttle_allows_sync(&kb, at(1900)));
+    assert!(manager.throttle_allows_sync(&kb, at(2000)));
+}
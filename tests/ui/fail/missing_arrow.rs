@@ -0,0 +1,7 @@
+async fn read_line() {}
+
+fn main() {
+    let _ = odilia_input::keybinds! {
+        Odilia + 'k' read_line,
+    };
+}
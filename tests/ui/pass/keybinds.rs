@@ -0,0 +1,16 @@
+// Every modifier spelling and both kinds of key, naming nothing but odilia_input
+async fn read_line() {}
+async fn read_title() {}
+
+async fn setup() {
+    let _conflicts = odilia_input::keybinds! {
+        Odilia + 'k' => read_line,
+        Odilia + Control + Shift + F5 => read_title,
+        Ctrl + Alt + AltGr + Meta + Super + 'j' => read_line,
+    }
+    .await;
+}
+
+fn main() {
+    let _ = setup();
+}
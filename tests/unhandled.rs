@@ -0,0 +1,41 @@
+mod common;
+
+use common::{binding, block_on, chord, key_event, notification, unhandled_events};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::keybinds::{add_keybind, remove_unhandled_handler};
+use rdev::Key as RDevKey;
+
+use std::time::Duration;
+
+#[test]
+fn an_unbound_key_reaches_the_unhandled_handler_and_passes_through() {
+    let _input = common::setup();
+    let unhandled = unhandled_events();
+
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyZ]), [false, true]);
+    let kbm = unhandled.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(kbm, key_event(Key::Other('z'), Modifiers::ODILIA));
+    assert_eq!(notification(), None);
+}
+
+#[test]
+fn a_bound_key_does_not_reach_the_unhandled_handler() {
+    let _input = common::setup();
+    let kb = binding(Key::Other('y'), Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+    let unhandled = unhandled_events();
+
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::KeyY]), [false, false]);
+    assert_eq!(notification(), Some(kb));
+    assert!(unhandled.recv_timeout(Duration::from_millis(100)).is_err());
+}
+
+#[test]
+fn the_unhandled_handler_is_opt_in() {
+    let _input = common::setup();
+    let unhandled = unhandled_events();
+    block_on(remove_unhandled_handler());
+
+    assert_eq!(chord(&[RDevKey::KeyV]), [true]);
+    assert!(unhandled.recv_timeout(Duration::from_millis(100)).is_err());
+}
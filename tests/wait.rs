@@ -0,0 +1,38 @@
+mod common;
+
+use common::{binding, block_on, chord, key_event, notification, poll, start};
+use odilia_common::input::{Key, Modifiers};
+use odilia_input::keybinds::{add_keybind, wait_for, wait_for_timeout};
+use rdev::Key as RDevKey;
+
+use std::time::Duration;
+
+#[test]
+fn waiting_resolves_on_the_first_matching_event() {
+    let _input = common::setup();
+    let mut waiting = start(wait_for(|kbm| kbm.key == Some(Key::Down)));
+
+    assert_eq!(chord(&[RDevKey::KeyA]), [true]);
+    assert!(poll(&mut waiting).is_pending());
+    assert_eq!(chord(&[RDevKey::DownArrow]), [true]);
+    assert_eq!(block_on(waiting), key_event(Key::Down, Modifiers::NONE));
+}
+
+#[test]
+fn waiting_leaves_bindings_alone() {
+    let _input = common::setup();
+    let kb = binding(Key::Up, Modifiers::ODILIA);
+    block_on(add_keybind(kb.clone(), || async {}));
+    let waiting = start(wait_for(|kbm| kbm.key == Some(Key::Up)));
+
+    assert_eq!(chord(&[RDevKey::CapsLock, RDevKey::UpArrow]), [false, false]);
+    assert_eq!(notification(), Some(kb));
+    assert_eq!(block_on(waiting), key_event(Key::Up, Modifiers::ODILIA));
+}
+
+#[test]
+fn waiting_times_out_without_a_matching_event() {
+    let _input = common::setup();
+    let waiting = wait_for_timeout(|kbm| kbm.key == Some(Key::Left), Duration::from_millis(50));
+    assert_eq!(block_on(waiting), None);
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::{binding, block_on, chord, key_event, notifications};
+use odilia_common::input::{Key, KeyBinding, Modifiers};
+use odilia_input::keybinds::{add_keybind, would_consume, would_notify};
+use rdev::Key as RDevKey;
+
+/// What [`would_consume`] and [`would_notify`] predict for Control+`key`.
+fn predicted(key: char) -> (bool, bool) {
+    let kbm = key_event(Key::Other(key), Modifiers::CONTROL_L);
+    (would_consume(&kbm), would_notify(&kbm))
+}
+
+/// Whether pressing Control+`rdev_key` was consumed, and whether it notified.
+fn dispatched(rdev_key: RDevKey) -> (bool, bool) {
+    let passed = chord(&[RDevKey::ControlLeft, rdev_key]);
+    (!passed[1], !notifications().is_empty())
+}
+
+fn bind(key: char, consume: bool, notify: bool) {
+    let kb = KeyBinding {
+        consume,
+        notify,
+        ..binding(Key::Other(key), Modifiers::CONTROL_L)
+    };
+    block_on(add_keybind(kb, || async {}));
+}
+
+#[test]
+fn agrees_on_a_consuming_binding() {
+    let _input = common::setup();
+    bind('c', true, false);
+    assert_eq!(predicted('c'), (true, false));
+    assert_eq!(dispatched(RDevKey::KeyC), predicted('c'));
+}
+
+#[test]
+fn agrees_on_a_notifying_binding() {
+    let _input = common::setup();
+    bind('n', false, true);
+    assert_eq!(predicted('n'), (false, true));
+    assert_eq!(dispatched(RDevKey::KeyN), predicted('n'));
+}
+
+#[test]
+fn agrees_on_a_binding_which_does_both() {
+    let _input = common::setup();
+    bind('b', true, true);
+    assert_eq!(predicted('b'), (true, true));
+    assert_eq!(dispatched(RDevKey::KeyB), predicted('b'));
+}
+
+#[test]
+fn agrees_on_an_unbound_key() {
+    let _input = common::setup();
+    assert_eq!(predicted('u'), (false, false));
+    assert_eq!(dispatched(RDevKey::KeyU), predicted('u'));
+}